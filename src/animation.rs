@@ -0,0 +1,447 @@
+//! Runs the CSS transitions and `@keyframes` animations of a window
+
+use std::{
+    mem,
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+use {
+    FastHashMap,
+    css::{CssKeyframes, MediaQueryState},
+    css_parser::{
+        ParsedCssProperty, Transition, Animation, TimingFunction,
+        AnimationIterationCount, AnimationDirection, AnimationFillMode,
+    },
+    id_tree::NodeId,
+    traits::Layout,
+    ui_description::{UiDescription, StyledNode},
+};
+
+/// The values of the properties of a node after the cascade, by the key of the property
+type ComputedValues = BTreeMap<&'static str, ParsedCssProperty>;
+
+/// The transitions and animations that are currently running in a window.
+///
+/// Transitions and animations are tracked by the `NodeId` of the node, so they
+/// keep running when the DOM is re-created, as long as the node keeps its position.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct AnimationState {
+    /// The computed values of every node in the last frame (without the transitions),
+    /// a transition is started when one of these values changes
+    last_values: BTreeMap<NodeId, ComputedValues>,
+    /// The running transitions, by node and by the key of the property
+    transitions: BTreeMap<(NodeId, &'static str), RunningTransition>,
+    /// When the animations of a node have been started, by the name of their `@keyframes`
+    animation_starts: BTreeMap<NodeId, Vec<(String, Instant)>>,
+    /// Whether a transition or animation was still running in the last frame
+    is_running: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct RunningTransition {
+    from: ParsedCssProperty,
+    to: ParsedCssProperty,
+    start: Instant,
+    transition: Transition,
+}
+
+impl RunningTransition {
+    /// Returns the value of the property at `now`, or `None` if the transition has ended
+    fn sample(&self, now: Instant) -> Option<ParsedCssProperty> {
+        let elapsed = seconds_between(self.start, now) - as_seconds(self.transition.delay);
+        let duration = as_seconds(self.transition.duration);
+
+        if elapsed < 0.0 {
+            Some(self.from.clone())
+        } else if elapsed >= duration {
+            None
+        } else {
+            let progress = self.transition.timing_function.evaluate(elapsed / duration);
+            Some(self.from.interpolate(&self.to, progress).unwrap_or_else(|| self.to.clone()))
+        }
+    }
+}
+
+impl AnimationState {
+
+    /// Returns whether a transition or animation is running, in which case
+    /// the window has to be restyled every frame
+    pub(crate) fn is_running(&self) -> bool {
+        self.is_running
+    }
+
+    /// Starts the transitions of the properties that have changed since the last update and
+    /// returns the current values of the running transitions and animations, by node.
+    /// These values override the values of the cascade, transitions win over animations.
+    pub(crate) fn update<T: Layout>(
+        &mut self,
+        ui_description: &UiDescription<T>,
+        keyframes: &[CssKeyframes],
+        media_query_state: &MediaQueryState,
+        now: Instant)
+    -> BTreeMap<NodeId, Vec<ParsedCssProperty>>
+    {
+        let previous_values = mem::replace(&mut self.last_values, BTreeMap::new());
+        let mut animated_properties = BTreeMap::<NodeId, Vec<ParsedCssProperty>>::new();
+        let mut last_values = BTreeMap::new();
+        let mut animation_starts = BTreeMap::new();
+        let mut is_running = false;
+
+        for (node_id, styled_node) in &ui_description.styled_nodes {
+            let node_id = *node_id;
+            let values = get_computed_values(styled_node, &ui_description.dynamic_css_overrides);
+
+            let animations = match values.get("animation") {
+                Some(ParsedCssProperty::Animation(animations)) => &animations.0[..],
+                _ => &[],
+            };
+
+            let mut starts = Vec::new();
+            let mut properties = Vec::new();
+
+            for animation in animations {
+                // an animation keeps its start time as long as the node has it, removing
+                // and adding the animation again (i.e. on :hover) restarts it
+                let start = self.animation_starts.get(&node_id)
+                    .and_then(|starts| starts.iter().find(|(name, _)| *name == animation.name))
+                    .and_then(|(_, start)| Some(*start))
+                    .unwrap_or(now);
+                starts.push((animation.name.clone(), start));
+
+                let keyframes = match find_keyframes(keyframes, &animation.name, media_query_state) {
+                    Some(keyframes) => keyframes,
+                    None => continue,
+                };
+
+                let (position, animation_is_running) = get_keyframe_position(animation, seconds_between(start, now));
+                is_running = is_running || animation_is_running;
+
+                if let Some(position) = position {
+                    properties.extend(sample_keyframes(keyframes, &values, position, animation.timing_function));
+                }
+            }
+
+            if !starts.is_empty() {
+                animation_starts.insert(node_id, starts);
+            }
+            if !properties.is_empty() {
+                animated_properties.insert(node_id, properties);
+            }
+
+            if let Some(previous_values) = previous_values.get(&node_id) {
+                let transitions = match values.get("transition") {
+                    Some(ParsedCssProperty::Transition(transitions)) => &transitions.0[..],
+                    _ => &[],
+                };
+                self.start_transitions(node_id, previous_values, &values, transitions, now);
+            }
+
+            last_values.insert(node_id, values);
+        }
+
+        let mut transitions = BTreeMap::new();
+
+        for ((node_id, key), transition) in mem::replace(&mut self.transitions, BTreeMap::new()) {
+            // the node has been removed or the value of the property has changed without a transition
+            if last_values.get(&node_id).and_then(|values| values.get(key)) != Some(&transition.to) {
+                continue;
+            }
+            if let Some(value) = transition.sample(now) {
+                animated_properties.entry(node_id).or_insert_with(Vec::new).push(value);
+                transitions.insert((node_id, key), transition);
+            }
+        }
+
+        self.is_running = is_running || !transitions.is_empty();
+        self.transitions = transitions;
+        self.last_values = last_values;
+        self.animation_starts = animation_starts;
+
+        animated_properties
+    }
+
+    /// Starts a transition for every property of the node that has changed since the last
+    /// update and that has a `transition`. A running transition of the property is replaced,
+    /// the new transition starts at the current value of the old one.
+    fn start_transitions(
+        &mut self,
+        node_id: NodeId,
+        previous_values: &ComputedValues,
+        values: &ComputedValues,
+        transitions: &[Transition],
+        now: Instant)
+    {
+        for (key, value) in values {
+            let previous_value = match previous_values.get(key) {
+                Some(previous_value) if previous_value != value => previous_value,
+                _ => continue,
+            };
+
+            let running_transition = self.transitions.remove(&(node_id, *key));
+
+            // like in CSS, the last transition that applies to the property wins
+            let transition = match transitions.iter().rev().find(|transition| transition.applies_to(key)) {
+                Some(transition) if transition.duration > Duration::from_millis(0) => transition,
+                _ => continue,
+            };
+
+            let from = running_transition
+                .and_then(|running_transition| running_transition.sample(now))
+                .unwrap_or_else(|| previous_value.clone());
+
+            // properties that can't be interpolated change immediately
+            if from.interpolate(value, 0.0).is_some() {
+                self.transitions.insert((node_id, *key), RunningTransition {
+                    from: from,
+                    to: value.clone(),
+                    start: now,
+                    transition: transition.clone(),
+                });
+            }
+        }
+    }
+}
+
+/// Returns the value of every property of the node, the last declaration of a property wins
+fn get_computed_values(styled_node: &StyledNode, dynamic_css_overrides: &FastHashMap<String, ParsedCssProperty>)
+-> ComputedValues
+{
+    let mut values = ComputedValues::new();
+
+    for declaration in &styled_node.css_constraints.list {
+        let property = match declaration.get_value(dynamic_css_overrides) {
+            Some(property) => property,
+            None => continue,
+        };
+        values.insert(property.get_key(), property.clone());
+    }
+
+    values
+}
+
+/// Returns the last `@keyframes` rule with the `name` whose `@media` queries match
+fn find_keyframes<'a>(keyframes: &'a [CssKeyframes], name: &str, media_query_state: &MediaQueryState)
+-> Option<&'a CssKeyframes>
+{
+    keyframes.iter().rev().find(|keyframes| {
+        keyframes.name == name && keyframes.media_queries.iter().all(|query| query.matches(media_query_state))
+    })
+}
+
+/// Returns the position in the keyframes (0.0 - 1.0) of the animation, `elapsed` seconds after
+/// it has been started, and whether the animation is still running. The position is `None` if
+/// the animation has no effect at this time (during the delay or after it has ended, depending
+/// on the `animation-fill-mode`).
+fn get_keyframe_position(animation: &Animation, elapsed: f32) -> (Option<f32>, bool) {
+    let duration = as_seconds(animation.duration);
+    let elapsed = elapsed - as_seconds(animation.delay);
+
+    let fills_backwards = animation.fill_mode == AnimationFillMode::Backwards || animation.fill_mode == AnimationFillMode::Both;
+    let fills_forwards = animation.fill_mode == AnimationFillMode::Forwards || animation.fill_mode == AnimationFillMode::Both;
+
+    if elapsed < 0.0 {
+        let position = if fills_backwards { Some(get_directed_position(animation.direction, 0.0, 0.0)) } else { None };
+        return (position, true);
+    }
+
+    // an animation without a duration ends immediately
+    let iteration_count = match animation.iteration_count {
+        AnimationIterationCount::Infinite if duration > 0.0 => None,
+        AnimationIterationCount::Infinite => Some(1.0),
+        AnimationIterationCount::Count(count) => Some(count),
+    };
+    let progress = if duration > 0.0 { elapsed / duration } else { iteration_count.unwrap_or(0.0) };
+
+    match iteration_count {
+        Some(count) if progress >= count => {
+            if !fills_forwards {
+                return (None, false);
+            }
+            // the position at the end of the last iteration, i.e. the middle of the second iteration for `1.5`
+            let (iteration, fraction) = if count == 0.0 {
+                (0.0, 0.0)
+            } else if count.fract() == 0.0 {
+                (count - 1.0, 1.0)
+            } else {
+                (count.floor(), count.fract())
+            };
+            (Some(get_directed_position(animation.direction, iteration, fraction)), false)
+        },
+        _ => (Some(get_directed_position(animation.direction, progress.floor(), progress.fract())), true),
+    }
+}
+
+/// Returns the position in the keyframes for the `fraction` of the `iteration` (starting at 0)
+fn get_directed_position(direction: AnimationDirection, iteration: f32, fraction: f32) -> f32 {
+    let is_odd_iteration = iteration % 2.0 == 1.0;
+    let is_reversed = match direction {
+        AnimationDirection::Normal => false,
+        AnimationDirection::Reverse => true,
+        AnimationDirection::Alternate => is_odd_iteration,
+        AnimationDirection::AlternateReverse => !is_odd_iteration,
+    };
+    if is_reversed { 1.0 - fraction } else { fraction }
+}
+
+/// Returns the value of every property of the keyframes at the `position`, the
+/// `timing_function` eases between two keyframes. Like in CSS, the computed value
+/// of the node is used if a property has no keyframe at `from` or `to`.
+fn sample_keyframes(keyframes: &CssKeyframes, values: &ComputedValues, position: f32, timing_function: TimingFunction)
+-> Vec<ParsedCssProperty>
+{
+    // the keys of all animated properties, in the order of the keyframes
+    let mut keys = Vec::<&'static str>::new();
+    for keyframe in &keyframes.keyframes {
+        for property in &keyframe.properties {
+            if !keys.contains(&property.get_key()) {
+                keys.push(property.get_key());
+            }
+        }
+    }
+
+    keys.into_iter().filter_map(|key| {
+        let mut frames = Vec::<(f32, &ParsedCssProperty)>::new();
+
+        for keyframe in &keyframes.keyframes {
+            if let Some(property) = keyframe.properties.iter().rev().find(|property| property.get_key() == key) {
+                // of two keyframes with the same offset, the later one wins
+                if frames.last().map_or(false, |(offset, _)| *offset == keyframe.offset) {
+                    frames.pop();
+                }
+                frames.push((keyframe.offset, property));
+            }
+        }
+
+        if let Some(value) = values.get(key) {
+            if frames.first().map_or(true, |(offset, _)| *offset > 0.0) {
+                frames.insert(0, (0.0, value));
+            }
+            if frames.last().map_or(true, |(offset, _)| *offset < 1.0) {
+                frames.push((1.0, value));
+            }
+        }
+
+        let (first_offset, first) = *frames.first()?;
+        let (last_offset, last) = *frames.last()?;
+
+        if position <= first_offset {
+            return Some(first.clone());
+        } else if position >= last_offset {
+            return Some(last.clone());
+        }
+
+        let end = frames.iter().position(|(offset, _)| *offset > position)?;
+        let (start_offset, from) = frames[end - 1];
+        let (end_offset, to) = frames[end];
+        let t = timing_function.evaluate((position - start_offset) / (end_offset - start_offset));
+
+        // properties that can't be interpolated switch to the next value halfway
+        Some(from.interpolate(to, t).unwrap_or_else(|| if t < 0.5 { from.clone() } else { to.clone() }))
+    }).collect()
+}
+
+fn as_seconds(duration: Duration) -> f32 {
+    duration.as_secs() as f32 + duration.subsec_nanos() as f32 / 1_000_000_000.0
+}
+
+/// Returns the seconds from `start` to `now`, negative if `start` is after `now`
+fn seconds_between(start: Instant, now: Instant) -> f32 {
+    if now >= start {
+        as_seconds(now.duration_since(start))
+    } else {
+        -as_seconds(start.duration_since(now))
+    }
+}
+
+#[cfg(test)]
+mod animation_tests {
+    use super::*;
+    use css::Css;
+    use dom::{Dom, NodeType};
+    use traits::{TestLayout, style_test_dom};
+
+    fn style(css: &Css) -> UiDescription<TestLayout> {
+        style_test_dom(&Dom::new(NodeType::Div).with_class("a"), css)
+    }
+
+    fn at(start: Instant, millis: u64) -> Instant {
+        start + Duration::from_millis(millis)
+    }
+
+    fn animated_values(animated_properties: &BTreeMap<NodeId, Vec<ParsedCssProperty>>) -> Vec<String> {
+        animated_properties.values().flat_map(|properties| properties.iter().map(|property| property.to_string())).collect()
+    }
+
+    #[test]
+    fn test_transition() {
+        let before = Css::new_from_string(".a { background-color: #000000; width: 10px; transition: background-color 100ms linear; }").unwrap();
+        let after = Css::new_from_string(".a { background-color: #ff0000; width: 20px; transition: background-color 100ms linear; }").unwrap();
+
+        let start = Instant::now();
+        let mut state = AnimationState::default();
+
+        // nothing is transitioned on the first frame
+        assert!(state.update(&style(&before), &[], &MediaQueryState::default(), start).is_empty());
+        assert!(!state.is_running());
+
+        // only the color has a transition, the width changes immediately
+        let animated = state.update(&style(&after), &[], &MediaQueryState::default(), at(start, 50));
+        assert_eq!(animated_values(&animated), vec!["background-color: #000000"]);
+        assert!(state.is_running());
+
+        let animated = state.update(&style(&after), &[], &MediaQueryState::default(), at(start, 100));
+        assert_eq!(animated_values(&animated), vec!["background-color: #800000"]);
+
+        let animated = state.update(&style(&after), &[], &MediaQueryState::default(), at(start, 150));
+        assert!(animated.is_empty());
+        assert!(!state.is_running());
+    }
+
+    #[test]
+    fn test_keyframe_animation() {
+        let css = Css::new_from_string("
+            .a { width: 10px; animation: grow 100ms linear 2 alternate; }
+            @keyframes grow { to { width: 110px; } 50% { border-radius: 10px; } }
+        ").unwrap();
+
+        let start = Instant::now();
+        let mut state = AnimationState::default();
+        let mut sample = |millis| {
+            let animated = state.update(&style(&css), &css.keyframes, &MediaQueryState::default(), at(start, millis));
+            animated_values(&animated)
+        };
+
+        // the border radius has no keyframe at `from` or `to` and no computed value, so it keeps its only value
+        assert_eq!(sample(0), vec!["border-radius: 10px", "width: 10px"]);
+        assert_eq!(sample(25), vec!["border-radius: 10px", "width: 35px"]);
+        assert_eq!(sample(100), vec!["border-radius: 10px", "width: 110px"]);
+        // the second iteration is played in reverse
+        assert_eq!(sample(175), vec!["border-radius: 10px", "width: 35px"]);
+        // without a fill mode, the animation has no effect after it has ended
+        assert!(sample(200).is_empty());
+        assert!(!state.is_running());
+    }
+
+    #[test]
+    fn test_keyframe_position() {
+        let animation = |iteration_count, direction, fill_mode| Animation {
+            name: "a".to_string(),
+            duration: Duration::from_millis(1000),
+            timing_function: TimingFunction::Linear,
+            delay: Duration::from_millis(1000),
+            iteration_count: iteration_count,
+            direction: direction,
+            fill_mode: fill_mode,
+        };
+
+        let once = animation(AnimationIterationCount::Count(1.5), AnimationDirection::Normal, AnimationFillMode::Both);
+        assert_eq!(get_keyframe_position(&once, 0.5), (Some(0.0), true));
+        assert_eq!(get_keyframe_position(&once, 1.25), (Some(0.25), true));
+        assert_eq!(get_keyframe_position(&once, 3.0), (Some(0.5), false));
+
+        let infinite = animation(AnimationIterationCount::Infinite, AnimationDirection::AlternateReverse, AnimationFillMode::None);
+        assert_eq!(get_keyframe_position(&infinite, 0.5), (None, true));
+        assert_eq!(get_keyframe_position(&infinite, 1.25), (Some(0.75), true));
+        assert_eq!(get_keyframe_position(&infinite, 102.25), (Some(0.25), true));
+    }
+}
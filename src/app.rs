@@ -1,11 +1,12 @@
 use std::{
     fmt,
     io::Read,
+    collections::BTreeSet,
     sync::{Arc, Mutex, PoisonError},
 };
 use glium::{SwapBuffersError, glutin::Event};
 use glium::glutin::dpi::{LogicalPosition, LogicalSize};
-use webrender::api::{RenderApi, HitTestFlags, DevicePixel};
+use webrender::api::{RenderApi, HitTestFlags, DevicePixel, LayoutPoint};
 use image::ImageError;
 use euclid::{TypedScale, TypedSize2D};
 #[cfg(feature = "logging")]
@@ -18,12 +19,14 @@ use {
     text_cache::TextId,
     dom::UpdateScreen,
     window::FakeWindow,
-    css::{Css, FakeCss},
+    css::{Css, FakeCss, PseudoClassState, MediaQueryState},
     resources::AppResources,
     app_state::AppState,
     traits::Layout,
     ui_state::UiState,
     ui_description::UiDescription,
+    display_list::SolvedLayout,
+    id_tree::NodeId,
 };
 
 /// Graphical application that maintains some kind of application state
@@ -65,6 +68,7 @@ impl<T: Layout> fmt::Debug for RuntimeError<T> {
 
 pub(crate) struct FrameEventInfo {
     pub(crate) should_redraw_window: bool,
+    pub(crate) should_restyle_window: bool,
     pub(crate) should_swap_window: bool,
     pub(crate) should_hittest: bool,
     pub(crate) cur_cursor_pos: LogicalPosition,
@@ -77,6 +81,7 @@ impl Default for FrameEventInfo {
     fn default() -> Self {
         Self {
             should_redraw_window: false,
+            should_restyle_window: false,
             should_swap_window: false,
             should_hittest: false,
             cur_cursor_pos: LogicalPosition::new(0.0, 0.0),
@@ -146,6 +151,7 @@ impl<'a, T: Layout> App<'a, T> {
         self.app_state.windows.push(FakeWindow {
             state: window.state.clone(),
             css: FakeCss::default(),
+            layout: SolvedLayout::empty(),
             read_only_window: window.display.clone(),
         });
         self.windows.push(window);
@@ -218,6 +224,7 @@ impl<'a, T: Layout> App<'a, T> {
                 }
 
                 if frame_event_info.should_hittest {
+                    let was_left_down = window.state.mouse_state.left_down;
                     for event in &events {
                         do_hit_test_and_call_callbacks(
                             event,
@@ -227,6 +234,11 @@ impl<'a, T: Layout> App<'a, T> {
                             &ui_state_cache,
                             &mut self.app_state);
                     }
+                    // Restyle the nodes whose :hover, :active or :focus state has changed
+                    if let Some(changed_nodes) = update_pseudo_class_state(window, &ui_state_cache[idx], was_left_down) {
+                        ui_description_cache[idx].restyle_nodes(&window.css, &changed_nodes);
+                        frame_event_info.should_restyle_window = true;
+                    }
                 }
 
                 if frame_event_info.should_swap_window || frame_event_info.is_resize_event {
@@ -256,9 +268,30 @@ impl<'a, T: Layout> App<'a, T> {
                 window.update_from_external_window_state(&mut frame_event_info);
                 // Update the window state every frame that was set by the user
                 window.update_from_user_window_state(self.app_state.windows[idx].state.clone());
+                // Show the `cursor` of the hovered node (or the cursor set by the user)
+                window.update_mouse_cursor(self.app_state.windows[idx].state.mouse_state.mouse_cursor_type);
                 // Reset the scroll amount to 0 (for the next frame)
                 window.clear_scroll_state();
 
+                // Re-evaluate the @media queries if the window size, DPI or color scheme has changed
+                let media_query_state = MediaQueryState::from_window_state(&window.state);
+                if media_query_state != window.css.media_query_state {
+                    window.css.media_query_state = media_query_state;
+                    ui_description_cache[idx] = UiDescription::from_ui_state(&ui_state_cache[idx], &window.css);
+                    frame_event_info.should_restyle_window = true;
+                }
+
+                // Swap in the new stylesheet if the file of a `Css::hot_reload` stylesheet has changed
+                if window.css.reload_if_modified() {
+                    ui_description_cache[idx] = UiDescription::from_ui_state(&ui_state_cache[idx], &window.css);
+                    frame_event_info.should_restyle_window = true;
+                }
+
+                // Keep restyling the window every frame while a transition or animation is running
+                if window.css.animation_state.is_running() {
+                    frame_event_info.should_restyle_window = true;
+                }
+
                 if frame_event_info.should_redraw_window || force_redraw_cache[idx] > 0 {
                     // Call the Layout::layout() fn, get the DOM
                     let window_id = WindowId { id: idx };
@@ -269,10 +302,20 @@ impl<'a, T: Layout> App<'a, T> {
 
                     // Style the DOM
                     ui_description_cache[idx] = UiDescription::from_ui_state(&ui_state_cache[idx], &mut window.css);
+                    // Start the transitions of the changed properties, sample the running animations
+                    ui_description_cache[idx].update_animations(&mut window.css);
                     // send webrender the size and buffer of the display
                     Self::update_display(&window);
                     // render the window (webrender will send an Awakened event when the frame is done)
-                    render(window, &WindowId { id: idx }, &ui_description_cache[idx], &mut self.app_state.resources, true);
+                    render(window, &WindowId { id: idx }, &ui_description_cache[idx], &mut self.app_state.resources);
+                    // Make the solved layout available to the callbacks of the next frame
+                    self.app_state.windows[idx].layout = window.solver.solved_layout.clone();
+                } else if frame_event_info.should_restyle_window {
+                    // Only the style has changed, the DOM stays the same, so
+                    // Layout::layout() doesn't need to be called again
+                    ui_description_cache[idx].update_animations(&mut window.css);
+                    render(window, &WindowId { id: idx }, &ui_description_cache[idx], &mut self.app_state.resources);
+                    self.app_state.windows[idx].layout = window.solver.solved_layout.clone();
                 }
             }
 
@@ -534,6 +577,10 @@ fn preprocess_event(event: &Event, frame_event_info: &mut FrameEventInfo) -> Win
                     frame_event_info.should_hittest = true;
                     frame_event_info.cur_cursor_pos = *position;
                 },
+                WindowEvent::CursorLeft { .. } => {
+                    // clear the :hover state
+                    frame_event_info.should_hittest = true;
+                },
                 WindowEvent::Resized(wh) => {
                     frame_event_info.new_window_size = Some(*wh);
                     frame_event_info.is_resize_event = true;
@@ -606,12 +653,18 @@ fn do_hit_test_and_call_callbacks<T: Layout>(
         .get(&item.tag.0)
         .and_then(|callback_list| Some((item, callback_list)))
     ) {
-        // TODO: currently we don't have information about what DOM node was hit
+        let hit_node_bounds = window.solver.solved_layout.get_bounds_by_tag(item.tag.0);
+        let cursor_relative_to_item = match hit_node_bounds {
+            Some(bounds) => (item.point_in_viewport.x - bounds.origin.x, item.point_in_viewport.y - bounds.origin.y),
+            None => (item.point_in_viewport.x, item.point_in_viewport.y),
+        };
+
         let window_event = WindowEvent {
             window: window_id.id,
             number_of_previous_siblings: None,
-            cursor_relative_to_item: (item.point_in_viewport.x, item.point_in_viewport.y),
+            cursor_relative_to_item: cursor_relative_to_item,
             cursor_in_viewport: (item.point_in_viewport.x, item.point_in_viewport.y),
+            hit_node_bounds: hit_node_bounds,
         };
 
         // Invoke callback if necessary
@@ -632,17 +685,59 @@ fn do_hit_test_and_call_callbacks<T: Layout>(
         // Copy the current fake CSS changes to the real CSS, then clear the fake CSS again
         // TODO: .clone() and .clear() can be one operation
         window.css.dynamic_css_overrides = app_state.windows[window_id.id].css.dynamic_css_overrides.clone();
+        // custom properties stay set until they are overridden again
+        let custom_property_overrides = app_state.windows[window_id.id].css.custom_property_overrides.clone();
+        window.css.custom_property_overrides.extend(custom_property_overrides);
         // clear the dynamic CSS overrides
         app_state.windows[window_id.id].css.clear();
     }
 }
 
+/// Resolves the `:hover`, `:active` and `:focus` state of the nodes from the current
+/// mouse state and the solved layout of the last frame.
+///
+/// Returns the nodes that need to be restyled, or `None` if no node has changed
+/// its state in a way that affects the CSS.
+fn update_pseudo_class_state<T: Layout>(
+    window: &mut Window<T>,
+    ui_state: &UiState<T>,
+    was_left_down: bool)
+-> Option<BTreeSet<NodeId>>
+{
+    let arena = ui_state.dom.arena.borrow();
+
+    let hit_node = window.state.mouse_state.cursor_pos.and_then(|pos| {
+        window.solver.solved_layout.hit_test(LayoutPoint::new(pos.x as f32, pos.y as f32))
+    });
+
+    let is_left_down = window.state.mouse_state.left_down;
+    let focused = if is_left_down && !was_left_down {
+        hit_node
+    } else {
+        window.css.pseudo_class_state.focused
+    };
+
+    let new_state = PseudoClassState::new(hit_node, is_left_down, focused, &*arena);
+    if new_state == window.css.pseudo_class_state {
+        return None;
+    }
+
+    let changed_nodes = window.css.pseudo_class_state.get_changed_nodes(&new_state);
+    window.css.pseudo_class_state = new_state;
+
+    // Don't restyle if no rule depends on the pseudo-class state
+    if window.css.rules.iter().any(|rule| rule.path.has_dynamic_pseudo_selector()) {
+        Some(changed_nodes)
+    } else {
+        None
+    }
+}
+
 fn render<T: Layout>(
     window: &mut Window<T>,
     _window_id: &WindowId,
     ui_description: &UiDescription<T>,
-    app_resources: &mut AppResources,
-    has_window_size_changed: bool)
+    app_resources: &mut AppResources)
 {
     use webrender::api::*;
     use display_list::DisplayList;
@@ -652,10 +747,8 @@ fn render<T: Layout>(
     let builder = display_list.into_display_list_builder(
         window.internal.pipeline_id,
         &mut window.solver,
-        &mut window.css,
         app_resources,
         &window.internal.api,
-        has_window_size_changed,
         &window.state.size);
 
     if let Some(new_builder) = builder {
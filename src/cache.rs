@@ -7,42 +7,23 @@
 //! - An element changes its content An element is pushed as a child The order / childs of an element
 //! - are restructured
 //!
-//! In order for the caching to be effective, we need to solve the  problem of only adding
-//! EditVariable-s if needed. In order to do that, we need two elements for each DOM node:
+//! In order to detect these changes, we need two elements for each DOM node:
 //!
 //! - The self-hash (the hash of the current DOM node, including hashing the content)
 //! - The hashes of the individual children (like a `Vec<DomHash>`), in their correct order
 //!
-//! For detecting these changes, we build an `Arena<DomHash>` (empty on startup) and a
-//! `HashMap<(DomHash, bool) -> LayoutRect>`. The latter stores all active EditVariables.  Whenever we
-//! insert or remove from the HashMap, we also remove the variables from the solver
-//!
-//! When a re-layout is required, we hash the nodes from the UiDescription, starting from the root. Each
-//! time we go to the next sibling / next child, this change is also reflected by going through the
-//! `Arena<DomHash>`. For each node, we calculate the self-hash of the node and compare it with the hash
-//! in that position in the `Arena<DomHash>`. If the hash does not exist in the `Arena<DomHash>`, we
-//! insert it in the `HashMap<(DomHash, bool)`, create a new LayoutRect and add the variables to the
-//! solver. We set the `bool` to true to indicate, that this hash is currently active in the DOM and
-//! should not be removed. Then we add the hash to the `Arena<DomHash>`.
-//!
-//! If there is a hash, but the hashes differ, this means that either the order of the current siblings
-//! were  changed or the actual contents of the node were changed. So we look up the hash in the
-//! `HashMap<(DomHash, bool)>`.  If we can find it, this means that we already have EditVariables in the
-//! solver corresponding to the node and the node was simply reordered.
-//! If we can't find it, it's either a completely new DOM element or the contents of the node have changed.
-//!
-//! Lastly, we go through the `HashMap<(DomHash, bool)>` and remove the edit variables if the `bool` is false,
-//! meaning that the variable was not present in the current DOM tree, so leaving the variables in the solver
-//! would be garbage.
+//! For detecting these changes, we keep the `Arena<DomHash>` of the previous layout (empty on startup).
+//! When a re-layout is required, we hash the nodes from the UiDescription into a new `Arena<DomHash>`
+//! and compare it with the previous one, node by node. Every node whose hash differs from the hash
+//! in that position in the previous `Arena<DomHash>` (or that didn't exist in the previous DOM) is
+//! added to the `DomChangeSet`. Then the new `Arena<DomHash>` replaces the previous one.
 
 use std::{
     ops::Deref,
     collections::BTreeMap,
 };
-use cassowary::Solver;
 
 use {
-    constraints::DisplayRect,
     id_tree::{NodeId, Arena},
     traits::Layout,
     dom::NodeData,
@@ -263,56 +244,6 @@ pub(crate) struct DomNodeHash {
     pub(crate) children_hash: Vec<DomHash>,
 }
 
-#[derive(Debug)]
-pub(crate) struct EditVariableCache {
-    pub(crate) map: BTreeMap<DomHash, (bool, DisplayRect)>
-}
-
-impl EditVariableCache {
-    pub(crate) fn empty() -> Self {
-        Self {
-            map: BTreeMap::new(),
-        }
-    }
-
-    pub(crate) fn initialize_new_rectangles(&mut self, solver: &mut Solver, rects: &DomChangeSet) {
-        use std::collections::btree_map::Entry::*;
-
-        for dom_hash in rects.added_nodes.values() {
-
-            let map_entry = self.map.entry(*dom_hash);
-            match map_entry {
-                Occupied(e) => {
-                    e.into_mut().0 = true;
-                },
-                Vacant(e) => {
-                    let rect = DisplayRect::default();
-                    rect.add_to_solver(solver);
-                    e.insert((true, rect));
-                }
-            }
-        }
-    }
-
-    /// Last step of the caching algorithm:
-    /// Remove all edit variables where the `bool` is set to false
-    pub(crate) fn remove_unused_variables(&mut self, solver: &mut Solver) {
-
-        let mut to_be_removed = Vec::<DomHash>::new();
-
-        for (key, &(active, variable_rect)) in &self.map {
-            if !active {
-                variable_rect.remove_from_solver(solver);
-                to_be_removed.push(*key);
-            }
-        }
-
-        for hash in &to_be_removed {
-            self.map.remove(hash);
-        }
-    }
-}
-
 // Empty test, for some reason codecov doesn't detect any files (and therefore
 // doesn't report codecov % correctly) except if they have at least one test in
 // the file. This is an empty test, which should be updated later on
@@ -1,10 +1,26 @@
 //! CSS parsing and styling
-use std::ops::Add;
+use std::{
+    ops::Add,
+    fmt,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+    cell::RefCell,
+    collections::BTreeSet,
+};
 use {
     FastHashMap,
-    traits::IntoParsedCssProperty,
-    css_parser::{ParsedCssProperty, CssParsingError},
+    traits::{Layout, IntoParsedCssProperty, DefaultStyle},
+    css_parser::{
+        ParsedCssProperty, CssParsingError, ToCssString, ColorU, parse_pixel_value,
+        get_property_name, is_inherited_property, CURRENT_COLOR,
+    },
     errors::CssSyntaxError,
+    id_tree::{NodeId, Arena},
+    dom::NodeData,
+    window_state::{WindowState, ColorScheme},
+    widgets::Button,
+    animation::AnimationState,
 };
 
 #[cfg(target_os="windows")]
@@ -15,8 +31,9 @@ const NATIVE_CSS_LINUX: &str = include_str!("styles/native_linux.css");
 const NATIVE_CSS_MACOS: &str = include_str!("styles/native_macos.css");
 
 /// All the keys that, when changed, can trigger a re-layout
-const RELAYOUT_RULES: [&str; 13] = [
+const RELAYOUT_RULES: [&str; 15] = [
     "border", "width", "height", "min-width", "min-height", "max-width", "max-height",
+    "padding", "margin",
     "direction", "wrap", "justify-content", "align-items", "align-content",
     "order"
 ];
@@ -31,17 +48,125 @@ pub struct Css {
     /// - `String`: The ID of the dynamic property
     /// - `ParsedCssProperty`: What to override it with
     pub(crate) dynamic_css_overrides: FastHashMap<String, ParsedCssProperty>,
-    /// Has the CSS changed in a way where it needs a re-layout?
-    ///
-    /// Ex. if only a background color has changed, we need to redraw, but we
-    /// don't need to re-layout the frame
-    pub(crate) needs_relayout: bool,
+    /// Which nodes are currently hovered, pressed or focused, used to
+    /// match the `:hover`, `:active` and `:focus` pseudo-classes
+    pub(crate) pseudo_class_state: PseudoClassState,
+    /// The custom properties (`--accent`) that have been set from the `FakeCss`,
+    /// these override the values declared in the stylesheet for every node
+    pub(crate) custom_property_overrides: FastHashMap<String, String>,
+    /// The window size, DPI and color scheme that the `@media` queries are evaluated against
+    pub(crate) media_query_state: MediaQueryState,
+    /// The file that the stylesheet is reloaded from, see `Css::hot_reload`
+    pub(crate) hot_reload_source: Option<HotReloadSource>,
+    /// The `@keyframes` rules, in the order of the stylesheet
+    pub(crate) keyframes: Vec<CssKeyframes>,
+    /// The transitions and animations that are currently running
+    pub(crate) animation_state: AnimationState,
+}
+
+/// The file of a hot-reloaded stylesheet, see `Css::hot_reload`
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct HotReloadSource {
+    pub(crate) path: PathBuf,
+    /// The files that were read on the last load (the stylesheet and the stylesheets
+    /// that it imports) with their modification times at that point
+    pub(crate) loaded_files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl HotReloadSource {
+    /// Returns whether the stylesheet or one of its imports has been modified since the last load
+    fn is_modified(&self) -> bool {
+        self.loaded_files.is_empty() ||
+        self.loaded_files.iter().any(|&(ref path, last_modified)| get_modified_time(path) != last_modified)
+    }
+}
+
+/// Returns the modification time of the file, `None` if the file doesn't exist
+fn get_modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Replaces the hot-reloaded items with the `new_items`. The new items are inserted at the
+/// position of the old ones (or at the end, if there were none), so they keep their precedence
+/// over the rules of the stylesheets that have been merged before and after them.
+fn replace_hot_reloaded<T, F: Fn(&T) -> bool>(items: &mut Vec<T>, new_items: Vec<T>, is_hot_reloaded: F) {
+    let position = items.iter().position(|item| is_hot_reloaded(item)).unwrap_or(items.len());
+    items.retain(|item| !is_hot_reloaded(item));
+    items.splice(position..position, new_items);
+}
+
+/// The state of the window that `@media` queries are evaluated against
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct MediaQueryState {
+    /// Width of the window, in logical pixels
+    pub(crate) width: f32,
+    /// Height of the window, in logical pixels
+    pub(crate) height: f32,
+    /// Device pixels per logical pixel, i.e. `2.0` on a HiDPI monitor
+    pub(crate) hidpi_factor: f32,
+    /// The color scheme that the user prefers
+    pub(crate) color_scheme: ColorScheme,
+}
+
+impl Default for MediaQueryState {
+    fn default() -> Self {
+        Self::from_window_state(&WindowState::default())
+    }
+}
+
+impl MediaQueryState {
+    pub(crate) fn from_window_state(window_state: &WindowState) -> Self {
+        Self {
+            width: window_state.size.dimensions.width as f32,
+            height: window_state.size.dimensions.height as f32,
+            hidpi_factor: window_state.size.hidpi_factor as f32,
+            color_scheme: window_state.color_scheme,
+        }
+    }
+}
+
+/// The nodes that the `:hover`, `:active` and `:focus` pseudo-classes currently
+/// apply to, resolved from the mouse state after each hit test.
+///
+/// Note: The `NodeId`s refer to the DOM of the last frame. If the DOM changes,
+/// the state is resolved again on the next mouse event.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct PseudoClassState {
+    /// The top-most node under the mouse cursor and all of its ancestors
+    pub(crate) hovered: BTreeSet<NodeId>,
+    /// Same as `hovered`, but only while the left mouse button is held down
+    pub(crate) active: BTreeSet<NodeId>,
+    /// The node that was last clicked on
+    pub(crate) focused: Option<NodeId>,
+}
+
+impl PseudoClassState {
+    /// Resolves the state from the top-most node under the mouse cursor (if any)
+    pub(crate) fn new<T>(hit_node: Option<NodeId>, is_mouse_down: bool, focused: Option<NodeId>, arena: &Arena<T>) -> Self {
+        let hovered = hit_node
+            .and_then(|node_id| Some(node_id.ancestors(arena).collect::<BTreeSet<NodeId>>()))
+            .unwrap_or_default();
+        let active = if is_mouse_down { hovered.clone() } else { BTreeSet::new() };
+        Self { hovered, active, focused }
+    }
+
+    /// Returns the nodes whose pseudo-class state differs between `self` and `other`,
+    /// i.e. the nodes that have to be restyled
+    pub(crate) fn get_changed_nodes(&self, other: &Self) -> BTreeSet<NodeId> {
+        let mut changed_nodes = self.hovered.symmetric_difference(&other.hovered).cloned().collect::<BTreeSet<NodeId>>();
+        changed_nodes.extend(self.active.symmetric_difference(&other.active).cloned());
+        if self.focused != other.focused {
+            changed_nodes.extend(self.focused.iter().chain(other.focused.iter()).cloned());
+        }
+        changed_nodes
+    }
 }
 
 /// Fake CSS that can be changed by the user
 #[derive(Debug, Default, Clone)]
 pub struct FakeCss {
     pub dynamic_css_overrides: FastHashMap<String, ParsedCssProperty>,
+    pub custom_property_overrides: FastHashMap<String, String>,
 }
 
 impl FakeCss {
@@ -56,11 +181,23 @@ impl FakeCss {
         Ok(())
     }
 
+    /// Set the value of a custom property, i.e. `set_custom_property("--accent", "#ff0000")`.
+    ///
+    /// Every `var(--accent)` in the stylesheet will use the new value, which re-themes
+    /// the whole UI. Unlike dynamic properties, the value stays set for the following frames.
+    pub fn set_custom_property<S, V>(&mut self, name: S, value: V)
+    where S: Into<String>,
+          V: Into<String>,
+    {
+        self.custom_property_overrides.insert(name.into(), value.into());
+    }
+
     /// Library-internal only: clear the dynamic overrides
     ///
     /// Is usually invoked at the end of the frame, to get a clean slate
     pub(crate) fn clear(&mut self) {
         self.dynamic_css_overrides = FastHashMap::default();
+        self.custom_property_overrides = FastHashMap::default();
     }
 }
 
@@ -73,6 +210,20 @@ pub enum CssParseError<'a> {
     UnclosedBlock,
     /// Invalid syntax, such as `#div { #div: "my-value" }`
     MalformedCss,
+    /// A selector that is valid CSS, but not supported by azul, such as
+    /// the `+` (adjacent sibling) combinator or the `:visited` pseudo-class
+    UnsupportedSelector,
+    /// The argument of `:nth-child()` is not of the form `an+b`, `odd` or `even`,
+    /// i.e. `:nth-child(2x+1)`
+    InvalidNthChildPattern(&'a str),
+    /// An at-rule that azul doesn't support, such as `@font-face`
+    UnsupportedAtRule(&'a str),
+    /// A `@media` query that couldn't be parsed or uses unsupported
+    /// media features, i.e. `@media (orientation: portrait)`
+    InvalidMediaQuery(&'a str),
+    /// A `@keyframes` rule without a name or with a keyframe that is
+    /// not `from`, `to` or a percentage, i.e. `@keyframes fade { 150% { } }`
+    InvalidKeyframes(&'a str),
     /// Error parsing dynamic CSS property, such as
     /// `#div { width: {{ my_id }} /* no default case */ }`
     DynamicCssParseError(DynamicCssParseError<'a>),
@@ -80,6 +231,112 @@ pub enum CssParseError<'a> {
     /// (Css is parsed eagerly, directly converted to strongly typed values
     /// as soon as possible)
     UnexpectedValue(CssParsingError<'a>),
+    /// An `@import` rule couldn't be resolved
+    ImportError(CssImportError),
+}
+
+/// Error that can happen while resolving an `@import` rule
+#[derive(Debug, Clone, PartialEq)]
+pub enum CssImportError {
+    /// The stylesheet uses `@import`, but was parsed without a `CssLoader`,
+    /// use `Css::new_from_string_with_loader` instead of `Css::new_from_string`
+    NoLoader(String),
+    /// The `@import` rule has no path, i.e. `@import 5px;`
+    InvalidImport(String),
+    /// The loader couldn't load the stylesheet at the path
+    NotFound(String),
+    /// The stylesheet imports itself (directly or via other stylesheets)
+    CyclicImport(String),
+    /// Error in the imported stylesheet. The error is formatted, since it
+    /// borrows from the imported source, which doesn't outlive the parsing.
+    ParseError { path: String, error: String },
+}
+
+/// A `CssParseError` together with the position of the error in the stylesheet
+#[derive(Debug, Clone, PartialEq)]
+pub struct CssParseErrorWithLocation<'a> {
+    pub error: CssParseError<'a>,
+    /// Line of the error, starting at 1
+    pub line: usize,
+    /// Column of the error (in characters), starting at 1
+    pub column: usize,
+    /// The selector, declaration or at-rule that caused the error, i.e. `width: hello`
+    pub snippet: &'a str,
+}
+
+impl<'a> CssParseErrorWithLocation<'a> {
+    /// Locates the error in the `source`, the `snippet` has to be a slice of the `source`
+    fn new(source: &'a str, error: CssParseError<'a>, snippet: &'a str) -> Self {
+        let before_snippet = &source[..get_offset(source, snippet)];
+        let line_start = before_snippet.rfind('\n').and_then(|newline| Some(newline + 1)).unwrap_or(0);
+        Self {
+            error: error,
+            line: before_snippet.matches('\n').count() + 1,
+            column: before_snippet[line_start..].chars().count() + 1,
+            snippet: snippet,
+        }
+    }
+}
+
+impl<'a> fmt::Display for CssParseErrorWithLocation<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {:?} in `{}`", self.line, self.column, self.error, self.snippet)
+    }
+}
+
+impl<'a> From<CssImportError> for CssParseError<'a> {
+    fn from(e: CssImportError) -> Self {
+        CssParseError::ImportError(e)
+    }
+}
+
+/// Loads the stylesheets of `@import` rules, see `Css::new_from_string_with_loader`.
+///
+/// Implemented for closures, so a loader can be as simple as:
+///
+/// ```no_run,ignore
+/// let css = Css::new_from_string_with_loader(css_string, &|path: &str| my_stylesheets.get(path).cloned())?;
+/// ```
+pub trait CssLoader {
+    /// Returns the contents of the stylesheet at `path` (the path of the `@import`
+    /// rule, i.e. `theme.css` for `@import "theme.css";`) or `None` if it doesn't exist
+    fn load_css(&self, path: &str) -> Option<String>;
+}
+
+impl<F> CssLoader for F where F: Fn(&str) -> Option<String> {
+    fn load_css(&self, path: &str) -> Option<String> {
+        (self)(path)
+    }
+}
+
+/// Loads imported stylesheets from the disk. All `@import` paths (also the ones
+/// of nested imports) are relative to the `directory`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CssFileLoader {
+    pub directory: PathBuf,
+    /// The files that the loader has been asked for (also the ones that don't
+    /// exist), so that `Css::hot_reload` can watch the imported stylesheets
+    loaded_files: RefCell<Vec<PathBuf>>,
+}
+
+impl CssFileLoader {
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Self {
+        Self { directory: directory.into(), loaded_files: RefCell::new(Vec::new()) }
+    }
+
+    /// Returns the paths of the files that the loader has been asked for, in the order of the requests
+    pub(crate) fn get_loaded_files(&self) -> Vec<PathBuf> {
+        self.loaded_files.borrow().clone()
+    }
+}
+
+impl CssLoader for CssFileLoader {
+    fn load_css(&self, path: &str) -> Option<String> {
+        let path = self.directory.join(path);
+        let css_string = fs::read_to_string(&path).ok();
+        self.loaded_files.borrow_mut().push(path);
+        css_string
+    }
 }
 
 impl<'a> From<CssParsingError<'a>> for CssParseError<'a> {
@@ -101,178 +358,1603 @@ impl<'a> From<DynamicCssParseError<'a>> for CssParseError<'a> {
 /// to do the cascading.
 #[derive(Debug, Clone, PartialEq)]
 pub(crate) struct CssRule {
-    /// `div` (`*` by default)
-    pub html_type: String,
-    /// `#myid` (`None` by default)
-    pub id: Option<String>,
-    /// `.myclass .myotherclass` (vec![] by default)
-    pub classes: Vec<String>,
+    /// The full selector of the rule, i.e. `div#main .toolbar > p`
+    pub path: CssPath,
     /// `("justify-content", "center")`
     pub declaration: (String, CssDeclaration),
+    /// Whether the declaration was marked as `!important`
+    pub important: bool,
+    /// The queries of the `@media` blocks that the rule is nested in,
+    /// the rule only applies if all of them match
+    pub media_queries: Vec<CssMediaQueryList>,
+    /// Whether the rule comes from a user-agent stylesheet or from the app
+    pub origin: CssOrigin,
+    /// Whether the rule comes from the file of `Css::hot_reload`,
+    /// only these rules are replaced when the file is reloaded
+    pub hot_reloaded: bool,
+}
+
+/// The layer of the cascade that a rule comes from, see `Css::layered`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum CssOrigin {
+    /// The default style of azul and its widgets, i.e. `Css::native()`
+    UserAgent,
+    /// The style of the application
+    App,
+}
+
+impl CssOrigin {
+    /// Returns the precedence of the origin in the cascade (higher wins):
+    /// the app wins over the user agent, but `!important` user-agent
+    /// declarations win over `!important` app declarations
+    pub(crate) fn get_precedence(&self, important: bool) -> u8 {
+        match (*self, important) {
+            (CssOrigin::UserAgent, false) | (CssOrigin::App, true) => 0,
+            (CssOrigin::App, false) | (CssOrigin::UserAgent, true) => 1,
+        }
+    }
+}
+
+/// The comma-separated queries of a `@media` block, i.e.
+/// `(max-width: 600px), (prefers-color-scheme: dark)`. Matches if any query matches.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CssMediaQueryList(pub(crate) Vec<CssMediaQuery>);
+
+/// A single media query, i.e. `screen and (min-width: 400px) and (max-width: 600px)`.
+/// Matches if all of its features match.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CssMediaQuery {
+    pub(crate) features: Vec<CssMediaFeature>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum CssMediaFeature {
+    /// `(min-width: 400px)`
+    MinWidth(f32),
+    /// `(max-width: 600px)`
+    MaxWidth(f32),
+    /// `(min-height: 400px)`
+    MinHeight(f32),
+    /// `(max-height: 600px)`
+    MaxHeight(f32),
+    /// `(min-resolution: 2dppx)`, in device pixels per logical pixel
+    MinResolution(f32),
+    /// `(max-resolution: 1dppx)`, in device pixels per logical pixel
+    MaxResolution(f32),
+    /// `(prefers-color-scheme: dark)`
+    PrefersColorScheme(ColorScheme),
+}
+
+impl CssMediaQueryList {
+    fn from_str<'a>(input: &'a str) -> Result<Self, CssParseError<'a>> {
+        input.split(',')
+            .map(CssMediaQuery::from_str)
+            .collect::<Result<Vec<CssMediaQuery>, CssParseError<'a>>>()
+            .and_then(|queries| Ok(CssMediaQueryList(queries)))
+    }
+
+    pub(crate) fn matches(&self, state: &MediaQueryState) -> bool {
+        self.0.iter().any(|query| query.matches(state))
+    }
+}
+
+impl CssMediaQuery {
+    fn from_str<'a>(input: &'a str) -> Result<Self, CssParseError<'a>> {
+        let mut features = Vec::new();
+        let mut rest = input.trim();
+
+        while !rest.is_empty() {
+            if rest.starts_with('(') {
+                let feature_end = rest.find(')').ok_or(CssParseError::InvalidMediaQuery(input))?;
+                features.push(CssMediaFeature::from_str(&rest[1..feature_end]).ok_or(CssParseError::InvalidMediaQuery(input))?);
+                rest = rest[feature_end + 1..].trim_left();
+            } else {
+                let word_end = rest.find(|c: char| c.is_whitespace() || c == '(').unwrap_or(rest.len());
+                match &rest[..word_end] {
+                    // azul only renders to screens
+                    "and" | "only" | "screen" | "all" => { },
+                    _ => return Err(CssParseError::InvalidMediaQuery(input)),
+                }
+                rest = rest[word_end..].trim_left();
+            }
+        }
+
+        Ok(Self { features })
+    }
+
+    fn matches(&self, state: &MediaQueryState) -> bool {
+        self.features.iter().all(|feature| feature.matches(state))
+    }
+}
+
+impl CssMediaFeature {
+    /// Parses the inside of the parentheses, i.e. `max-width: 600px`
+    fn from_str(input: &str) -> Option<Self> {
+        use self::CssMediaFeature::*;
+
+        let mut colon_split = input.splitn(2, ':');
+        let name = colon_split.next()?.trim();
+        let value = colon_split.next()?.trim();
+
+        let length = || parse_pixel_value(value).ok().and_then(|v| Some(v.to_pixels()));
+
+        match name {
+            "min-width" => Some(MinWidth(length()?)),
+            "max-width" => Some(MaxWidth(length()?)),
+            "min-height" => Some(MinHeight(length()?)),
+            "max-height" => Some(MaxHeight(length()?)),
+            "min-resolution" => Some(MinResolution(parse_resolution(value)?)),
+            "max-resolution" => Some(MaxResolution(parse_resolution(value)?)),
+            "prefers-color-scheme" => match value {
+                "light" => Some(PrefersColorScheme(ColorScheme::Light)),
+                "dark" => Some(PrefersColorScheme(ColorScheme::Dark)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn matches(&self, state: &MediaQueryState) -> bool {
+        use self::CssMediaFeature::*;
+        match *self {
+            MinWidth(width) => state.width >= width,
+            MaxWidth(width) => state.width <= width,
+            MinHeight(height) => state.height >= height,
+            MaxHeight(height) => state.height <= height,
+            MinResolution(resolution) => state.hidpi_factor >= resolution,
+            MaxResolution(resolution) => state.hidpi_factor <= resolution,
+            PrefersColorScheme(color_scheme) => state.color_scheme == color_scheme,
+        }
+    }
+}
+
+impl fmt::Display for CssMediaQueryList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let queries = self.0.iter().map(|query| query.to_string()).collect::<Vec<String>>();
+        write!(f, "{}", queries.join(", "))
+    }
+}
+
+impl fmt::Display for CssMediaQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.features.is_empty() {
+            write!(f, "all")
+        } else {
+            let features = self.features.iter().map(|feature| feature.to_string()).collect::<Vec<String>>();
+            write!(f, "{}", features.join(" and "))
+        }
+    }
+}
+
+impl fmt::Display for CssMediaFeature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::CssMediaFeature::*;
+        match *self {
+            MinWidth(width) => write!(f, "(min-width: {}px)", width),
+            MaxWidth(width) => write!(f, "(max-width: {}px)", width),
+            MinHeight(height) => write!(f, "(min-height: {}px)", height),
+            MaxHeight(height) => write!(f, "(max-height: {}px)", height),
+            MinResolution(resolution) => write!(f, "(min-resolution: {}dppx)", resolution),
+            MaxResolution(resolution) => write!(f, "(max-resolution: {}dppx)", resolution),
+            PrefersColorScheme(ColorScheme::Light) => write!(f, "(prefers-color-scheme: light)"),
+            PrefersColorScheme(ColorScheme::Dark) => write!(f, "(prefers-color-scheme: dark)"),
+        }
+    }
+}
+
+/// Parses a resolution such as `2dppx`, `2x` or `192dpi` into device pixels per logical pixel
+fn parse_resolution(input: &str) -> Option<f32> {
+    const DPI_PER_DPPX: f32 = 96.0;
+
+    let (number, factor) = if input.ends_with("dppx") {
+        (&input[..input.len() - 4], 1.0)
+    } else if input.ends_with("dpi") {
+        (&input[..input.len() - 3], 1.0 / DPI_PER_DPPX)
+    } else if input.ends_with('x') {
+        (&input[..input.len() - 1], 1.0)
+    } else {
+        return None;
+    };
+
+    number.trim().parse::<f32>().ok().and_then(|n| Some(n * factor))
+}
+
+/// A CSS selector, split into its simple selectors and combinators, i.e.
+/// `div#main .toolbar > p` is stored as
+/// `[Type("div"), Id("main"), Children, Class("toolbar"), DirectChildren, Type("p")]`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CssPath {
+    pub(crate) selectors: Vec<CssPathSelector>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum CssPathSelector {
+    /// `*`
+    Global,
+    /// `div`, `p`, etc.
+    Type(String),
+    /// `.myclass`
+    Class(String),
+    /// `#myid`
+    Id(String),
+    /// The `>` combinator: the next selector has to match a direct child
+    DirectChildren,
+    /// The ` ` (descendant) combinator: the next selector has to match any descendant
+    Children,
+    /// `:hover`, `:active`, etc.
+    PseudoSelector(CssPathPseudoSelector),
+}
+
+/// Pseudo-classes that depend on the state of the window (`:hover`) or
+/// on the position of the node among its siblings (`:first-child`)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum CssPathPseudoSelector {
+    /// `:hover` - the mouse cursor is over the node or one of its children
+    Hover,
+    /// `:active` - the node is hovered and the left mouse button is held down
+    Active,
+    /// `:focus` - the node was the last node that has been clicked on
+    Focus,
+    /// `:first-child`
+    First,
+    /// `:last-child`
+    Last,
+    /// `:only-child`
+    Only,
+    /// `:nth-child(2n+1)`
+    NthChild(CssNthChildPattern),
+}
+
+/// The `an+b` argument of `:nth-child()`: matches every node whose (1-based)
+/// index among its siblings is `repeat * n + offset`, for any `n >= 0`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CssNthChildPattern {
+    pub(crate) repeat: isize,
+    pub(crate) offset: isize,
+}
+
+impl CssNthChildPattern {
+    /// Parses `odd`, `even`, `3`, `n+2`, `-n+3`, `2n-1`, etc.
+    fn from_str(pattern: &str) -> Option<Self> {
+        let pattern = pattern.trim().to_lowercase().replace(' ', "");
+
+        match pattern.as_str() {
+            "odd" => return Some(Self { repeat: 2, offset: 1 }),
+            "even" => return Some(Self { repeat: 2, offset: 0 }),
+            _ => { },
+        }
+
+        let (repeat, offset) = match pattern.find('n') {
+            Some(n_position) => {
+                let repeat = match &pattern[..n_position] {
+                    "" | "+" => 1,
+                    "-" => -1,
+                    r => r.parse::<isize>().ok()?,
+                };
+                let offset = match &pattern[n_position + 1..] {
+                    "" => 0,
+                    o if o.starts_with('+') || o.starts_with('-') => o.parse::<isize>().ok()?,
+                    _ => return None,
+                };
+                (repeat, offset)
+            },
+            None => (0, pattern.parse::<isize>().ok()?),
+        };
+
+        Some(Self { repeat, offset })
+    }
+
+    /// Returns whether the 1-based index of a node matches the pattern
+    fn matches(&self, index: usize) -> bool {
+        let difference = index as isize - self.offset;
+        if self.repeat == 0 {
+            difference == 0
+        } else {
+            difference % self.repeat == 0 && difference / self.repeat >= 0
+        }
+    }
+}
+
+impl CssPathPseudoSelector {
+    fn from_str<'a>(pseudo_class: &'a str) -> Result<Self, CssParseError<'a>> {
+        use self::CssPathPseudoSelector::*;
+
+        const NTH_CHILD: &str = "nth-child(";
+
+        match pseudo_class {
+            "hover" => Ok(Hover),
+            "active" => Ok(Active),
+            "focus" => Ok(Focus),
+            "first-child" => Ok(First),
+            "last-child" => Ok(Last),
+            "only-child" => Ok(Only),
+            p if p.starts_with(NTH_CHILD) && p.ends_with(')') => {
+                let pattern = &p[NTH_CHILD.len()..p.len() - 1];
+                CssNthChildPattern::from_str(pattern)
+                    .and_then(|pattern| Some(NthChild(pattern)))
+                    .ok_or(CssParseError::InvalidNthChildPattern(pattern))
+            },
+            _ => Err(CssParseError::UnsupportedSelector),
+        }
+    }
+
+    /// Returns whether the pseudo-class depends on the `PseudoClassState`
+    fn is_dynamic(&self) -> bool {
+        use self::CssPathPseudoSelector::*;
+        match self {
+            Hover | Active | Focus => true,
+            _ => false,
+        }
+    }
+}
+
+impl CssPathSelector {
+    fn is_combinator(&self) -> bool {
+        use self::CssPathSelector::*;
+        match self {
+            DirectChildren | Children => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for CssPathSelector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::CssPathSelector::*;
+        match *self {
+            Global => write!(f, "*"),
+            Type(ref name) => write!(f, "{}", name),
+            Class(ref class) => write!(f, ".{}", class),
+            Id(ref id) => write!(f, "#{}", id),
+            DirectChildren => write!(f, " > "),
+            Children => write!(f, " "),
+            PseudoSelector(ref pseudo_selector) => write!(f, ":{}", pseudo_selector),
+        }
+    }
+}
+
+impl fmt::Display for CssPathPseudoSelector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::CssPathPseudoSelector::*;
+        match *self {
+            Hover => write!(f, "hover"),
+            Active => write!(f, "active"),
+            Focus => write!(f, "focus"),
+            First => write!(f, "first-child"),
+            Last => write!(f, "last-child"),
+            Only => write!(f, "only-child"),
+            NthChild(ref pattern) => write!(f, "nth-child({})", pattern),
+        }
+    }
+}
+
+/// Writes the pattern as `an+b`, i.e. `2n+1` (`odd`) or `-n+3`
+impl fmt::Display for CssNthChildPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.repeat, self.offset) {
+            (0, offset) => write!(f, "{}", offset),
+            (repeat, 0) => write!(f, "{}n", repeat),
+            (repeat, offset) => write!(f, "{}n{:+}", repeat, offset),
+        }
+    }
+}
+
+impl fmt::Display for CssPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for selector in &self.selectors {
+            write!(f, "{}", selector)?;
+        }
+        Ok(())
+    }
+}
+
+impl CssPath {
+
+    /// Returns the specificity of the selector as `(ids, classes, types)`,
+    /// i.e. `div#main .toolbar > p` has a specificity of `(1, 1, 2)`.
+    /// Pseudo-classes count as classes, the universal selector and the
+    /// combinators don't count.
+    pub(crate) fn get_specificity(&self) -> (usize, usize, usize) {
+        use self::CssPathSelector::*;
+        let ids = self.selectors.iter().filter(|s| if let Id(_) = s { true } else { false }).count();
+        let classes = self.selectors.iter().filter(|s| match s { Class(_) | PseudoSelector(_) => true, _ => false }).count();
+        let types = self.selectors.iter().filter(|s| if let Type(_) = s { true } else { false }).count();
+        (ids, classes, types)
+    }
+
+    /// Returns whether the selector matches the node: the last compound selector
+    /// (i.e. `p` in `.toolbar > p`) has to match the node itself, the compound selectors
+    /// before it have to match the ancestors of the node, as required by the combinators.
+    pub(crate) fn matches_html_element<T: Layout>(
+        &self,
+        node_id: NodeId,
+        arena: &Arena<NodeData<T>>,
+        pseudo_class_state: &PseudoClassState)
+    -> bool
+    {
+
+        let mut compound_selectors = Vec::<&[CssPathSelector]>::new();
+        let mut combinators = Vec::<&CssPathSelector>::new();
+        let mut compound_start = 0;
+
+        for (idx, selector) in self.selectors.iter().enumerate() {
+            if selector.is_combinator() {
+                compound_selectors.push(&self.selectors[compound_start..idx]);
+                combinators.push(selector);
+                compound_start = idx + 1;
+            }
+        }
+        compound_selectors.push(&self.selectors[compound_start..]);
+
+        matches_compound_selectors(&compound_selectors, &combinators, node_id, arena, pseudo_class_state)
+    }
+
+    /// Returns whether the selector contains a `:hover`, `:active` or `:focus` pseudo-class
+    pub(crate) fn has_dynamic_pseudo_selector(&self) -> bool {
+        self.selectors.iter().any(|s| match s {
+            CssPathSelector::PseudoSelector(p) => p.is_dynamic(),
+            _ => false,
+        })
+    }
+}
+
+/// Matches the last compound selector against the node, then walks up
+/// the tree to match the remaining compound selectors (with backtracking,
+/// since a descendant combinator can match any ancestor)
+fn matches_compound_selectors<T: Layout>(
+    compound_selectors: &[&[CssPathSelector]],
+    combinators: &[&CssPathSelector],
+    node_id: NodeId,
+    arena: &Arena<NodeData<T>>,
+    pseudo_class_state: &PseudoClassState)
+-> bool
+{
+    let (last_compound, previous_compounds) = match compound_selectors.split_last() {
+        Some(s) => s,
+        None => return true,
+    };
+
+    if !matches_compound_selector(last_compound, node_id, arena, pseudo_class_state) {
+        return false;
+    }
+
+    let (last_combinator, previous_combinators) = match combinators.split_last() {
+        Some(s) => s,
+        None => return true,
+    };
+
+    match **last_combinator {
+        CssPathSelector::DirectChildren => {
+            match arena[node_id].parent() {
+                Some(parent) => matches_compound_selectors(previous_compounds, previous_combinators, parent, arena, pseudo_class_state),
+                None => false,
+            }
+        },
+        _ => {
+            // skip the node itself, ancestors() starts with the current node
+            node_id.ancestors(arena).skip(1).any(|ancestor|
+                matches_compound_selectors(previous_compounds, previous_combinators, ancestor, arena, pseudo_class_state)
+            )
+        }
+    }
+}
+
+/// Returns whether all the simple selectors of a compound selector
+/// (i.e. `div#main.active:hover`) match the node
+fn matches_compound_selector<T: Layout>(
+    compound_selector: &[CssPathSelector],
+    node_id: NodeId,
+    arena: &Arena<NodeData<T>>,
+    pseudo_class_state: &PseudoClassState)
+-> bool
+{
+    use self::CssPathSelector::*;
+    use self::CssPathPseudoSelector::*;
+
+    let node = &arena[node_id];
+    // 1-based index of the node among its siblings
+    let index_in_parent = || node_id.preceding_siblings(arena).count();
+
+    compound_selector.iter().all(|selector| match selector {
+        Global => true,
+        Type(node_type) => node.data.node_type.get_css_id() == node_type,
+        Class(class) => node.data.classes.iter().any(|c| c == class),
+        Id(id) => node.data.id.as_ref() == Some(id),
+        PseudoSelector(Hover) => pseudo_class_state.hovered.contains(&node_id),
+        PseudoSelector(Active) => pseudo_class_state.active.contains(&node_id),
+        PseudoSelector(Focus) => pseudo_class_state.focused == Some(node_id),
+        PseudoSelector(First) => node.previous_sibling().is_none(),
+        PseudoSelector(Last) => node.next_sibling().is_none(),
+        PseudoSelector(Only) => node.previous_sibling().is_none() && node.next_sibling().is_none(),
+        PseudoSelector(NthChild(pattern)) => pattern.matches(index_in_parent()),
+        DirectChildren | Children => false,
+    })
+}
+
+/// Pushes a combinator onto the current selector path, ignoring leading combinators
+/// and merging two consecutive combinators (`div > p` may be tokenized as `div`,
+/// ` `, `>`, `p`, in which case the `>` wins)
+fn push_combinator(path: &mut Vec<CssPathSelector>, combinator: CssPathSelector) {
+    match path.last().and_then(|last| Some(last.is_combinator())) {
+        None => { },
+        Some(true) => {
+            if combinator == CssPathSelector::DirectChildren {
+                path.pop();
+                path.push(combinator);
+            }
+        },
+        Some(false) => path.push(combinator),
+    }
+}
+
+/// Finishes the current selector (at a `,` or a `{`) and pushes it to the selectors of the current block
+fn finish_css_path(path: &mut Vec<CssPathSelector>, paths: &mut Vec<CssPath>) {
+    while path.last().and_then(|last| Some(last.is_combinator())) == Some(true) {
+        path.pop();
+    }
+    if path.is_empty() {
+        path.push(CssPathSelector::Global);
+    }
+    paths.push(CssPath { selectors: path.drain(..).collect() });
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum CssDeclaration {
+    Static(ParsedCssProperty),
+    Dynamic(DynamicCssProperty),
+    /// The declaration of a custom property, i.e. `--accent: #3070ff`.
+    /// The value is stored unparsed, since it can be used by any property.
+    CustomProperty(String),
+    /// A value that references custom properties, i.e. `color: var(--accent, black)`, or the
+    /// color of the node, i.e. `border: 1px solid currentColor`. It can only be parsed once
+    /// the custom properties and the `color` of the node are known.
+    Unresolved(String),
+    /// `inherit`, `initial` or `unset`, which is resolved during the cascade
+    Keyword(CssWideKeyword),
+}
+
+impl CssDeclaration {
+    /// Returns the property that the declaration sets (see `css_parser::get_property_name`),
+    /// `None` if the declaration isn't resolved yet
+    pub(crate) fn get_property_name(&self) -> Option<&'static str> {
+        match *self {
+            CssDeclaration::Static(ref property) => Some(get_property_name(property.get_key())),
+            CssDeclaration::Dynamic(ref dynamic) => Some(get_property_name(dynamic.default.get_key())),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the children of a node inherit the declaration, i.e. `color`.
+    /// The `font-size` is inherited by the layout instead, as the computed size in pixels
+    /// (see `ui_solver`) - inheriting a `1.5em` would make the text larger on every level.
+    pub(crate) fn is_inherited(&self) -> bool {
+        self.get_property_name().map(|property| is_inherited_property(property) && property != "font-size").unwrap_or(false)
+    }
+
+    /// Returns the value of a static or dynamic declaration. For a dynamic declaration, the
+    /// override (see `FakeCss::set_dynamic_property`) is used if there is one.
+    pub(crate) fn get_value<'a>(&'a self, dynamic_css_overrides: &'a FastHashMap<String, ParsedCssProperty>)
+    -> Option<&'a ParsedCssProperty>
+    {
+        match *self {
+            CssDeclaration::Static(ref property) => Some(property),
+            CssDeclaration::Dynamic(ref dynamic) => Some(dynamic_css_overrides.get(&dynamic.dynamic_id).unwrap_or(&dynamic.default)),
+            _ => None,
+        }
+    }
+}
+
+/// The keywords that can be used as the value of any property.
+///
+/// For the longhands of `padding`, `margin`, `overflow` and `border`, the keyword only applies to
+/// the side / direction of the longhand (i.e. `padding-top: initial` only resets the top side).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum CssWideKeyword {
+    /// Use the value of the parent node
+    Inherit,
+    /// Reset the property to its initial value, i.e. remove all earlier declarations of it
+    Initial,
+    /// `inherit` for inherited properties (i.e. `color`), `initial` for all other properties
+    Unset,
+}
+
+impl CssWideKeyword {
+    fn from_str(input: &str) -> Option<Self> {
+        match input {
+            "inherit" => Some(CssWideKeyword::Inherit),
+            "initial" => Some(CssWideKeyword::Initial),
+            "unset" => Some(CssWideKeyword::Unset),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the node uses the value of the parent for the property with the `key`
+    pub(crate) fn inherits(&self, key: &str) -> bool {
+        match *self {
+            CssWideKeyword::Inherit => true,
+            CssWideKeyword::Initial => false,
+            CssWideKeyword::Unset => is_inherited_property(key),
+        }
+    }
+}
+
+impl fmt::Display for CssWideKeyword {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CssWideKeyword::Inherit => write!(f, "inherit"),
+            CssWideKeyword::Initial => write!(f, "initial"),
+            CssWideKeyword::Unset => write!(f, "unset"),
+        }
+    }
+}
+
+/// A `CssProperty` is a type of CSS Rule,
+/// but the contents of the rule is dynamic.
+///
+/// Azul has "dynamic properties", i.e.:
+///
+/// ```no_run,ignore
+/// #my_div {
+///    padding: {{ my_dynamic_property_id | 400px }};
+/// }
+/// ```
+///
+/// At runtime the CSS is immutable (which is a performance optimization - if we
+/// can assume that the CSS never changes at runtime), we can do some optimizations on it.
+/// Also it leads to cleaner code, since both animations and conditional CSS styling
+/// now use the same API.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct DynamicCssProperty {
+    pub(crate) dynamic_id: String,
+    pub(crate) default: ParsedCssProperty,
+}
+
+/// A `@keyframes` rule, i.e. `@keyframes fade { from { background-color: red; } to { ... } }`.
+/// The keyframes are played by the `animation` property of a node.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CssKeyframes {
+    /// The name that the `animation` property refers to
+    pub(crate) name: String,
+    /// The keyframes, sorted by their offset
+    pub(crate) keyframes: Vec<CssKeyframe>,
+    /// The queries of the `@media` blocks that the rule is nested in, the
+    /// rule is only used while all of them match
+    pub(crate) media_queries: Vec<CssMediaQueryList>,
+    /// Whether the rule is part of the user-agent stylesheet, see `Css::layered`
+    pub(crate) origin: CssOrigin,
+    /// Whether the rule comes from the file of `Css::hot_reload`, see `CssRule::hot_reloaded`
+    pub(crate) hot_reloaded: bool,
+}
+
+/// A single keyframe of a `@keyframes` rule, i.e. `50% { width: 5px; }`
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CssKeyframe {
+    /// The position of the keyframe in the animation, `from` = 0.0, `to` = 1.0
+    pub(crate) offset: f32,
+    pub(crate) properties: Vec<ParsedCssProperty>,
+}
+
+impl CssRule {
+    pub fn needs_relayout(&self) -> bool {
+        // RELAYOUT_RULES.iter().any(|r| self.declaration.0 == *r)
+        // TODO
+        true
+    }
+}
+
+/// Writes the value of the declaration, i.e. `[[ my_id | 400px ]]` for a dynamic property
+impl fmt::Display for CssDeclaration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CssDeclaration::Static(ref property) => write!(f, "{}", property.to_css_string()),
+            CssDeclaration::Dynamic(ref dynamic) => write!(f, "[[ {} | {} ]]", dynamic.dynamic_id, dynamic.default.to_css_string()),
+            CssDeclaration::CustomProperty(ref value) |
+            CssDeclaration::Unresolved(ref value) => write!(f, "{}", value),
+            CssDeclaration::Keyword(ref keyword) => write!(f, "{}", keyword),
+        }
+    }
+}
+
+/// Writes the declaration of the rule, i.e. `width: 5px !important`
+impl fmt::Display for CssRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.declaration.0, self.declaration.1)?;
+        if self.important {
+            write!(f, " !important")?;
+        }
+        Ok(())
+    }
+}
+
+impl Css {
+
+    /// Creates an empty set of CSS rules
+    pub fn empty() -> Self {
+        Self {
+            rules: Vec::new(),
+            dynamic_css_overrides: FastHashMap::default(),
+            pseudo_class_state: PseudoClassState::default(),
+            custom_property_overrides: FastHashMap::default(),
+            media_query_state: MediaQueryState::default(),
+            hot_reload_source: None,
+            keyframes: Vec::new(),
+            animation_state: AnimationState::default(),
+        }
+    }
+
+    /// Parses a CSS string (single-threaded) and returns the parsed rules,
+    /// or the first error in the stylesheet
+    ///
+    /// Returns an error if the stylesheet uses `@import`, use
+    /// `new_from_string_with_loader` for stylesheets with imports.
+    pub fn new_from_string<'a>(css_string: &'a str) -> Result<Self, CssParseErrorWithLocation<'a>> {
+        Self::parse_strict(css_string, None)
+    }
+
+    /// Same as `new_from_string`, but the stylesheets of `@import` rules are
+    /// loaded with the `loader` and their rules are inserted at the position
+    /// of the `@import` rule
+    pub fn new_from_string_with_loader<'a, L: CssLoader>(css_string: &'a str, loader: &L)
+    -> Result<Self, CssParseErrorWithLocation<'a>>
+    {
+        Self::parse_strict(css_string, Some(loader))
+    }
+
+    /// Parses a CSS string the way a browser does: invalid rules and declarations
+    /// are skipped instead of failing the whole stylesheet. Returns the rules that
+    /// could be parsed, together with the errors of the skipped parts (in source order).
+    pub fn new_from_string_lossy<'a>(css_string: &'a str) -> (Self, Vec<CssParseErrorWithLocation<'a>>) {
+        Self::parse(css_string, None)
+    }
+
+    /// Same as `new_from_string_lossy`, but with a loader for the `@import` rules,
+    /// see `new_from_string_with_loader`
+    pub fn new_from_string_lossy_with_loader<'a, L: CssLoader>(css_string: &'a str, loader: &L)
+    -> (Self, Vec<CssParseErrorWithLocation<'a>>)
+    {
+        Self::parse(css_string, Some(loader))
+    }
+
+    /// Appends the rules of `other` to the rules of `self`. Both stylesheets keep their
+    /// layers - on equal specificity, the rules of `other` win, since they come later.
+    pub fn merge(&mut self, mut other: Css) {
+        self.rules.append(&mut other.rules);
+        self.keyframes.append(&mut other.keyframes);
+        if other.hot_reload_source.is_some() {
+            self.hot_reload_source = other.hot_reload_source;
+        }
+    }
+
+    /// Loads the stylesheet from a file and reloads it whenever the file is modified,
+    /// so that the style can be edited while the app is running. Meant for development,
+    /// use `include_str!` and `Css::new_from_string` for the final app.
+    ///
+    /// The file is checked for modifications on every frame. When it has changed, the window
+    /// is restyled and laid out again with the new rules. If the file can't be parsed, the
+    /// error is logged and the last stylesheet that could be parsed stays active. `@import`
+    /// paths are relative to the directory of the file, the stylesheet is reloaded as well
+    /// when one of the imported files is modified.
+    ///
+    /// The stylesheet can be layered on top of the native style with
+    /// `Css::layered(Css::native(), Css::hot_reload("main.css"))`. On a reload, only the
+    /// rules of the file are replaced, the rules of all other stylesheets are kept.
+    pub fn hot_reload<P: Into<PathBuf>>(path: P) -> Self {
+        let path = path.into();
+        let mut css = Self::empty();
+
+        if let Err(e) = fs::metadata(&path) {
+            error!("Could not load stylesheet {:?}: {}", path, e);
+        }
+
+        css.hot_reload_source = Some(HotReloadSource { path: path, loaded_files: Vec::new() });
+        css.reload_if_modified();
+        css
+    }
+
+    /// Re-parses the stylesheet of `Css::hot_reload` if the file (or one of the files that
+    /// it imports) has been modified since it was last loaded. Returns whether the rules
+    /// have been replaced, in which case the caller has to restyle the window.
+    pub(crate) fn reload_if_modified(&mut self) -> bool {
+        let (path, last_modified) = match self.hot_reload_source {
+            Some(ref source) => {
+                // The file might not exist for a moment while an editor saves it,
+                // so errors are ignored here - it'll be reloaded once it exists again
+                let modified = match get_modified_time(&source.path) {
+                    Some(modified) => modified,
+                    None => return false,
+                };
+                if !source.is_modified() {
+                    return false;
+                }
+                (source.path.clone(), modified)
+            },
+            None => return false,
+        };
+
+        let loader = CssFileLoader::new(path.parent().and_then(|parent| Some(parent.to_path_buf())).unwrap_or_default());
+        let reloaded = self.reload_from_file(&path, &loader);
+
+        if let Some(ref mut source) = self.hot_reload_source {
+            let imported_files = loader.get_loaded_files().into_iter().map(|file| {
+                let modified = get_modified_time(&file);
+                (file, modified)
+            });
+            source.loaded_files = Some((path, Some(last_modified))).into_iter().chain(imported_files).collect();
+        }
+
+        reloaded
+    }
+
+    /// Replaces the rules and keyframes of the hot-reloaded file with the ones in the file at `path`
+    fn reload_from_file(&mut self, path: &Path, loader: &CssFileLoader) -> bool {
+        let css_string = match fs::read_to_string(path) {
+            Ok(css_string) => css_string,
+            Err(e) => {
+                error!("Could not load stylesheet {:?}: {}", path, e);
+                return false;
+            }
+        };
+
+        match Css::new_from_string_with_loader(&css_string, loader) {
+            Ok(mut css) => {
+                for rule in &mut css.rules {
+                    rule.hot_reloaded = true;
+                }
+                for keyframes in &mut css.keyframes {
+                    keyframes.hot_reloaded = true;
+                }
+                replace_hot_reloaded(&mut self.rules, css.rules, |rule| rule.hot_reloaded);
+                replace_hot_reloaded(&mut self.keyframes, css.keyframes, |keyframes| keyframes.hot_reloaded);
+                true
+            },
+            Err(e) => {
+                error!("Could not parse stylesheet {:?}, keeping the last working stylesheet: {}", path, e);
+                false
+            }
+        }
+    }
+
+    /// Combines a user-agent stylesheet (usually `Css::native()`) with the stylesheet of
+    /// the app. The rules of the app always win over the user-agent rules, regardless of
+    /// their specificity - except for `!important` user-agent rules, which win over
+    /// any rule of the app.
+    ///
+    /// ```no_run,ignore
+    /// let css = Css::layered(Css::native(), Css::new_from_string(MY_CSS)?);
+    /// ```
+    pub fn layered(user_agent: Css, app: Css) -> Self {
+        let mut css = user_agent.into_user_agent_layer();
+        css.merge(app);
+        css
+    }
+
+    /// Adds the default stylesheet of a widget to the user-agent layer, so that the
+    /// widget is styled even if the app doesn't style it. `Css::native()` already
+    /// contains the default styles of the built-in widgets.
+    pub fn with_default_style<W: DefaultStyle>(mut self) -> Self {
+        self.merge(W::default_style().into_user_agent_layer());
+        self
+    }
+
+    /// Moves all rules into the user-agent layer of the cascade
+    fn into_user_agent_layer(mut self) -> Self {
+        for rule in &mut self.rules {
+            rule.origin = CssOrigin::UserAgent;
+        }
+        for keyframes in &mut self.keyframes {
+            keyframes.origin = CssOrigin::UserAgent;
+        }
+        self
+    }
+
+    fn parse_strict<'a>(css_string: &'a str, loader: Option<&CssLoader>)
+    -> Result<Self, CssParseErrorWithLocation<'a>>
+    {
+        let (css, mut errors) = Self::parse(css_string, loader);
+        if errors.is_empty() {
+            Ok(css)
+        } else {
+            Err(errors.remove(0))
+        }
+    }
+
+    fn parse<'a>(css_string: &'a str, loader: Option<&CssLoader>) -> (Self, Vec<CssParseErrorWithLocation<'a>>) {
+        let (css_rules, keyframes, errors) = CssParser::parse(css_string, &[], loader, Vec::new());
+
+        let css = Self {
+            rules: css_rules,
+            dynamic_css_overrides: FastHashMap::default(),
+            pseudo_class_state: PseudoClassState::default(),
+            custom_property_overrides: FastHashMap::default(),
+            media_query_state: MediaQueryState::default(),
+            hot_reload_source: None,
+            keyframes: keyframes,
+            animation_state: AnimationState::default(),
+        };
+
+        (css, errors)
+    }
+
+    /// Returns the native style for the OS, including the default style of the built-in widgets.
+    /// All of its rules are user-agent rules, see `Css::layered`
+    #[cfg(target_os="windows")]
+    pub fn native() -> Self {
+        Self::new_from_string(NATIVE_CSS_WINDOWS).unwrap().into_user_agent_layer()
+            .with_default_style::<Button>()
+    }
+
+    /// Returns the native style for the OS, including the default style of the built-in widgets.
+    /// All of its rules are user-agent rules, see `Css::layered`
+    #[cfg(target_os="linux")]
+    pub fn native() -> Self {
+        Self::new_from_string(NATIVE_CSS_LINUX).unwrap().into_user_agent_layer()
+            .with_default_style::<Button>()
+    }
+
+    /// Returns the native style for the OS, including the default style of the built-in widgets.
+    /// All of its rules are user-agent rules, see `Css::layered`
+    #[cfg(target_os="macos")]
+    pub fn native() -> Self {
+        Self::new_from_string(NATIVE_CSS_MACOS).unwrap().into_user_agent_layer()
+            .with_default_style::<Button>()
+    }
+}
+
+/// Pretty-prints the stylesheet: consecutive rules with the same selector (and the
+/// same `@media` blocks) are written as one rule set, in the order of the stylesheet.
+///
+/// Parsing the output results in the same rules again. The origin of the rules
+/// (see `Css::layered`) is not part of the output.
+impl fmt::Display for Css {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const INDENT: &str = "    ";
+
+        let mut current_media_queries: &[CssMediaQueryList] = &[];
+        let mut current_path = None;
+
+        for rule in &self.rules {
+            if current_media_queries != &rule.media_queries[..] || current_path != Some(&rule.path) {
+                let depth = current_media_queries.len();
+                if current_path.is_some() {
+                    writeln!(f, "{}}}", INDENT.repeat(depth))?;
+                }
+
+                // close the @media blocks that the new rule isn't nested in, then open the new ones
+                let common = current_media_queries.iter().zip(rule.media_queries.iter())
+                    .take_while(|(a, b)| a == b).count();
+                for depth in (common..current_media_queries.len()).rev() {
+                    writeln!(f, "{}}}", INDENT.repeat(depth))?;
+                }
+                if current_path.is_some() || common > 0 {
+                    writeln!(f)?;
+                }
+                for (depth, media_query) in rule.media_queries.iter().enumerate().skip(common) {
+                    writeln!(f, "{}@media {} {{", INDENT.repeat(depth), media_query)?;
+                }
+
+                writeln!(f, "{}{} {{", INDENT.repeat(rule.media_queries.len()), rule.path)?;
+                current_media_queries = &rule.media_queries;
+                current_path = Some(&rule.path);
+            }
+
+            writeln!(f, "{}{};", INDENT.repeat(current_media_queries.len() + 1), rule)?;
+        }
+
+        if current_path.is_some() {
+            writeln!(f, "{}}}", INDENT.repeat(current_media_queries.len()))?;
+        }
+        for depth in (0..current_media_queries.len()).rev() {
+            writeln!(f, "{}}}", INDENT.repeat(depth))?;
+        }
+
+        for (idx, keyframes) in self.keyframes.iter().enumerate() {
+            if idx > 0 || current_path.is_some() {
+                writeln!(f)?;
+            }
+            for (depth, media_query) in keyframes.media_queries.iter().enumerate() {
+                writeln!(f, "{}@media {} {{", INDENT.repeat(depth), media_query)?;
+            }
+            let depth = keyframes.media_queries.len();
+            writeln!(f, "{}@keyframes {} {{", INDENT.repeat(depth), keyframes.name)?;
+            for keyframe in &keyframes.keyframes {
+                // rounded, so that i.e. 0.3 is written as `30%` instead of `30.000002%`
+                writeln!(f, "{}{}% {{", INDENT.repeat(depth + 1), (keyframe.offset * 100_000.0).round() / 1000.0)?;
+                for property in &keyframe.properties {
+                    writeln!(f, "{}{};", INDENT.repeat(depth + 2), property)?;
+                }
+                writeln!(f, "{}}}", INDENT.repeat(depth + 1))?;
+            }
+            for depth in (0..depth + 1).rev() {
+                writeln!(f, "{}}}", INDENT.repeat(depth))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The state of the parser while parsing a stylesheet (and its `@media` blocks).
+///
+/// The parser doesn't stop at the first error, invalid rules and declarations are
+/// skipped and their errors are collected, see `Css::new_from_string_lossy`.
+struct CssParser<'a, 'l> {
+    /// The rules that have been parsed so far
+    rules: Vec<CssRule>,
+    /// The `@keyframes` rules that have been parsed so far
+    keyframes: Vec<CssKeyframes>,
+    /// The errors, together with the selector, declaration or at-rule that caused them
+    errors: Vec<(CssParseError<'a>, &'a str)>,
+    loader: Option<&'l CssLoader>,
+    /// The paths of the stylesheets that are currently being imported
+    import_stack: Vec<String>,
+}
+
+impl<'a, 'l> CssParser<'a, 'l> {
+
+    /// Parses a whole stylesheet, `media_queries` are the queries that the
+    /// stylesheet is nested in (i.e. the queries of an `@import`).
+    /// Returns the parsed rules and keyframes and the errors, located in the `css_string`
+    fn parse(
+        css_string: &'a str,
+        media_queries: &[CssMediaQueryList],
+        loader: Option<&'l CssLoader>,
+        import_stack: Vec<String>)
+    -> (Vec<CssRule>, Vec<CssKeyframes>, Vec<CssParseErrorWithLocation<'a>>)
+    {
+        let mut parser = Self {
+            rules: Vec::new(),
+            keyframes: Vec::new(),
+            errors: Vec::new(),
+            loader: loader,
+            import_stack: import_stack,
+        };
+
+        parser.parse_stylesheet(css_string, media_queries);
+
+        // the errors of the at-rule blocks are found before the ones inside of
+        // the rules, sort them so that they are in the order of the source
+        parser.errors.sort_by_key(|&(_, snippet)| get_offset(css_string, snippet));
+
+        let errors = parser.errors.into_iter()
+            .map(|(error, snippet)| CssParseErrorWithLocation::new(css_string, error, snippet))
+            .collect();
+
+        (parser.rules, parser.keyframes, errors)
+    }
+
+    /// Parses the rules and at-rules of a stylesheet, `media_queries` are
+    /// the queries of the `@media` blocks that `css_string` is nested in
+    fn parse_stylesheet(&mut self, css_string: &'a str, media_queries: &[CssMediaQueryList]) {
+        for block in split_at_rules(css_string, &mut self.errors) {
+            match block {
+                CssBlock::Rules(rules) => {
+                    self.parse_rules(rules, media_queries);
+                },
+                CssBlock::AtRule { name, prelude, block, header } => {
+                    if let Err(e) = self.parse_at_rule(name, prelude, block, header, media_queries) {
+                        self.errors.push((e, header));
+                    }
+                },
+            }
+        }
+    }
+
+    /// Parses an `@media`, `@import` or `@keyframes` rule, other at-rules are not supported
+    fn parse_at_rule(
+        &mut self,
+        name: &'a str,
+        prelude: &'a str,
+        block: Option<&'a str>,
+        header: &'a str,
+        media_queries: &[CssMediaQueryList])
+    -> Result<(), CssParseError<'a>>
+    {
+        let mut nested_media_queries = media_queries.to_vec();
+
+        match (name, block) {
+            ("media", Some(block)) => {
+                nested_media_queries.push(CssMediaQueryList::from_str(prelude)?);
+                self.parse_stylesheet(block, &nested_media_queries);
+            },
+            ("import", None) => {
+                let (path, import_media_queries) = split_import_prelude(prelude)
+                    .ok_or_else(|| CssImportError::InvalidImport(prelude.to_string()))?;
+                if !import_media_queries.is_empty() {
+                    nested_media_queries.push(CssMediaQueryList::from_str(import_media_queries)?);
+                }
+                self.parse_import(path, header, &nested_media_queries)?;
+            },
+            ("keyframes", Some(block)) => {
+                self.parse_keyframes(prelude, block, media_queries)?;
+            },
+            _ => return Err(CssParseError::UnsupportedAtRule(name)),
+        }
+
+        Ok(())
+    }
+
+    /// Loads the stylesheet of an `@import` rule and parses it. The errors of the imported
+    /// stylesheet are located in the imported stylesheet and attributed to the `@import`.
+    fn parse_import(&mut self, path: &'a str, header: &'a str, media_queries: &[CssMediaQueryList])
+    -> Result<(), CssParseError<'a>>
+    {
+        let loader = self.loader.ok_or_else(|| CssImportError::NoLoader(path.to_string()))?;
+
+        if self.import_stack.iter().any(|imported_path| imported_path == path) {
+            return Err(CssImportError::CyclicImport(path.to_string()).into());
+        }
+
+        let imported_css = loader.load_css(path).ok_or_else(|| CssImportError::NotFound(path.to_string()))?;
+
+        let mut import_stack = self.import_stack.clone();
+        import_stack.push(path.to_string());
+
+        let (mut imported_rules, mut imported_keyframes, imported_errors) =
+            CssParser::parse(&imported_css, media_queries, Some(loader), import_stack);
+
+        self.rules.append(&mut imported_rules);
+        self.keyframes.append(&mut imported_keyframes);
+        for imported_error in imported_errors {
+            let error = CssImportError::ParseError { path: path.to_string(), error: imported_error.to_string() };
+            self.errors.push((error.into(), header));
+        }
+
+        Ok(())
+    }
+
+    /// Parses the block of a `@keyframes` rule, i.e. `from { width: 0px; } 50%, to { width: 5px; }`.
+    /// Invalid declarations are skipped, like in a normal rule, but an invalid keyframe
+    /// selector discards the whole `@keyframes` rule.
+    fn parse_keyframes(&mut self, name: &'a str, block: &'a str, media_queries: &[CssMediaQueryList])
+    -> Result<(), CssParseError<'a>>
+    {
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(CssParseError::InvalidKeyframes(name));
+        }
+
+        let mut keyframes = Vec::new();
+        let mut position = 0;
+
+        while let Some(block_start) = find_unnested_byte(block, position, b"{") {
+            let block_end = find_unnested_byte(block, block_start + 1, b"}").ok_or(CssParseError::UnclosedBlock)?;
+            let selector = skip_comments(&block[position..block_start]).trim_right();
+
+            let offsets = selector.split(',')
+                .map(|offset| parse_keyframe_offset(offset.trim()).ok_or(CssParseError::InvalidKeyframes(selector)))
+                .collect::<Result<Vec<f32>, _>>()?;
+
+            let properties = self.parse_keyframe_declarations(&block[block_start + 1..block_end]);
+            keyframes.extend(offsets.into_iter().map(|offset| CssKeyframe { offset: offset, properties: properties.clone() }));
+            position = block_end + 1;
+        }
+
+        let rest = skip_comments(&block[position..]).trim_right();
+        if !rest.is_empty() {
+            return Err(CssParseError::MalformedCss);
+        }
+
+        // keyframes with the same offset keep their order, the later one wins
+        keyframes.sort_by(|a: &CssKeyframe, b: &CssKeyframe| a.offset.partial_cmp(&b.offset).unwrap());
+
+        self.keyframes.push(CssKeyframes {
+            name: name.to_string(),
+            keyframes: keyframes,
+            media_queries: media_queries.to_vec(),
+            origin: CssOrigin::App,
+            hot_reloaded: false,
+        });
+
+        Ok(())
+    }
+
+    /// Parses the declarations of a single keyframe, i.e. `width: 0px; color: red;`
+    fn parse_keyframe_declarations(&mut self, declarations: &'a str) -> Vec<ParsedCssProperty> {
+        let mut properties = Vec::new();
+        let mut position = 0;
+
+        while position < declarations.len() {
+            let declaration_end = find_unnested_byte(declarations, position, b";").unwrap_or(declarations.len());
+            let declaration = skip_comments(&declarations[position..declaration_end]).trim_right();
+            position = declaration_end + 1;
+
+            if declaration.is_empty() {
+                continue;
+            }
+
+            let (key, value) = match declaration.find(':') {
+                Some(colon) => (declaration[..colon].trim(), &declaration[colon + 1..]),
+                None => {
+                    self.errors.push((CssParseError::MalformedCss, declaration));
+                    continue;
+                }
+            };
+
+            // like in the browser, `!important` declarations are ignored in keyframes
+            let (value, important) = split_important(value);
+            if important {
+                continue;
+            }
+
+            match ParsedCssProperty::from_kv(key, value) {
+                Ok(property) => properties.push(property),
+                Err(e) => self.errors.push((e.into(), declaration)),
+            }
+        }
+
+        properties
+    }
+
+    /// Parses a list of rules (without any at-rules). The rules are parsed
+    /// one by one, so that an invalid rule can be skipped.
+    fn parse_rules(&mut self, css_string: &'a str, media_queries: &[CssMediaQueryList]) {
+        let mut position = 0;
+
+        while let Some(block_start) = find_unnested_byte(css_string, position, b"{") {
+            match find_unnested_byte(css_string, block_start + 1, b"}") {
+                Some(block_end) => {
+                    self.parse_rule(&css_string[position..block_start], &css_string[position..block_end + 1], media_queries);
+                    position = block_end + 1;
+                },
+                None => {
+                    // non-even number of blocks
+                    self.errors.push((CssParseError::UnclosedBlock, skip_comments(&css_string[position..]).trim_right()));
+                    return;
+                }
+            }
+        }
+
+        // Text after the last block, i.e. the `p` in `div { } p`
+        let rest = skip_comments(&css_string[position..]).trim_right();
+        if !rest.is_empty() {
+            self.errors.push((CssParseError::MalformedCss, rest));
+        }
+    }
+
+    /// Parses a single rule, i.e. `div.a, div.b { width: 100px; }`. If the `selector`
+    /// is invalid, the whole rule is skipped, invalid declarations are skipped one by one.
+    fn parse_rule(&mut self, selector: &'a str, rule: &'a str, media_queries: &[CssMediaQueryList]) {
+        use simplecss::{Tokenizer, Token, Combinator};
+
+        let selector = skip_comments(selector).trim_right();
+        let mut tokenizer = Tokenizer::new(rule);
+
+        // TODO: This could be more efficient. We don't even need to clone the
+        // strings, but this is just a quick-n-dirty CSS parser
+        // This will also use up a lot of memory, since the strings get duplicated
+
+        let mut parser_in_block = false;
+        // The selector that is currently being parsed
+        let mut current_path = Vec::<CssPathSelector>::new();
+        // All selectors of the current block, i.e. `div.a, div.b { }`
+        let mut current_paths = Vec::<CssPath>::new();
+
+        loop {
+            let token = match tokenizer.parse_next() {
+                Ok(token) => token,
+                Err(e) => {
+                    // The tokenizer can't continue after a syntax error, so the rest of the rule is skipped
+                    let (row, col) = get_syntax_error_position(&e);
+                    self.errors.push((CssParseError::ParseError(e), get_rest_of_line(rule, row, col)));
+                    return;
+                }
+            };
+
+            let selector_result = match token {
+                Token::EndOfStream => {
+                    return;
+                },
+                Token::BlockStart => {
+                    parser_in_block = true;
+                    finish_css_path(&mut current_path, &mut current_paths);
+                    Ok(())
+                },
+                Token::BlockEnd => {
+                    parser_in_block = false;
+                    Ok(())
+                },
+                Token::Declaration(key, val) => {
+                    if !parser_in_block {
+                        Err(CssParseError::MalformedCss)
+                    } else {
+                        match parse_declaration(key, val) {
+                            Ok((key, css_decl, important)) => {
+                                for path in &current_paths {
+                                    self.rules.push(CssRule {
+                                        path: path.clone(),
+                                        declaration: (key.to_string(), css_decl.clone()),
+                                        important: important,
+                                        media_queries: media_queries.to_vec(),
+                                        origin: CssOrigin::App,
+                                        hot_reloaded: false,
+                                    });
+                                }
+                            },
+                            Err(e) => {
+                                self.errors.push((e, get_span(rule, key, val)));
+                            },
+                        }
+                        Ok(())
+                    }
+                },
+                // selectors are not allowed inside of a block
+                _ if parser_in_block => {
+                    Err(CssParseError::MalformedCss)
+                },
+                Token::Comma => {
+                    finish_css_path(&mut current_path, &mut current_paths);
+                    Ok(())
+                },
+                Token::UniversalSelector => {
+                    current_path.push(CssPathSelector::Global);
+                    Ok(())
+                },
+                Token::TypeSelector(div_type) => {
+                    if div_type == "*" {
+                        current_path.push(CssPathSelector::Global);
+                    } else {
+                        current_path.push(CssPathSelector::Type(div_type.to_string()));
+                    }
+                    Ok(())
+                },
+                Token::IdSelector(id) => {
+                    current_path.push(CssPathSelector::Id(id.to_string()));
+                    Ok(())
+                },
+                Token::ClassSelector(class) => {
+                    current_path.push(CssPathSelector::Class(class.to_string()));
+                    Ok(())
+                },
+                Token::PseudoClass(pseudo_class) => {
+                    CssPathPseudoSelector::from_str(pseudo_class).and_then(|pseudo_selector| {
+                        current_path.push(CssPathSelector::PseudoSelector(pseudo_selector));
+                        Ok(())
+                    })
+                },
+                Token::Combinator(combinator) => {
+                    match combinator {
+                        Combinator::Space => { push_combinator(&mut current_path, CssPathSelector::Children); Ok(()) },
+                        Combinator::GreaterThan => { push_combinator(&mut current_path, CssPathSelector::DirectChildren); Ok(()) },
+                        _ => Err(CssParseError::UnsupportedSelector),
+                    }
+                },
+                _ => Ok(()),
+            };
+
+            if let Err(e) = selector_result {
+                // skip the whole rule
+                self.errors.push((e, selector));
+                return;
+            }
+        }
+    }
+}
+
+/// Parses the selector of a keyframe, `from` = 0.0, `50%` = 0.5, `to` = 1.0
+fn parse_keyframe_offset(selector: &str) -> Option<f32> {
+    match selector {
+        "from" => Some(0.0),
+        "to" => Some(1.0),
+        _ if selector.ends_with('%') => {
+            let percentage = selector[..selector.len() - 1].parse::<f32>().ok()?;
+            if percentage >= 0.0 && percentage <= 100.0 {
+                Some(percentage / 100.0)
+            } else {
+                None
+            }
+        },
+        _ => None,
+    }
+}
+
+/// Parses a declaration, i.e. `width: 100px !important`, into its key, value and importance
+fn parse_declaration<'a>(key: &'a str, value: &'a str) -> Result<(&'a str, CssDeclaration, bool), CssParseError<'a>> {
+    let key = key.trim();
+    let (value, important) = split_important(value);
+
+    let css_decl = if key.starts_with("--") {
+        CssDeclaration::CustomProperty(value.to_string())
+    } else if value.contains(VAR_FUNCTION) || contains_current_color(value) {
+        CssDeclaration::Unresolved(value.to_string())
+    } else if let Some(keyword) = CssWideKeyword::from_str(value) {
+        // the keyword itself is no valid value, but unsupported keys still have to be reported
+        if let Err(e @ CssParsingError::UnsupportedCssKey(_, _)) = ParsedCssProperty::from_kv(key, value) {
+            return Err(DynamicCssParseError::from(e).into());
+        }
+        CssDeclaration::Keyword(keyword)
+    } else {
+        // see if the Declaration is static or dynamic
+        //
+        // css_val = "center" | "{{ my_dynamic_id | center }}"
+        determine_static_or_dynamic_css_property(key, value)?
+    };
+
+    Ok((key, css_decl, important))
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub(crate) enum CssDeclaration {
-    Static(ParsedCssProperty),
-    Dynamic(DynamicCssProperty),
+/// Returns the byte offset of `slice` in `source`, `slice` has to be a slice of `source`
+fn get_offset(source: &str, slice: &str) -> usize {
+    (slice.as_ptr() as usize).saturating_sub(source.as_ptr() as usize).min(source.len())
 }
 
-/// A `CssProperty` is a type of CSS Rule,
-/// but the contents of the rule is dynamic.
-///
-/// Azul has "dynamic properties", i.e.:
-///
-/// ```no_run,ignore
-/// #my_div {
-///    padding: {{ my_dynamic_property_id | 400px }};
-/// }
-/// ```
-///
-/// At runtime the CSS is immutable (which is a performance optimization - if we
-/// can assume that the CSS never changes at runtime), we can do some optimizations on it.
-/// Also it leads to cleaner code, since both animations and conditional CSS styling
-/// now use the same API.
-///
-#[derive(Debug, Clone, PartialEq)]
-pub(crate) struct DynamicCssProperty {
-    pub(crate) dynamic_id: String,
-    pub(crate) default: ParsedCssProperty,
+/// Returns the part of `source` from the start of `first` to the end of `last`,
+/// both have to be slices of `source`, i.e. the whole declaration from its key and value
+fn get_span<'a>(source: &'a str, first: &str, last: &str) -> &'a str {
+    let start = get_offset(source, first);
+    let end = (get_offset(source, last) + last.len()).min(source.len()).max(start);
+    &source[start..end]
 }
 
-impl CssRule {
-    pub fn needs_relayout(&self) -> bool {
-        // RELAYOUT_RULES.iter().any(|r| self.declaration.0 == *r)
-        // TODO
-        true
-    }
+/// Returns the rest of the line at the (1-based) `row` and `col` of the `source`
+fn get_rest_of_line(source: &str, row: usize, col: usize) -> &str {
+    let line_start = source.split('\n').take(row.saturating_sub(1)).map(|line| line.len() + 1).sum::<usize>().min(source.len());
+    let line = &source[line_start..];
+    let line = &line[..line.find('\n').unwrap_or(line.len())];
+    let column_start = line.char_indices().nth(col.saturating_sub(1)).and_then(|(idx, _)| Some(idx)).unwrap_or(line.len());
+    line[column_start..].trim_right()
 }
 
-impl Css {
+/// Returns the row and column (starting at 1) of a tokenizer error
+fn get_syntax_error_position(error: &CssSyntaxError) -> (usize, usize) {
+    use simplecss::Error::*;
+    let position = match *error {
+        UnexpectedEndOfStream(position) |
+        UnsupportedToken(position) |
+        UnknownToken(position) => position,
+        InvalidAdvance { pos, .. } => pos,
+    };
+    (position.row, position.col)
+}
 
-    /// Creates an empty set of CSS rules
-    pub fn empty() -> Self {
-        Self {
-            rules: Vec::new(),
-            needs_relayout: false,
-            dynamic_css_overrides: FastHashMap::default(),
+/// Skips the whitespace and comments at the start of `css_string`
+fn skip_comments(mut css_string: &str) -> &str {
+    loop {
+        css_string = css_string.trim_left();
+        if !css_string.starts_with("/*") {
+            return css_string;
         }
+        css_string = css_string.find("*/").and_then(|comment_end| Some(&css_string[comment_end + 2..])).unwrap_or("");
     }
+}
 
-    /// Parses a CSS string (single-threaded) and returns the parsed rules
-    pub fn new_from_string<'a>(css_string: &'a str) -> Result<Self, CssParseError<'a>> {
-        use simplecss::{Tokenizer, Token};
-        use std::collections::HashSet;
-
-        let mut tokenizer = Tokenizer::new(css_string);
+/// A top-level part of a stylesheet, see `split_at_rules`
+#[derive(Debug, Clone, PartialEq)]
+enum CssBlock<'a> {
+    /// Normal rules, i.e. `div { width: 100px; }`
+    Rules(&'a str),
+    /// An at-rule, i.e. `@media (max-width: 600px) { ... }` or `@import "a.css";`
+    AtRule {
+        /// `media`
+        name: &'a str,
+        /// `(max-width: 600px)`
+        prelude: &'a str,
+        /// The contents of the `{ }` block, `None` for statements like `@import`
+        block: Option<&'a str>,
+        /// `@media (max-width: 600px)`, for error messages
+        header: &'a str,
+    },
+}
 
-        let mut block_nesting = 0_usize;
-        let mut css_rules = Vec::<CssRule>::new();
+/// Splits a stylesheet into its normal rules and its top-level at-rules,
+/// since the tokenizer can't parse at-rules itself. An unclosed at-rule
+/// block is pushed to the `errors` and ends the stylesheet.
+fn split_at_rules<'a>(css_string: &'a str, errors: &mut Vec<(CssParseError<'a>, &'a str)>) -> Vec<CssBlock<'a>> {
+    let mut blocks = Vec::new();
+    let mut position = 0;
 
-        // TODO: For now, rules may not be nested, otherwise, this won't work
-        // TODO: This could be more efficient. We don't even need to clone the
-        // strings, but this is just a quick-n-dirty CSS parser
-        // This will also use up a lot of memory, since the strings get duplicated
+    while let Some(at_position) = find_unnested_byte(css_string, position, b"@") {
 
-        let mut parser_in_block = false;
-        let mut current_type = "*";
-        let mut current_id = None;
-        let mut current_classes = HashSet::<&str>::new();
-
-        'css_parse_loop: loop {
-            let tokenize_result = tokenizer.parse_next();
-            match tokenize_result {
-                Ok(token) => {
-                    match token {
-                        Token::EndOfStream => {
-                            break 'css_parse_loop;
-                        },
-                        Token::BlockStart => {
-                            parser_in_block = true;
-                            block_nesting += 1;
-                        },
-                        Token::BlockEnd => {
-                            block_nesting -= 1;
-                            parser_in_block = false;
-                            current_type = "*";
-                            current_id = None;
-                            current_classes = HashSet::<&str>::new();
-                        },
-                        Token::TypeSelector(div_type) => {
-                            if parser_in_block {
-                                return Err(CssParseError::MalformedCss);
-                            }
-                            current_type = div_type;
-                        },
-                        Token::IdSelector(id) => {
-                            if parser_in_block {
-                                return Err(CssParseError::MalformedCss);
-                            }
-                            current_id = Some(id.to_string());
-                        }
-                        Token::ClassSelector(class) => {
-                            if parser_in_block {
-                                return Err(CssParseError::MalformedCss);
-                            }
-                            current_classes.insert(class);
-                        }
-                        Token::Declaration(key, val) => {
-                            if !parser_in_block {
-                                return Err(CssParseError::MalformedCss);
-                            }
-
-                            // see if the Declaration is static or dynamic
-                            //
-                            // css_val = "center" | "{{ my_dynamic_id | center }}"
-                            let css_decl = determine_static_or_dynamic_css_property(key, val)?;
-                            let mut css_rule = CssRule {
-                                html_type: current_type.to_string(),
-                                id: current_id.clone(),
-                                classes: current_classes.iter().map(|e| e.to_string()).collect::<Vec<String>>(),
-                                declaration: (key.to_string(), css_decl),
-                            };
-                            // IMPORTANT!
-                            css_rule.classes.sort();
-                            css_rules.push(css_rule);
-                        },
-                        _ => { }
-                    }
-                },
-                Err(e) => {
-                    return Err(CssParseError::ParseError(e));
-                }
-            }
+        if !css_string[position..at_position].trim().is_empty() {
+            blocks.push(CssBlock::Rules(&css_string[position..at_position]));
         }
 
-        // non-even number of blocks
-        if block_nesting != 0 {
-            return Err(CssParseError::UnclosedBlock);
-        }
+        let name_start = at_position + 1;
+        let name_end = css_string[name_start..]
+            .find(|c: char| !(c.is_alphanumeric() || c == '-'))
+            .and_then(|name_len| Some(name_start + name_len))
+            .unwrap_or(css_string.len());
+        let name = &css_string[name_start..name_end];
 
-        Ok(Self {
-            rules: css_rules,
-            // force re-layout for the first frame
-            needs_relayout: true,
-            dynamic_css_overrides: FastHashMap::default(),
-        })
+        match find_unnested_byte(css_string, name_end, b";{") {
+            Some(prelude_end) if css_string.as_bytes()[prelude_end] == b'{' => {
+                let header = css_string[at_position..prelude_end].trim_right();
+                let block_end = match find_unnested_byte(css_string, prelude_end + 1, b"}") {
+                    Some(block_end) => block_end,
+                    None => {
+                        errors.push((CssParseError::UnclosedBlock, header));
+                        return blocks;
+                    }
+                };
+                blocks.push(CssBlock::AtRule {
+                    name: name,
+                    prelude: css_string[name_end..prelude_end].trim(),
+                    block: Some(&css_string[prelude_end + 1..block_end]),
+                    header: header,
+                });
+                position = block_end + 1;
+            },
+            Some(prelude_end) => {
+                blocks.push(CssBlock::AtRule {
+                    name: name,
+                    prelude: css_string[name_end..prelude_end].trim(),
+                    block: None,
+                    header: css_string[at_position..prelude_end].trim_right(),
+                });
+                position = prelude_end + 1;
+            },
+            None => {
+                // statement at the end of the file, without a trailing semicolon
+                blocks.push(CssBlock::AtRule {
+                    name: name,
+                    prelude: css_string[name_end..].trim(),
+                    block: None,
+                    header: css_string[at_position..].trim_right(),
+                });
+                position = css_string.len();
+            },
+        }
     }
 
-    /// Returns the native style for the OS
-    #[cfg(target_os="windows")]
-    pub fn native() -> Self {
-        Self::new_from_string(NATIVE_CSS_WINDOWS).unwrap()
+    if !css_string[position..].trim().is_empty() {
+        blocks.push(CssBlock::Rules(&css_string[position..]));
     }
 
-    /// Returns the native style for the OS
-    #[cfg(target_os="linux")]
-    pub fn native() -> Self {
-        Self::new_from_string(NATIVE_CSS_LINUX).unwrap()
+    blocks
+}
+
+/// Splits the prelude of an `@import` rule, i.e. `url("theme.css") (prefers-color-scheme: dark)`,
+/// into the path (`theme.css`) and the media queries that follow the path
+fn split_import_prelude<'a>(prelude: &'a str) -> Option<(&'a str, &'a str)> {
+    let (path, media_queries) = if prelude.starts_with("url(") {
+        let url_end = prelude.find(')')?;
+        (prelude[4..url_end].trim(), &prelude[url_end + 1..])
+    } else if prelude.starts_with('"') || prelude.starts_with('\'') {
+        let string_end = prelude[1..].find(&prelude[..1])? + 2;
+        (&prelude[..string_end], &prelude[string_end..])
+    } else {
+        return None;
+    };
+
+    let is_quoted = path.len() >= 2 && (path.starts_with('"') && path.ends_with('"') ||
+                                        path.starts_with('\'') && path.ends_with('\''));
+    let path = if is_quoted { &path[1..path.len() - 1] } else { path };
+
+    if path.is_empty() {
+        None
+    } else {
+        Some((path, media_queries.trim()))
     }
+}
 
-    /// Returns the native style for the OS
-    #[cfg(target_os="macos")]
-    pub fn native() -> Self {
-        Self::new_from_string(NATIVE_CSS_MACOS).unwrap()
+/// Returns the position of the first of the `targets` at or after `position` that
+/// isn't nested in a block or in parentheses and isn't part of a comment or a string
+fn find_unnested_byte(css_string: &str, mut position: usize, targets: &[u8]) -> Option<usize> {
+    let bytes = css_string.as_bytes();
+    let mut depth = 0_usize;
+
+    while position < bytes.len() {
+        match bytes[position] {
+            b'/' if bytes.get(position + 1) == Some(&b'*') => {
+                position = css_string[position + 2..].find("*/")
+                    .and_then(|comment_len| Some(position + 2 + comment_len + 2))
+                    .unwrap_or(bytes.len());
+                continue;
+            },
+            quote @ b'"' | quote @ b'\'' => {
+                position = css_string[position + 1..].find(quote as char)
+                    .and_then(|string_len| Some(position + 1 + string_len + 1))
+                    .unwrap_or(bytes.len());
+                continue;
+            },
+            byte if depth == 0 && targets.contains(&byte) => return Some(position),
+            b'{' | b'(' => depth += 1,
+            b'}' | b')' => depth = depth.saturating_sub(1),
+            _ => { },
+        }
+        position += 1;
     }
+
+    None
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -296,6 +1978,123 @@ impl<'a> From<CssParsingError<'a>> for DynamicCssParseError<'a> {
     }
 }
 
+/// Splits off the `!important` annotation of a CSS value,
+/// i.e. `"red !important"` => `("red", true)`
+fn split_important<'a>(value: &'a str) -> (&'a str, bool) {
+    const IMPORTANT: &str = "important";
+
+    let value = value.trim();
+    if value.len() >= IMPORTANT.len() && value[value.len() - IMPORTANT.len()..].eq_ignore_ascii_case(IMPORTANT) {
+        let without_keyword = value[..value.len() - IMPORTANT.len()].trim_right();
+        if without_keyword.ends_with('!') {
+            return (without_keyword[..without_keyword.len() - 1].trim_right(), true);
+        }
+    }
+
+    (value, false)
+}
+
+const VAR_FUNCTION: &str = "var(";
+
+/// Maximum nesting depth of `var()` references, guards against
+/// custom properties that reference each other
+const MAX_VAR_REFERENCE_DEPTH: usize = 16;
+
+/// Substitutes the `var(--name, fallback)` references in a CSS value, i.e.
+/// `1px solid var(--accent)` => `1px solid #3070ff`.
+///
+/// The `overrides` (set via `FakeCss::set_custom_property`) take precedence over
+/// the `custom_properties` of the node. Returns `None` if a custom property is
+/// not defined and the `var()` has no fallback value.
+pub(crate) fn resolve_var_references(
+    value: &str,
+    custom_properties: &FastHashMap<String, String>,
+    overrides: &FastHashMap<String, String>)
+-> Option<String>
+{
+    resolve_var_references_inner(value, custom_properties, overrides, 0)
+}
+
+fn resolve_var_references_inner(
+    value: &str,
+    custom_properties: &FastHashMap<String, String>,
+    overrides: &FastHashMap<String, String>,
+    depth: usize)
+-> Option<String>
+{
+    if depth > MAX_VAR_REFERENCE_DEPTH {
+        return None;
+    }
+
+    let mut resolved = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(var_start) = rest.find(VAR_FUNCTION) {
+        resolved.push_str(&rest[..var_start]);
+
+        // "var(--accent, black)" => "--accent, black"
+        let arguments_start = var_start + VAR_FUNCTION.len();
+        let arguments_end = arguments_start + find_unnested(&rest[arguments_start..], ')')?;
+        let arguments = &rest[arguments_start..arguments_end];
+
+        let (name, fallback) = match find_unnested(arguments, ',') {
+            Some(comma) => (arguments[..comma].trim(), Some(&arguments[comma + 1..])),
+            None => (arguments.trim(), None),
+        };
+
+        let value = overrides.get(name)
+            .or_else(|| custom_properties.get(name))
+            .and_then(|value| Some(value.as_str()))
+            .or(fallback)?;
+
+        resolved.push_str(&resolve_var_references_inner(value.trim(), custom_properties, overrides, depth + 1)?);
+        rest = &rest[arguments_end + 1..];
+    }
+
+    resolved.push_str(rest);
+    Some(resolved)
+}
+
+/// Returns whether the CSS value uses the `currentColor` keyword (which is case-insensitive)
+fn contains_current_color(value: &str) -> bool {
+    value.to_ascii_lowercase().contains(CURRENT_COLOR)
+}
+
+/// Substitutes the `currentColor` keywords in a CSS value with the `color` of the
+/// node, i.e. `1px solid currentColor` => `1px solid #3070ff`
+pub(crate) fn resolve_current_color(value: &str, color: &ColorU) -> String {
+    // the ASCII lowercase string has the same byte indices as the original one
+    let lowercase = value.to_ascii_lowercase();
+    let color = color.to_css_string();
+
+    let mut resolved = String::with_capacity(value.len());
+    let mut last_end = 0;
+    for (start, _) in lowercase.match_indices(CURRENT_COLOR) {
+        resolved.push_str(&value[last_end..start]);
+        resolved.push_str(&color);
+        last_end = start + CURRENT_COLOR.len();
+    }
+
+    resolved.push_str(&value[last_end..]);
+    resolved
+}
+
+/// Returns the byte index of the first `character` that isn't nested in parentheses
+fn find_unnested(value: &str, character: char) -> Option<usize> {
+    let mut depth = 0_usize;
+    for (idx, c) in value.char_indices() {
+        if c == character && depth == 0 {
+            return Some(idx);
+        }
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ => { },
+        }
+    }
+    None
+}
+
 /// Determine if a Css property is static (immutable) or if it can change
 /// during the runtime of the program
 fn determine_static_or_dynamic_css_property<'a>(key: &'a str, value: &'a str)
@@ -433,4 +2232,553 @@ fn test_detect_static_or_dynamic_property() {
         determine_static_or_dynamic_css_property("text-align", "[[ |  ]]"),
         Err(DynamicCssParseError::EmptyBraces)
     );
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_css_path_parsing() {
+    use self::CssPathSelector::*;
+
+    let css = Css::new_from_string("div#main.active, .toolbar .button, .list > p { width: 100px; }").unwrap();
+    let paths = css.rules.iter().map(|rule| rule.path.clone()).collect::<Vec<CssPath>>();
+
+    assert_eq!(paths, vec![
+        CssPath { selectors: vec![Type("div".into()), Id("main".into()), Class("active".into())] },
+        CssPath { selectors: vec![Class("toolbar".into()), Children, Class("button".into())] },
+        CssPath { selectors: vec![Class("list".into()), DirectChildren, Type("p".into())] },
+    ]);
+}
+
+#[test]
+fn test_css_path_matching() {
+    use dom::{Dom, NodeType};
+    use traits::TestLayout;
+
+    let dom: Dom<TestLayout> = Dom::new(NodeType::Div).with_class("toolbar")
+        .with_child(Dom::new(NodeType::Div).with_class("list")
+            .with_child(Dom::new(NodeType::Label(String::from("Hello"))).with_id("main").with_class("active")));
+    let arena = dom.arena.borrow();
+    let list = arena[dom.root].first_child().unwrap();
+    let label = arena[list].first_child().unwrap();
+
+    let matches = |selector: &str, node_id: NodeId| {
+        let css = Css::new_from_string(selector).unwrap();
+        css.rules[0].path.matches_html_element(node_id, &*arena, &PseudoClassState::default())
+    };
+
+    assert!(matches("p#main.active { width: 1px; }", label));
+    assert!(!matches("p#main.inactive { width: 1px; }", label));
+    assert!(matches(".toolbar p { width: 1px; }", label));
+    assert!(matches(".list > p { width: 1px; }", label));
+    assert!(!matches(".toolbar > p { width: 1px; }", label));
+    assert!(matches(".toolbar > .list > .active { width: 1px; }", label));
+    assert!(matches("* { width: 1px; }", list));
+    assert!(!matches(".list .list { width: 1px; }", list));
+}
+
+#[test]
+fn test_split_important() {
+    assert_eq!(split_important("red"), ("red", false));
+    assert_eq!(split_important(" red !important "), ("red", true));
+    assert_eq!(split_important("red!IMPORTANT"), ("red", true));
+    assert_eq!(split_important("red ! important"), ("red", true));
+    assert_eq!(split_important("important"), ("important", false));
+}
+
+#[test]
+fn test_css_path_specificity() {
+    let css = Css::new_from_string("div#main .toolbar > p { width: 1px; } * { width: 1px; }").unwrap();
+    assert_eq!(css.rules[0].path.get_specificity(), (1, 1, 2));
+    assert_eq!(css.rules[1].path.get_specificity(), (0, 0, 0));
+}
+
+#[test]
+fn test_css_pseudo_class_matching() {
+    use dom::{Dom, NodeType};
+    use traits::TestLayout;
+
+    let dom: Dom<TestLayout> = Dom::new(NodeType::Div).with_class("button")
+        .with_child(Dom::new(NodeType::Label(String::from("Hello"))));
+    let arena = dom.arena.borrow();
+    let button = dom.root;
+    let label = arena[button].first_child().unwrap();
+
+    let css = Css::new_from_string("
+        .button:hover { width: 1px; }
+        .button:active p { width: 1px; }
+        p:focus { width: 1px; }
+    ").unwrap();
+
+    assert_eq!(css.rules[0].path.get_specificity(), (0, 2, 0));
+    assert!(Css::new_from_string(".button:visited { width: 1px; }").is_err());
+
+    let matches = |rule: usize, node_id: NodeId, state: &PseudoClassState| {
+        css.rules[rule].path.matches_html_element(node_id, &*arena, state)
+    };
+
+    let none = PseudoClassState::default();
+    assert!(!matches(0, button, &none));
+    assert!(!matches(1, label, &none));
+    assert!(!matches(2, label, &none));
+
+    // hovering the label also hovers its parent
+    let hovered = PseudoClassState::new(Some(label), false, None, &*arena);
+    assert!(matches(0, button, &hovered));
+    assert!(!matches(1, label, &hovered));
+
+    let pressed = PseudoClassState::new(Some(label), true, Some(label), &*arena);
+    assert!(matches(1, label, &pressed));
+    assert!(matches(2, label, &pressed));
+
+    assert_eq!(hovered.get_changed_nodes(&none), vec![button, label].into_iter().collect());
+    assert_eq!(hovered.get_changed_nodes(&pressed), vec![button, label].into_iter().collect());
+    assert!(pressed.get_changed_nodes(&pressed).is_empty());
+}
+
+#[test]
+fn test_css_nth_child_pattern() {
+    let pattern = |repeat, offset| Some(CssNthChildPattern { repeat, offset });
+
+    assert_eq!(CssNthChildPattern::from_str("odd"), pattern(2, 1));
+    assert_eq!(CssNthChildPattern::from_str("even"), pattern(2, 0));
+    assert_eq!(CssNthChildPattern::from_str("3"), pattern(0, 3));
+    assert_eq!(CssNthChildPattern::from_str("n"), pattern(1, 0));
+    assert_eq!(CssNthChildPattern::from_str("-n+3"), pattern(-1, 3));
+    assert_eq!(CssNthChildPattern::from_str(" 2n - 1 "), pattern(2, -1));
+    assert_eq!(CssNthChildPattern::from_str("2x+1"), None);
+    assert_eq!(CssNthChildPattern::from_str("2n1"), None);
+
+    let matching = |p: CssNthChildPattern| (1..8).filter(|i| p.matches(*i)).collect::<Vec<usize>>();
+    assert_eq!(matching(pattern(2, 1).unwrap()), vec![1, 3, 5, 7]);
+    assert_eq!(matching(pattern(2, 0).unwrap()), vec![2, 4, 6]);
+    assert_eq!(matching(pattern(0, 3).unwrap()), vec![3]);
+    assert_eq!(matching(pattern(-1, 3).unwrap()), vec![1, 2, 3]);
+    assert_eq!(matching(pattern(3, -1).unwrap()), vec![2, 5]);
+
+    assert_eq!(
+        Css::new_from_string("p:nth-child(2x+1) { width: 1px; }").map_err(|e| e.error),
+        Err(CssParseError::InvalidNthChildPattern("2x+1"))
+    );
+}
+
+#[test]
+fn test_css_structural_pseudo_class_matching() {
+    use dom::{Dom, NodeType};
+    use traits::TestLayout;
+
+    let dom: Dom<TestLayout> = Dom::new(NodeType::Div).with_class("list")
+        .with_child(Dom::new(NodeType::Div))
+        .with_child(Dom::new(NodeType::Div))
+        .with_child(Dom::new(NodeType::Div)
+            .with_child(Dom::new(NodeType::Div)));
+    let arena = dom.arena.borrow();
+    let rows = dom.root.children(&*arena).collect::<Vec<NodeId>>();
+    let only_child = arena[rows[2]].first_child().unwrap();
+
+    let matching_nodes = |selector: &str| {
+        let css = Css::new_from_string(selector).unwrap();
+        arena.linear_iter()
+            .filter(|node_id| css.rules[0].path.matches_html_element(*node_id, &*arena, &PseudoClassState::default()))
+            .collect::<Vec<NodeId>>()
+    };
+
+    assert_eq!(matching_nodes(".list > div:first-child { width: 1px; }"), vec![rows[0]]);
+    assert_eq!(matching_nodes(".list > div:last-child { width: 1px; }"), vec![rows[2]]);
+    assert_eq!(matching_nodes(".list > div:nth-child(odd) { width: 1px; }"), vec![rows[0], rows[2]]);
+    assert_eq!(matching_nodes(".list > div:nth-child(2) { width: 1px; }"), vec![rows[1]]);
+    assert_eq!(matching_nodes(".list div:only-child { width: 1px; }"), vec![only_child]);
+}
+
+#[test]
+fn test_resolve_var_references() {
+    let mut custom_properties = FastHashMap::default();
+    custom_properties.insert(String::from("--accent"), String::from("#3070ff"));
+    custom_properties.insert(String::from("--border"), String::from("1px solid var(--accent)"));
+    custom_properties.insert(String::from("--loop"), String::from("var(--loop)"));
+    let mut overrides = FastHashMap::default();
+
+    let resolve = |value: &str, overrides: &FastHashMap<String, String>| {
+        resolve_var_references(value, &custom_properties, overrides)
+    };
+
+    assert_eq!(resolve("var(--accent)", &overrides), Some(String::from("#3070ff")));
+    assert_eq!(resolve("var(--border)", &overrides), Some(String::from("1px solid #3070ff")));
+    assert_eq!(resolve("var(--missing, rgb(0, 0, 0))", &overrides), Some(String::from("rgb(0, 0, 0)")));
+    assert_eq!(resolve("var(--missing, var(--accent))", &overrides), Some(String::from("#3070ff")));
+    assert_eq!(resolve("var(--missing)", &overrides), None);
+    assert_eq!(resolve("var(--loop)", &overrides), None);
+
+    overrides.insert(String::from("--accent"), String::from("red"));
+    assert_eq!(resolve("var(--border)", &overrides), Some(String::from("1px solid red")));
+
+    let css = Css::new_from_string("div { --accent: #3070ff; color: var(--accent, black); }").unwrap();
+    assert_eq!(css.rules[0].declaration, (String::from("--accent"), CssDeclaration::CustomProperty(String::from("#3070ff"))));
+    assert_eq!(css.rules[1].declaration, (String::from("color"), CssDeclaration::Unresolved(String::from("var(--accent, black)"))));
+}
+
+#[test]
+fn test_split_at_rules() {
+    let mut errors = Vec::new();
+    let css = "div { width: 1px; } /* @media */ @import \"a.css\"; @media (max-width: 600px) { p { color: red; } } span { }";
+    assert_eq!(split_at_rules(css, &mut errors), vec![
+        CssBlock::Rules("div { width: 1px; } /* @media */ "),
+        CssBlock::AtRule { name: "import", prelude: "\"a.css\"", block: None, header: "@import \"a.css\"" },
+        CssBlock::AtRule { name: "media", prelude: "(max-width: 600px)", block: Some(" p { color: red; } "), header: "@media (max-width: 600px)" },
+        CssBlock::Rules(" span { }"),
+    ]);
+    assert!(errors.is_empty());
+
+    assert_eq!(split_at_rules("div { } @media screen { p { }", &mut errors), vec![CssBlock::Rules("div { } ")]);
+    assert_eq!(errors, vec![(CssParseError::UnclosedBlock, "@media screen")]);
+}
+
+#[test]
+fn test_media_queries() {
+    use self::CssMediaFeature::*;
+
+    assert_eq!(
+        CssMediaQueryList::from_str("screen and (min-width: 400px) and (max-width: 600px), (min-resolution: 2dppx)"),
+        Ok(CssMediaQueryList(vec![
+            CssMediaQuery { features: vec![MinWidth(400.0), MaxWidth(600.0)] },
+            CssMediaQuery { features: vec![MinResolution(2.0)] },
+        ]))
+    );
+    assert_eq!(CssMediaQueryList::from_str("(min-resolution: 192dpi)"), Ok(CssMediaQueryList(vec![CssMediaQuery { features: vec![MinResolution(2.0)] }])));
+    assert_eq!(CssMediaQueryList::from_str("(orientation: portrait)"), Err(CssParseError::InvalidMediaQuery("(orientation: portrait)")));
+    assert_eq!(CssMediaQueryList::from_str("print"), Err(CssParseError::InvalidMediaQuery("print")));
+
+    let css = Css::new_from_string("
+        div { width: 1px; }
+        @media (max-width: 600px) {
+            div { width: 2px; }
+            @media (prefers-color-scheme: dark) { div { width: 3px; } }
+        }
+        @media (min-resolution: 2dppx) { div { width: 4px; } }
+    ").unwrap();
+
+    let matching_rules = |state: MediaQueryState| {
+        css.rules.iter().enumerate()
+            .filter(|(_, rule)| rule.media_queries.iter().all(|query| query.matches(&state)))
+            .map(|(idx, _)| idx)
+            .collect::<Vec<usize>>()
+    };
+
+    let wide = MediaQueryState { width: 800.0, height: 600.0, hidpi_factor: 1.0, color_scheme: ColorScheme::Light };
+    assert_eq!(matching_rules(wide), vec![0]);
+    assert_eq!(matching_rules(MediaQueryState { width: 600.0, .. wide }), vec![0, 1]);
+    assert_eq!(matching_rules(MediaQueryState { width: 600.0, color_scheme: ColorScheme::Dark, .. wide }), vec![0, 1, 2]);
+    assert_eq!(matching_rules(MediaQueryState { hidpi_factor: 2.0, .. wide }), vec![0, 3]);
+
+    assert_eq!(Css::new_from_string("@font-face { font-family: x; }").map_err(|e| e.error), Err(CssParseError::UnsupportedAtRule("font-face")));
+}
+
+#[test]
+fn test_split_import_prelude() {
+    assert_eq!(split_import_prelude("\"a.css\""), Some(("a.css", "")));
+    assert_eq!(split_import_prelude("'a.css' (max-width: 600px)"), Some(("a.css", "(max-width: 600px)")));
+    assert_eq!(split_import_prelude("url(a.css)"), Some(("a.css", "")));
+    assert_eq!(split_import_prelude("url(\"a.css\") screen"), Some(("a.css", "screen")));
+    assert_eq!(split_import_prelude("a.css"), None);
+    assert_eq!(split_import_prelude("\"\""), None);
+}
+
+#[test]
+fn test_css_imports() {
+    let loader = |path: &str| match path {
+        "theme.css" => Some("div { width: 1px; } @import \"colors.css\";".to_string()),
+        "colors.css" => Some("div { color: red; }".to_string()),
+        "dark.css" => Some("div { color: black; }".to_string()),
+        "cycle.css" => Some("@import \"cycle.css\";".to_string()),
+        "broken.css" => Some("div { width: 1px; ".to_string()),
+        _ => None,
+    };
+
+    // imported rules are inserted at the position of the @import
+    let css = Css::new_from_string_with_loader("@import \"theme.css\"; div { height: 1px; }", &loader).unwrap();
+    let properties = css.rules.iter().map(|rule| rule.declaration.0.as_str()).collect::<Vec<&str>>();
+    assert_eq!(properties, vec!["width", "color", "height"]);
+
+    // the media queries of the @import apply to the imported rules
+    let css = Css::new_from_string_with_loader("@import url(dark.css) (prefers-color-scheme: dark);", &loader).unwrap();
+    let dark = MediaQueryState { color_scheme: ColorScheme::Dark, .. MediaQueryState::default() };
+    assert!(css.rules[0].media_queries.iter().all(|query| query.matches(&dark)));
+    assert!(!css.rules[0].media_queries.iter().all(|query| query.matches(&MediaQueryState::default())));
+
+    let import_error = |css_string| Css::new_from_string_with_loader(css_string, &loader).map_err(|e| e.error);
+    assert_eq!(Css::new_from_string("@import \"theme.css\";").map_err(|e| e.error), Err(CssImportError::NoLoader("theme.css".into()).into()));
+    assert_eq!(import_error("@import \"missing.css\";"), Err(CssImportError::NotFound("missing.css".into()).into()));
+    assert_eq!(import_error("@import 5px;"), Err(CssImportError::InvalidImport("5px".into()).into()));
+    assert_eq!(import_error("@import \"cycle.css\";"), Err(CssImportError::ParseError {
+        path: "cycle.css".into(),
+        error: "line 1, column 1: ImportError(CyclicImport(\"cycle.css\")) in `@import \"cycle.css\"`".into(),
+    }.into()));
+    assert_eq!(import_error("@import \"broken.css\";"), Err(CssImportError::ParseError {
+        path: "broken.css".into(),
+        error: "line 1, column 1: UnclosedBlock in `div { width: 1px;`".into(),
+    }.into()));
+}
+
+#[test]
+fn test_css_error_locations() {
+    use css_parser::InvalidValueErr;
+
+    let error = Css::new_from_string("div { width: 1px; }\n\np {\n    text-align: hello;\n}").unwrap_err();
+    let invalid_value = CssParsingError::InvalidValueErr(InvalidValueErr("hello"));
+    assert_eq!(error.error, CssParseError::DynamicCssParseError(DynamicCssParseError::UnexpectedValue(invalid_value)));
+    assert_eq!((error.line, error.column, error.snippet), (4, 5, "text-align: hello"));
+
+    let error = Css::new_from_string("div { }\n  /* links */ a:visited, p { width: 1px; }").unwrap_err();
+    assert_eq!(error.error, CssParseError::UnsupportedSelector);
+    assert_eq!((error.line, error.column, error.snippet), (2, 15, "a:visited, p"));
+
+    let error = Css::new_from_string("div { }\n@media (orientation: portrait) { }").unwrap_err();
+    assert_eq!(error.error, CssParseError::InvalidMediaQuery("(orientation: portrait)"));
+    assert_eq!((error.line, error.column, error.snippet), (2, 1, "@media (orientation: portrait)"));
+
+    let error = Css::new_from_string("div { }\nspan { width: 1px;").unwrap_err();
+    assert_eq!(error.error, CssParseError::UnclosedBlock);
+    assert_eq!((error.line, error.column), (2, 1));
+
+    assert_eq!(error.to_string(), "line 2, column 1: UnclosedBlock in `span { width: 1px;`");
+
+    // the errors are in the order of the source, even if an at-rule block is broken
+    let css_string = "div { width: hello; }\n@media screen { p { }";
+    let error = Css::new_from_string(css_string).unwrap_err();
+    assert_eq!((error.line, error.column, error.snippet), (1, 7, "width: hello"));
+    let (_, errors) = Css::new_from_string_lossy(css_string);
+    assert_eq!(errors.iter().map(|error| (error.line, error.column)).collect::<Vec<_>>(), vec![(1, 7), (2, 1)]);
+}
+
+#[test]
+fn test_css_lossy_parsing() {
+    let (css, errors) = Css::new_from_string_lossy("
+        div { width: 1px; height: hello; min-width: 2px; }
+        a:visited { width: 3px; }
+        p { width: [[ ]]; height: 4px; }
+        @font-face { font-family: x; }
+        span { width: 5px; }
+    ");
+
+    // the valid declarations of the valid rules are kept
+    let properties = css.rules.iter().map(|rule| (&rule.path.selectors[0], rule.declaration.0.as_str())).collect::<Vec<_>>();
+    let selector = |div_type: &str| CssPathSelector::Type(div_type.to_string());
+    assert_eq!(properties, vec![
+        (&selector("div"), "width"),
+        (&selector("div"), "min-width"),
+        (&selector("p"), "height"),
+        (&selector("span"), "width"),
+    ]);
+
+    let errors = errors.iter().map(|e| (e.line, e.snippet)).collect::<Vec<_>>();
+    assert_eq!(errors, vec![
+        (2, "height: hello"),
+        (3, "a:visited"),
+        (4, "width: [[ ]]"),
+        (5, "@font-face"),
+    ]);
+}
+
+#[test]
+fn test_css_hot_reload() {
+    use std::{env, thread, time::Duration};
+
+    let file_name = format!("azul_test_css_hot_reload_{}.css", ::std::process::id());
+    let import_name = format!("azul_test_css_hot_reload_import_{}.css", ::std::process::id());
+    let path = env::temp_dir().join(&file_name);
+    let import_path = env::temp_dir().join(&import_name);
+    // Sets the content of the file and makes sure that its modification time changes
+    let write = |path: &Path, css_string: &str| {
+        let last_modified = get_modified_time(path);
+        fs::write(path, css_string).unwrap();
+        while get_modified_time(path) == last_modified {
+            thread::sleep(Duration::from_millis(10));
+            fs::write(path, css_string).unwrap();
+        }
+    };
+
+    write(&path, "div { width: 1px; }");
+    let mut css = Css::layered(Css::new_from_string("p { width: 1px; }").unwrap(), Css::hot_reload(&path));
+    css.merge(Css::new_from_string("p { color: red; }").unwrap());
+    assert_eq!(css.rules.len(), 3);
+    assert!(!css.reload_if_modified());
+
+    write(&path, "div { width: 2px; } div { height: 2px; }");
+    assert!(css.reload_if_modified());
+    // the rules of the other stylesheets are kept, the reloaded rules stay in their place
+    let properties = css.rules.iter().map(|rule| (rule.origin, rule.declaration.0.as_str())).collect::<Vec<_>>();
+    assert_eq!(properties, vec![
+        (CssOrigin::UserAgent, "width"),
+        (CssOrigin::App, "width"),
+        (CssOrigin::App, "height"),
+        (CssOrigin::App, "color"),
+    ]);
+
+    // a broken stylesheet keeps the last working one
+    write(&path, "div { width: 3px; ");
+    assert!(!css.reload_if_modified());
+    assert_eq!(css.rules.len(), 4);
+
+    // modifying an imported stylesheet reloads the stylesheet
+    write(&import_path, "span { width: 4px; }");
+    write(&path, &format!("@import \"{}\";", import_name));
+    assert!(css.reload_if_modified());
+    assert_eq!(css.rules.len(), 3);
+    write(&import_path, "span { width: 5px; } span { height: 5px; }");
+    assert!(css.reload_if_modified());
+    assert_eq!(css.rules.len(), 4);
+    assert!(!css.reload_if_modified());
+
+    fs::remove_file(&import_path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(!css.reload_if_modified());
+}
+
+#[test]
+fn test_css_layers() {
+    let mut css = Css::layered(Css::new_from_string("div { width: 1px; }").unwrap(), Css::new_from_string("div { width: 2px; }").unwrap());
+    css.merge(Css::new_from_string("div { width: 3px; }").unwrap());
+    let origins = css.rules.iter().map(|rule| rule.origin).collect::<Vec<CssOrigin>>();
+    assert_eq!(origins, vec![CssOrigin::UserAgent, CssOrigin::App, CssOrigin::App]);
+
+    // the default style of the widgets is part of the native user-agent stylesheet
+    assert!(Css::native().rules.iter().all(|rule| rule.origin == CssOrigin::UserAgent));
+    assert!(Css::native().rules.iter().any(|rule| rule.path.has_dynamic_pseudo_selector()));
+}
+
+#[test]
+fn test_css_display() {
+    let css = Css::new_from_string("
+        * { --accent: #3070ff; }
+        div#main .toolbar > p:nth-child(2n+1) { color: var(--accent, red); width: [[ my_width | 5px ]]; }
+        p:hover { color: red; }
+        @media (max-width: 600px), (prefers-color-scheme: dark) {
+            p { width: 10pt !important; }
+            @media (min-resolution: 2dppx) { p { height: 1em; } }
+        }
+        p { background-color: #00000080; }
+    ").unwrap();
+
+    let expected = "\
+* {
+    --accent: #3070ff;
+}
+
+div#main .toolbar > p:nth-child(2n+1) {
+    color: var(--accent, red);
+    width: [[ my_width | 5px ]];
+}
+
+p:hover {
+    color: #ff0000;
+}
+
+@media (max-width: 600px), (prefers-color-scheme: dark) {
+    p {
+        width: 10pt !important;
+    }
+
+    @media (min-resolution: 2dppx) {
+        p {
+            height: 1em;
+        }
+    }
+}
+
+p {
+    background-color: #00000080;
+}
+";
+    assert_eq!(css.to_string(), expected);
+    assert_eq!(Css::new_from_string(&css.to_string()).unwrap().rules, css.rules);
+}
+
+#[test]
+fn test_css_wide_keywords() {
+    use css_parser::PixelParseError;
+
+    let css = Css::new_from_string("div { color: inherit; width: initial !important; padding-top: unset; }").unwrap();
+    let declarations = css.rules.iter().map(|rule| rule.declaration.1.clone()).collect::<Vec<CssDeclaration>>();
+    assert_eq!(declarations, vec![
+        CssDeclaration::Keyword(CssWideKeyword::Inherit),
+        CssDeclaration::Keyword(CssWideKeyword::Initial),
+        CssDeclaration::Keyword(CssWideKeyword::Unset),
+    ]);
+    assert_eq!(css.to_string(), "div {\n    color: inherit;\n    width: initial !important;\n    padding-top: unset;\n}\n");
+
+    assert!(CssWideKeyword::Unset.inherits("font-size"));
+    assert!(!CssWideKeyword::Unset.inherits("background-color"));
+
+    assert_eq!(Css::new_from_string("div { colour: inherit; }").map_err(|e| e.error),
+        Err(CssParseError::DynamicCssParseError(DynamicCssParseError::UnexpectedValue(
+            CssParsingError::UnsupportedCssKey("colour", "inherit")))));
+    // keywords can't be used inside of other values
+    assert_eq!(Css::new_from_string("div { padding: 5px inherit; }").map_err(|e| e.error),
+        Err(CssParseError::DynamicCssParseError(DynamicCssParseError::UnexpectedValue(
+            CssParsingError::CssSideOffsetsParseError(::css_parser::CssSideOffsetsParseError::PixelParseError(
+                PixelParseError::InvalidComponent("inherit")))))));
+}
+
+#[test]
+fn test_css_display_round_trip() {
+    let css = Css::native();
+    let reparsed = Css::new_from_string(&css.to_string()).unwrap();
+    let declarations = |css: &Css| css.rules.iter()
+        .map(|rule| (rule.path.clone(), rule.declaration.clone(), rule.important, rule.media_queries.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(declarations(&reparsed), declarations(&css));
+
+    assert_eq!(CssNthChildPattern::from_str("-n+3").unwrap().to_string(), "-1n+3");
+    assert_eq!(CssNthChildPattern::from_str("even").unwrap().to_string(), "2n");
+    assert_eq!(CssNthChildPattern::from_str("4").unwrap().to_string(), "4");
+}
+
+#[test]
+fn test_parse_keyframes() {
+    let css = Css::new_from_string("
+        div { animation: fade 1s; }
+        @keyframes fade {
+            to { background-color: #ffffff; }
+            from, 50% { background-color: #000000; width: 5px !important; }
+        }
+        @media (max-width: 600px) {
+            @keyframes fade { 30% { width: 1px; } }
+        }
+    ").unwrap();
+
+    let expected = "\
+div {
+    animation: fade 1000ms ease 0ms 1 normal none;
+}
+
+@keyframes fade {
+    0% {
+        background-color: #000000;
+    }
+    50% {
+        background-color: #000000;
+    }
+    100% {
+        background-color: #ffffff;
+    }
+}
+
+@media (max-width: 600px) {
+    @keyframes fade {
+        30% {
+            width: 1px;
+        }
+    }
+}
+";
+    assert_eq!(css.to_string(), expected);
+    assert_eq!(Css::new_from_string(expected).unwrap().keyframes, css.keyframes);
+
+    let (css, errors) = Css::new_from_string_lossy("
+        @keyframes fade { 150% { width: 5px; } }
+        @keyframes { to { width: 5px; } }
+        @keyframes grow { to { width: 5; height: 5px; } }
+    ");
+    let errors = errors.iter().map(|error| (error.snippet, error.line)).collect::<Vec<_>>();
+    assert_eq!(errors, vec![("@keyframes fade", 2), ("@keyframes", 3), ("width: 5", 4)]);
+    assert_eq!(css.keyframes.len(), 1);
+    assert_eq!(css.keyframes[0].keyframes[0].properties, vec![ParsedCssProperty::from_kv("height", "5px").unwrap()]);
+}
@@ -1,28 +1,27 @@
 //! Contains utilities to convert strings (CSS strings) to servo types
 
-use std::num::{ParseIntError, ParseFloatError};
+use std::{
+    fmt,
+    num::{ParseIntError, ParseFloatError},
+    time::Duration,
+};
 pub use {
     euclid::{TypedSize2D, SideOffsets2D},
     webrender::api::{
         BorderRadius, BorderWidths, BorderDetails, NormalBorder,
         NinePatchBorder, LayoutPixel, BoxShadowClipMode, ColorU,
-        ColorF, LayoutVector2D, Gradient, RadialGradient, LayoutPoint,
-        LayoutSize, ExtendMode
+        ColorF, LayoutVector2D, Gradient, RadialGradient, GradientStop, LayoutPoint,
+        LayoutSize, LayoutRect, ExtendMode, LayoutTransform, BorderStyle, RepeatMode,
     },
 };
-use webrender::api::{BorderStyle, BorderSide, LayoutRect};
-use euclid::{TypedRotation2D, Angle, TypedPoint2D};
+use webrender::api::BorderSide;
+use euclid::{Angle, TypedPoint2D, TypedVector3D};
 
+/// The font size of the root node if it has no `font-size`, in pixels
 pub(crate) const EM_HEIGHT: f32 = 16.0;
 /// Webrender measures in points, not in pixels!
 pub(crate) const PT_TO_PX: f32 = 96.0 / 72.0;
 
-// In case no font size is specified for a node,
-// this will be subsituted as the default font size
-pub(crate) const DEFAULT_FONT_SIZE: FontSize = FontSize(PixelValue {
-    metric: CssMetric::Px,
-    number: 10_000,
-});
 
 /// Implements `From` for `$a`, mapping it to the `$b::$enum_type` variant
 macro_rules! impl_from {
@@ -46,7 +45,8 @@ macro_rules! impl_from_no_lifetimes {
     )
 }
 
-/// A parser that can accept a list of items and mappings
+/// A parser that can accept a list of items and mappings.
+/// Also implements `ToCssString` for `$return`, using the same mappings.
 macro_rules! multi_type_parser {
     ($fn:ident, $return:ident, $([$identifier_string:expr, $enum_type:ident]),+) => (
         fn $fn<'a>(input: &'a str)
@@ -59,9 +59,41 @@ macro_rules! multi_type_parser {
                 _ => Err(InvalidValueErr(input)),
             }
         }
+
+        impl ToCssString for $return {
+            fn to_css_string(&self) -> String {
+                match *self {
+                    $(
+                        $return::$enum_type => $identifier_string,
+                    )+
+                }.to_string()
+            }
+        }
     )
 }
 
+/// Implements `ToCssString` for a newtype around a value that implements `ToCssString`
+macro_rules! impl_to_css_string_newtype {
+    ($($type:ident),+) => ($(
+        impl ToCssString for $type {
+            fn to_css_string(&self) -> String {
+                self.0.to_css_string()
+            }
+        }
+    )+)
+}
+
+/// Implements `ToCssString` for a newtype around a comma-separated list of values
+macro_rules! impl_to_css_string_comma_list {
+    ($($type:ident),+) => ($(
+        impl ToCssString for $type {
+            fn to_css_string(&self) -> String {
+                self.0.iter().map(|value| value.to_css_string()).collect::<Vec<String>>().join(", ")
+            }
+        }
+    )+)
+}
+
 macro_rules! typed_pixel_value_parser {
     ($fn:ident, $return:ident) => (
         fn $fn<'a>(input: &'a str)
@@ -72,19 +104,76 @@ macro_rules! typed_pixel_value_parser {
     )
 }
 
+/// The properties whose value is inherited by the children of a node
+const INHERITED_PROPERTIES: [&str; 12] = [
+    "color", "font-size", "font-family", "font-weight", "font-style", "line-height",
+    "text-align", "letter-spacing", "word-spacing", "text-transform", "visibility", "cursor",
+];
+
+/// The properties whose longhands (`padding-top`, `overflow-x`) are merged into one value
+const MERGED_PROPERTIES: [&str; 3] = ["padding", "margin", "overflow"];
+
+/// The keys of `border` and its longhands, which are merged into one value as well.
+/// `border-radius` and `border-image` are separate properties.
+const BORDER_KEYS: [&str; 8] = [
+    "border", "border-top", "border-right", "border-bottom", "border-left",
+    "border-width", "border-style", "border-color",
+];
+
+/// Returns the property that the key belongs to, i.e. `"padding"` for `"padding-top"`
+pub(crate) fn get_property_name(key: &str) -> &str {
+    match key.find('-') {
+        Some(idx) if MERGED_PROPERTIES.contains(&&key[..idx]) => &key[..idx],
+        Some(_) if BORDER_KEYS.contains(&key) => "border",
+        _ => key,
+    }
+}
+
+/// Returns the initial value of a longhand of a merged property, i.e. `"0px"` for `"padding-top"`.
+/// The initial color of a border is `currentColor`, which has to be resolved before parsing.
+pub(crate) fn get_initial_longhand_value(key: &str) -> Option<&'static str> {
+    match get_property_name(key) {
+        "padding" | "margin" => Some("0px"),
+        "overflow" => Some("auto"),
+        "border" => match key {
+            "border-width" => Some("1px"),
+            "border-style" => Some("none"),
+            "border-color" => Some("currentColor"),
+            _ => Some("none currentColor"),
+        },
+        _ => None,
+    }
+}
+
+/// Returns whether the children of a node inherit the value of the property, i.e. `color`
+pub(crate) fn is_inherited_property(key: &str) -> bool {
+    INHERITED_PROPERTIES.contains(&get_property_name(key))
+}
+
 /// A successfully parsed CSS property
 #[derive(Debug, Clone, PartialEq)]
 pub enum ParsedCssProperty {
     BorderRadius(BorderRadius),
     BackgroundColor(BackgroundColor),
     TextColor(TextColor),
-    Border(BorderWidths, BorderDetails),
-    Background(Background),
+    Border(StyleBorder),
+    BorderImage(Option<StyleBorderImage>),
+    Background(StyleBackgrounds),
+    BackgroundSize(StyleBackgroundSizes),
+    BackgroundPosition(StyleBackgroundPositions),
+    BackgroundRepeat(StyleBackgroundRepeats),
     FontSize(FontSize),
     FontFamily(FontFamily),
     TextAlign(TextAlignmentHorz),
-    BoxShadow(Option<BoxShadowPreDisplayItem>),
+    BoxShadow(StyleBoxShadows),
     LineHeight(LineHeight),
+    FontWeight(FontWeight),
+    FontStyle(FontStyle),
+    LetterSpacing(LetterSpacing),
+    WordSpacing(WordSpacing),
+    TextDecoration(TextDecoration),
+    TextTransform(TextTransform),
+    VerticalAlign(TextAlignmentVert),
 
     Width(LayoutWidth),
     Height(LayoutHeight),
@@ -92,6 +181,8 @@ pub enum ParsedCssProperty {
     MinHeight(LayoutMinHeight),
     MaxWidth(LayoutMaxWidth),
     MaxHeight(LayoutMaxHeight),
+    Padding(LayoutPadding),
+    Margin(LayoutMargin),
 
     FlexWrap(LayoutWrap),
     FlexDirection(LayoutDirection),
@@ -99,15 +190,42 @@ pub enum ParsedCssProperty {
     AlignItems(LayoutAlignItems),
     AlignContent(LayoutAlignContent),
     Overflow(LayoutOverflow),
+
+    Position(LayoutPosition),
+    Top(LayoutTop),
+    Right(LayoutRight),
+    Bottom(LayoutBottom),
+    Left(LayoutLeft),
+    ZIndex(LayoutZIndex),
+    Display(LayoutDisplay),
+    Visibility(StyleVisibility),
+    Cursor(StyleCursor),
+
+    Opacity(StyleOpacity),
+    Transform(StyleTransforms),
+    Filter(StyleFilters),
+
+    Transition(StyleTransitions),
+    Animation(StyleAnimations),
 }
 
 impl_from_no_lifetimes!(BorderRadius, ParsedCssProperty::BorderRadius);
-impl_from_no_lifetimes!(Background, ParsedCssProperty::Background);
+impl_from_no_lifetimes!(StyleBackgrounds, ParsedCssProperty::Background);
+impl_from_no_lifetimes!(StyleBackgroundSizes, ParsedCssProperty::BackgroundSize);
+impl_from_no_lifetimes!(StyleBackgroundPositions, ParsedCssProperty::BackgroundPosition);
+impl_from_no_lifetimes!(StyleBackgroundRepeats, ParsedCssProperty::BackgroundRepeat);
 impl_from_no_lifetimes!(FontSize, ParsedCssProperty::FontSize);
 impl_from_no_lifetimes!(FontFamily, ParsedCssProperty::FontFamily);
 impl_from_no_lifetimes!(LayoutOverflow, ParsedCssProperty::Overflow);
 impl_from_no_lifetimes!(TextAlignmentHorz, ParsedCssProperty::TextAlign);
 impl_from_no_lifetimes!(LineHeight, ParsedCssProperty::LineHeight);
+impl_from_no_lifetimes!(FontWeight, ParsedCssProperty::FontWeight);
+impl_from_no_lifetimes!(FontStyle, ParsedCssProperty::FontStyle);
+impl_from_no_lifetimes!(LetterSpacing, ParsedCssProperty::LetterSpacing);
+impl_from_no_lifetimes!(WordSpacing, ParsedCssProperty::WordSpacing);
+impl_from_no_lifetimes!(TextDecoration, ParsedCssProperty::TextDecoration);
+impl_from_no_lifetimes!(TextTransform, ParsedCssProperty::TextTransform);
+impl_from_no_lifetimes!(TextAlignmentVert, ParsedCssProperty::VerticalAlign);
 
 impl_from_no_lifetimes!(LayoutWidth, ParsedCssProperty::Width);
 impl_from_no_lifetimes!(LayoutHeight, ParsedCssProperty::Height);
@@ -115,6 +233,8 @@ impl_from_no_lifetimes!(LayoutMinWidth, ParsedCssProperty::MinWidth);
 impl_from_no_lifetimes!(LayoutMinHeight, ParsedCssProperty::MinHeight);
 impl_from_no_lifetimes!(LayoutMaxWidth, ParsedCssProperty::MaxWidth);
 impl_from_no_lifetimes!(LayoutMaxHeight, ParsedCssProperty::MaxHeight);
+impl_from_no_lifetimes!(LayoutPadding, ParsedCssProperty::Padding);
+impl_from_no_lifetimes!(LayoutMargin, ParsedCssProperty::Margin);
 
 impl_from_no_lifetimes!(LayoutWrap, ParsedCssProperty::FlexWrap);
 impl_from_no_lifetimes!(LayoutDirection, ParsedCssProperty::FlexDirection);
@@ -122,22 +242,249 @@ impl_from_no_lifetimes!(LayoutJustifyContent, ParsedCssProperty::JustifyContent)
 impl_from_no_lifetimes!(LayoutAlignItems, ParsedCssProperty::AlignItems);
 impl_from_no_lifetimes!(LayoutAlignContent, ParsedCssProperty::AlignContent);
 
+impl_from_no_lifetimes!(LayoutPosition, ParsedCssProperty::Position);
+impl_from_no_lifetimes!(LayoutTop, ParsedCssProperty::Top);
+impl_from_no_lifetimes!(LayoutRight, ParsedCssProperty::Right);
+impl_from_no_lifetimes!(LayoutBottom, ParsedCssProperty::Bottom);
+impl_from_no_lifetimes!(LayoutLeft, ParsedCssProperty::Left);
+impl_from_no_lifetimes!(LayoutZIndex, ParsedCssProperty::ZIndex);
+impl_from_no_lifetimes!(LayoutDisplay, ParsedCssProperty::Display);
+impl_from_no_lifetimes!(StyleVisibility, ParsedCssProperty::Visibility);
+impl_from_no_lifetimes!(StyleCursor, ParsedCssProperty::Cursor);
+
 impl_from_no_lifetimes!(BackgroundColor, ParsedCssProperty::BackgroundColor);
 impl_from_no_lifetimes!(TextColor, ParsedCssProperty::TextColor);
 
-impl From<(BorderWidths, BorderDetails)> for ParsedCssProperty {
-    fn from((widths, details): (BorderWidths, BorderDetails)) -> Self {
-        ParsedCssProperty::Border(widths, details)
+impl_from_no_lifetimes!(StyleOpacity, ParsedCssProperty::Opacity);
+impl_from_no_lifetimes!(StyleTransforms, ParsedCssProperty::Transform);
+impl_from_no_lifetimes!(StyleFilters, ParsedCssProperty::Filter);
+
+impl_from_no_lifetimes!(StyleTransitions, ParsedCssProperty::Transition);
+impl_from_no_lifetimes!(StyleAnimations, ParsedCssProperty::Animation);
+
+impl_from_no_lifetimes!(StyleBorder, ParsedCssProperty::Border);
+
+impl From<Option<StyleBorderImage>> for ParsedCssProperty {
+    fn from(border_image: Option<StyleBorderImage>) -> Self {
+        ParsedCssProperty::BorderImage(border_image)
+    }
+}
+
+impl_from_no_lifetimes!(StyleBoxShadows, ParsedCssProperty::BoxShadow);
+
+/// Converts a parsed CSS value back into CSS text, i.e. `PixelValue` -> `"5px"`.
+///
+/// Parsing the returned string results in the same value again, but the text
+/// itself may differ from the original, i.e. colors are always written as `#rrggbb`.
+pub trait ToCssString {
+    fn to_css_string(&self) -> String;
+}
+
+impl ToCssString for ParsedCssProperty {
+    fn to_css_string(&self) -> String {
+        use self::ParsedCssProperty::*;
+        match *self {
+            BorderRadius(ref r) => r.to_css_string(),
+            BackgroundColor(ref c) => c.to_css_string(),
+            TextColor(ref c) => c.to_css_string(),
+            Border(ref b) => b.to_css_string(),
+            BorderImage(ref i) => i.to_css_string(),
+            Background(ref b) => b.to_css_string(),
+            BackgroundSize(ref s) => s.to_css_string(),
+            BackgroundPosition(ref p) => p.to_css_string(),
+            BackgroundRepeat(ref r) => r.to_css_string(),
+            FontSize(ref s) => s.to_css_string(),
+            FontFamily(ref f) => f.to_css_string(),
+            TextAlign(ref a) => a.to_css_string(),
+            BoxShadow(ref s) => s.to_css_string(),
+            LineHeight(ref l) => l.to_css_string(),
+            FontWeight(ref w) => w.to_css_string(),
+            FontStyle(ref s) => s.to_css_string(),
+            LetterSpacing(ref s) => s.to_css_string(),
+            WordSpacing(ref s) => s.to_css_string(),
+            TextDecoration(ref d) => d.to_css_string(),
+            TextTransform(ref t) => t.to_css_string(),
+            VerticalAlign(ref a) => a.to_css_string(),
+
+            Width(ref w) => w.to_css_string(),
+            Height(ref h) => h.to_css_string(),
+            MinWidth(ref w) => w.to_css_string(),
+            MinHeight(ref h) => h.to_css_string(),
+            MaxWidth(ref w) => w.to_css_string(),
+            MaxHeight(ref h) => h.to_css_string(),
+            Padding(ref p) => p.to_css_string(),
+            Margin(ref m) => m.to_css_string(),
+
+            FlexWrap(ref w) => w.to_css_string(),
+            FlexDirection(ref d) => d.to_css_string(),
+            JustifyContent(ref j) => j.to_css_string(),
+            AlignItems(ref a) => a.to_css_string(),
+            AlignContent(ref a) => a.to_css_string(),
+            Overflow(ref o) => o.to_css_string(),
+
+            Position(ref p) => p.to_css_string(),
+            Top(ref t) => t.to_css_string(),
+            Right(ref r) => r.to_css_string(),
+            Bottom(ref b) => b.to_css_string(),
+            Left(ref l) => l.to_css_string(),
+            ZIndex(ref z) => z.to_css_string(),
+            Display(ref d) => d.to_css_string(),
+            Visibility(ref v) => v.to_css_string(),
+            Cursor(ref c) => c.to_css_string(),
+
+            Opacity(ref o) => o.to_css_string(),
+            Transform(ref t) => t.to_css_string(),
+            Filter(ref f) => f.to_css_string(),
+
+            Transition(ref t) => t.to_css_string(),
+            Animation(ref a) => a.to_css_string(),
+        }
     }
 }
 
-impl From<Option<BoxShadowPreDisplayItem>> for ParsedCssProperty {
-    fn from(box_shadow: Option<BoxShadowPreDisplayItem>) -> Self {
-        ParsedCssProperty::BoxShadow(box_shadow)
+/// Writes the property as a declaration, i.e. `width: 5px`
+impl fmt::Display for ParsedCssProperty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.get_key(), self.to_css_string())
     }
 }
 
 impl ParsedCssProperty {
+
+    /// Returns the key of the property, i.e. `"width"` for a `ParsedCssProperty::Width`
+    pub fn get_key(&self) -> &'static str {
+        use self::ParsedCssProperty::*;
+        match *self {
+            BorderRadius(_) => "border-radius",
+            BackgroundColor(_) => "background-color",
+            TextColor(_) => "color",
+            Border(ref border) => border.get_key(),
+            BorderImage(_) => "border-image",
+            Background(_) => "background",
+            BackgroundSize(_) => "background-size",
+            BackgroundPosition(_) => "background-position",
+            BackgroundRepeat(_) => "background-repeat",
+            FontSize(_) => "font-size",
+            FontFamily(_) => "font-family",
+            TextAlign(_) => "text-align",
+            BoxShadow(_) => "box-shadow",
+            LineHeight(_) => "line-height",
+            FontWeight(_) => "font-weight",
+            FontStyle(_) => "font-style",
+            LetterSpacing(_) => "letter-spacing",
+            WordSpacing(_) => "word-spacing",
+            TextDecoration(_) => "text-decoration",
+            TextTransform(_) => "text-transform",
+            VerticalAlign(_) => "vertical-align",
+
+            Width(_) => "width",
+            Height(_) => "height",
+            MinWidth(_) => "min-width",
+            MinHeight(_) => "min-height",
+            MaxWidth(_) => "max-width",
+            MaxHeight(_) => "max-height",
+            Padding(ref padding) => padding.0.get_key(&PADDING_KEYS),
+            Margin(ref margin) => margin.0.get_key(&MARGIN_KEYS),
+
+            FlexWrap(_) => "flex-wrap",
+            FlexDirection(_) => "flex-direction",
+            JustifyContent(_) => "justify-content",
+            AlignItems(_) => "align-items",
+            AlignContent(_) => "align-content",
+            Overflow(ref overflow) => match (overflow.horizontal, overflow.vertical) {
+                (TextOverflowBehaviour::Modified(_), TextOverflowBehaviour::NotModified) => "overflow-x",
+                (TextOverflowBehaviour::NotModified, TextOverflowBehaviour::Modified(_)) => "overflow-y",
+                _ => "overflow",
+            },
+
+            Position(_) => "position",
+            Top(_) => "top",
+            Right(_) => "right",
+            Bottom(_) => "bottom",
+            Left(_) => "left",
+            ZIndex(_) => "z-index",
+            Display(_) => "display",
+            Visibility(_) => "visibility",
+            Cursor(_) => "cursor",
+
+            Opacity(_) => "opacity",
+            Transform(_) => "transform",
+            Filter(_) => "filter",
+
+            Transition(_) => "transition",
+            Animation(_) => "animation",
+        }
+    }
+
+    /// Returns whether the children of a node inherit the property, see `is_inherited_property`
+    pub fn is_inherited(&self) -> bool {
+        is_inherited_property(self.get_key())
+    }
+
+    /// For a longhand of a merged property (`padding-top`, `overflow-x`, `border-color`):
+    /// replaces the parts that are set in `self` with the parts of `other`, if they are set
+    /// there as well. The parts that aren't set in `self` stay unset, so that inheriting
+    /// `padding-top` doesn't touch the other sides.
+    pub(crate) fn replace_set_parts(&mut self, other: &Self) {
+        use self::ParsedCssProperty::*;
+        match (self, other) {
+            (Padding(a), Padding(b)) => a.0.replace_set_sides(&b.0),
+            (Margin(a), Margin(b)) => a.0.replace_set_sides(&b.0),
+            (Overflow(a), Overflow(b)) => a.replace_set_directions(b),
+            (Border(a), Border(b)) => a.replace_set_parts(b),
+            _ => { },
+        }
+    }
+
+    /// Interpolates between two values of the same property for transitions and animations,
+    /// `t = 0.0` returns `self` and `t = 1.0` returns `other`.
+    ///
+    /// Colors, lengths, border radii, shadows, gradient stops, background sizes and positions,
+    /// font weights, z-indices, opacity, transforms and filters can be interpolated.
+    /// Returns `None` for properties that can't be interpolated (i.e. `flex-direction`)
+    /// and for values that don't fit together, i.e. gradients with a different number of stops.
+    pub fn interpolate(&self, other: &Self, t: f32) -> Option<Self> {
+        use self::ParsedCssProperty::*;
+        let interpolated = match (self, other) {
+            (BorderRadius(a), BorderRadius(b)) => BorderRadius(interpolate_border_radius(a, b, t)),
+            (BackgroundColor(a), BackgroundColor(b)) => BackgroundColor(self::BackgroundColor(interpolate_color_u(&a.0, &b.0, t))),
+            (TextColor(a), TextColor(b)) => TextColor(self::TextColor(interpolate_color_u(&a.0, &b.0, t))),
+            (Border(a), Border(b)) => Border(a.interpolate(b, t)?),
+            (Background(a), Background(b)) => Background(StyleBackgrounds(interpolate_layers(&a.0, &b.0, t, interpolate_background)?)),
+            (BackgroundSize(a), BackgroundSize(b)) => BackgroundSize(StyleBackgroundSizes(interpolate_layers(&a.0, &b.0, t, interpolate_background_size)?)),
+            (BackgroundPosition(a), BackgroundPosition(b)) => BackgroundPosition(StyleBackgroundPositions(interpolate_layers(&a.0, &b.0, t, interpolate_background_position)?)),
+            (FontSize(a), FontSize(b)) => FontSize(self::FontSize(a.0.interpolate(&b.0, t))),
+            (BoxShadow(a), BoxShadow(b)) => BoxShadow(interpolate_box_shadows(a, b, t)?),
+            (LineHeight(a), LineHeight(b)) => LineHeight(self::LineHeight(PercentageValue { number: interpolate_f32(a.0.number, b.0.number, t) })),
+            (FontWeight(a), FontWeight(b)) => FontWeight(self::FontWeight(interpolate_f32(a.0 as f32, b.0 as f32, t).round().max(1.0).min(1000.0) as u16)),
+            (LetterSpacing(a), LetterSpacing(b)) => LetterSpacing(self::LetterSpacing(a.0.interpolate(&b.0, t))),
+            (WordSpacing(a), WordSpacing(b)) => WordSpacing(self::WordSpacing(a.0.interpolate(&b.0, t))),
+
+            (Width(a), Width(b)) => Width(LayoutWidth(a.0.interpolate(&b.0, t))),
+            (Height(a), Height(b)) => Height(LayoutHeight(a.0.interpolate(&b.0, t))),
+            (MinWidth(a), MinWidth(b)) => MinWidth(LayoutMinWidth(a.0.interpolate(&b.0, t))),
+            (MinHeight(a), MinHeight(b)) => MinHeight(LayoutMinHeight(a.0.interpolate(&b.0, t))),
+            (MaxWidth(a), MaxWidth(b)) => MaxWidth(LayoutMaxWidth(a.0.interpolate(&b.0, t))),
+            (MaxHeight(a), MaxHeight(b)) => MaxHeight(LayoutMaxHeight(a.0.interpolate(&b.0, t))),
+            (Padding(a), Padding(b)) => Padding(LayoutPadding(a.0.interpolate(&b.0, t)?)),
+            (Margin(a), Margin(b)) => Margin(LayoutMargin(a.0.interpolate(&b.0, t)?)),
+
+            // `auto` can't be interpolated
+            (Top(LayoutTop(Some(a))), Top(LayoutTop(Some(b)))) => Top(LayoutTop(Some(a.interpolate(b, t)))),
+            (Right(LayoutRight(Some(a))), Right(LayoutRight(Some(b)))) => Right(LayoutRight(Some(a.interpolate(b, t)))),
+            (Bottom(LayoutBottom(Some(a))), Bottom(LayoutBottom(Some(b)))) => Bottom(LayoutBottom(Some(a.interpolate(b, t)))),
+            (Left(LayoutLeft(Some(a))), Left(LayoutLeft(Some(b)))) => Left(LayoutLeft(Some(a.interpolate(b, t)))),
+            (ZIndex(LayoutZIndex(Some(a))), ZIndex(LayoutZIndex(Some(b)))) => ZIndex(LayoutZIndex(Some(interpolate_f32(*a as f32, *b as f32, t).round() as i32))),
+
+            (Opacity(a), Opacity(b)) => Opacity(StyleOpacity(interpolate_f32(a.0, b.0, t))),
+            (Transform(a), Transform(b)) => Transform(StyleTransforms(interpolate_functions(&a.0, &b.0, t, TransformFunction::identity, TransformFunction::interpolate)?)),
+            (Filter(a), Filter(b)) => Filter(StyleFilters(interpolate_functions(&a.0, &b.0, t, FilterFunction::identity, FilterFunction::interpolate)?)),
+
+            _ => return None,
+        };
+        Some(interpolated)
+    }
+
     /// Main parsing function, takes a stringified key / value pair and either
     /// returns the parsed value or an error
     pub fn from_kv<'a>(key: &'a str, value: &'a str) -> Result<Self, CssParsingError<'a>> {
@@ -147,12 +494,30 @@ impl ParsedCssProperty {
             "border-radius"     => Ok(parse_css_border_radius(value)?.into()),
             "background-color"  => Ok(parse_css_background_color(value)?.into()),
             "color"             => Ok(parse_css_text_color(value)?.into()),
-            "border"            => Ok(parse_css_border(value)?.into()),
-            "background"        => Ok(parse_css_background(value)?.into()),
+            "border"            => Ok(StyleBorder::uniform(parse_css_border(value)?).into()),
+            "border-top"        => Ok(StyleBorder { top: parse_css_border(value)?, .. Default::default() }.into()),
+            "border-right"      => Ok(StyleBorder { right: parse_css_border(value)?, .. Default::default() }.into()),
+            "border-bottom"     => Ok(StyleBorder { bottom: parse_css_border(value)?, .. Default::default() }.into()),
+            "border-left"       => Ok(StyleBorder { left: parse_css_border(value)?, .. Default::default() }.into()),
+            "border-width"      => Ok(parse_css_border_width(value)?.into()),
+            "border-style"      => Ok(parse_css_border_style(value)?.into()),
+            "border-color"      => Ok(parse_css_border_color(value)?.into()),
+            "border-image"      => Ok(parse_css_border_image(value)?.into()),
+            "background"        => Ok(parse_css_backgrounds(value)?.into()),
+            "background-size"   => Ok(parse_css_background_size(value)?.into()),
+            "background-position" => Ok(parse_css_background_position(value)?.into()),
+            "background-repeat" => Ok(parse_css_background_repeat(value)?.into()),
             "font-size"         => Ok(parse_css_font_size(value)?.into()),
             "font-family"       => Ok(parse_css_font_family(value)?.into()),
-            "box-shadow"        => Ok(parse_css_box_shadow(value)?.into()),
+            "box-shadow"        => Ok(parse_css_box_shadows(value)?.into()),
             "line-height"       => Ok(parse_line_height(value)?.into()),
+            "font-weight"       => Ok(parse_font_weight(value)?.into()),
+            "font-style"        => Ok(parse_font_style(value)?.into()),
+            "letter-spacing"    => Ok(LetterSpacing(parse_text_spacing(value)?).into()),
+            "word-spacing"      => Ok(WordSpacing(parse_text_spacing(value)?).into()),
+            "text-decoration"   => Ok(parse_text_decoration(value)?.into()),
+            "text-transform"    => Ok(parse_text_transform(value)?.into()),
+            "vertical-align"    => Ok(parse_layout_vertical_align(value)?.into()),
 
             "width"             => Ok(parse_layout_width(value)?.into()),
             "height"            => Ok(parse_layout_height(value)?.into()),
@@ -160,6 +525,16 @@ impl ParsedCssProperty {
             "min-height"        => Ok(parse_layout_min_height(value)?.into()),
             "max-width"         => Ok(parse_layout_max_width(value)?.into()),
             "max-height"        => Ok(parse_layout_max_height(value)?.into()),
+            "padding"           => Ok(LayoutPadding(parse_layout_side_offsets(value)?).into()),
+            "padding-top"       => Ok(LayoutPadding(LayoutSideOffsets::top(parse_pixel_value(value)?)).into()),
+            "padding-right"     => Ok(LayoutPadding(LayoutSideOffsets::right(parse_pixel_value(value)?)).into()),
+            "padding-bottom"    => Ok(LayoutPadding(LayoutSideOffsets::bottom(parse_pixel_value(value)?)).into()),
+            "padding-left"      => Ok(LayoutPadding(LayoutSideOffsets::left(parse_pixel_value(value)?)).into()),
+            "margin"            => Ok(LayoutMargin(parse_layout_side_offsets(value)?).into()),
+            "margin-top"        => Ok(LayoutMargin(LayoutSideOffsets::top(parse_pixel_value(value)?)).into()),
+            "margin-right"      => Ok(LayoutMargin(LayoutSideOffsets::right(parse_pixel_value(value)?)).into()),
+            "margin-bottom"     => Ok(LayoutMargin(LayoutSideOffsets::bottom(parse_pixel_value(value)?)).into()),
+            "margin-left"       => Ok(LayoutMargin(LayoutSideOffsets::left(parse_pixel_value(value)?)).into()),
 
             "flex-wrap"         => Ok(parse_layout_wrap(value)?.into()),
             "flex-direction"    => Ok(parse_layout_direction(value)?.into()),
@@ -189,11 +564,204 @@ impl ParsedCssProperty {
             },
             "text-align"        => Ok(parse_layout_text_align(value)?.into()),
 
+            "position"          => Ok(parse_layout_position(value)?.into()),
+            "top"               => Ok(LayoutTop(parse_layout_offset(value)?).into()),
+            "right"             => Ok(LayoutRight(parse_layout_offset(value)?).into()),
+            "bottom"            => Ok(LayoutBottom(parse_layout_offset(value)?).into()),
+            "left"              => Ok(LayoutLeft(parse_layout_offset(value)?).into()),
+            "z-index"           => Ok(parse_layout_z_index(value)?.into()),
+            "display"           => Ok(parse_layout_display(value)?.into()),
+            "visibility"        => Ok(parse_style_visibility(value)?.into()),
+            "cursor"            => Ok(parse_style_cursor(value)?.into()),
+
+            "opacity"           => Ok(parse_style_opacity(value)?.into()),
+            "transform"         => Ok(parse_css_transform(value)?.into()),
+            "filter"            => Ok(parse_css_filter(value)?.into()),
+
+            "transition"        => Ok(parse_css_transition(value)?.into()),
+            "animation"         => Ok(parse_css_animation(value)?.into()),
+
             _ => Err((key, value).into())
         }
     }
 }
 
+fn interpolate_f32(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn interpolate_color_u(a: &ColorU, b: &ColorU, t: f32) -> ColorU {
+    let channel = |a: u8, b: u8| interpolate_f32(a as f32, b as f32, t).round().max(0.0).min(255.0) as u8;
+    ColorU { r: channel(a.r, b.r), g: channel(a.g, b.g), b: channel(a.b, b.b), a: channel(a.a, b.a) }
+}
+
+fn interpolate_color_f(a: &ColorF, b: &ColorF, t: f32) -> ColorF {
+    ColorF {
+        r: interpolate_f32(a.r, b.r, t),
+        g: interpolate_f32(a.g, b.g, t),
+        b: interpolate_f32(a.b, b.b, t),
+        a: interpolate_f32(a.a, b.a, t),
+    }
+}
+
+fn interpolate_size(a: &LayoutSize, b: &LayoutSize, t: f32) -> LayoutSize {
+    LayoutSize::new(interpolate_f32(a.width, b.width, t), interpolate_f32(a.height, b.height, t))
+}
+
+fn interpolate_border_radius(a: &BorderRadius, b: &BorderRadius, t: f32) -> BorderRadius {
+    BorderRadius {
+        top_left: interpolate_size(&a.top_left, &b.top_left, t),
+        top_right: interpolate_size(&a.top_right, &b.top_right, t),
+        bottom_left: interpolate_size(&a.bottom_left, &b.bottom_left, t),
+        bottom_right: interpolate_size(&a.bottom_right, &b.bottom_right, t),
+    }
+}
+
+/// Interpolates two shadows with the same clip mode. Like in CSS, `none` is
+/// interpolated as a transparent shadow without an offset, blur or spread.
+fn interpolate_box_shadow(a: &Option<BoxShadowPreDisplayItem>, b: &Option<BoxShadowPreDisplayItem>, t: f32)
+-> Option<Option<BoxShadowPreDisplayItem>>
+{
+    fn transparent_shadow(shadow: &BoxShadowPreDisplayItem) -> BoxShadowPreDisplayItem {
+        BoxShadowPreDisplayItem {
+            offset: LayoutVector2D::zero(),
+            color: ColorF { a: 0.0, .. shadow.color },
+            blur_radius: 0.0,
+            spread_radius: 0.0,
+            clip_mode: shadow.clip_mode,
+        }
+    }
+
+    let (a, b) = match (a, b) {
+        (Some(a), Some(b)) => (*a, *b),
+        (Some(a), None) => (*a, transparent_shadow(a)),
+        (None, Some(b)) => (transparent_shadow(b), *b),
+        (None, None) => return Some(None),
+    };
+
+    if a.clip_mode != b.clip_mode {
+        return None;
+    }
+
+    Some(Some(BoxShadowPreDisplayItem {
+        offset: LayoutVector2D::new(interpolate_f32(a.offset.x, b.offset.x, t), interpolate_f32(a.offset.y, b.offset.y, t)),
+        color: interpolate_color_f(&a.color, &b.color, t),
+        blur_radius: interpolate_f32(a.blur_radius, b.blur_radius, t),
+        spread_radius: interpolate_f32(a.spread_radius, b.spread_radius, t),
+        clip_mode: a.clip_mode,
+    }))
+}
+
+/// Interpolates two lists of shadows, the shadows with the same index need to have the
+/// same clip mode. The shorter list is padded with `none` shadows, like in CSS.
+fn interpolate_box_shadows(a: &StyleBoxShadows, b: &StyleBoxShadows, t: f32) -> Option<StyleBoxShadows> {
+    let shadows = (0..a.0.len().max(b.0.len()))
+        .map(|idx| interpolate_box_shadow(&a.0.get(idx).cloned(), &b.0.get(idx).cloned(), t))
+        .collect::<Option<Vec<Option<BoxShadowPreDisplayItem>>>>()?;
+    Some(StyleBoxShadows(shadows.into_iter().filter_map(|shadow| shadow).collect()))
+}
+
+/// Interpolates the stops of two gradients of the same kind. The gradients need to
+/// have the same number of stops and the same stops need to have an offset.
+fn interpolate_background(a: &Background, b: &Background, t: f32) -> Option<Background> {
+    fn interpolate_stops(a: &[GradientStopPre], b: &[GradientStopPre], t: f32) -> Option<Vec<GradientStopPre>> {
+        if a.len() != b.len() {
+            return None;
+        }
+        a.iter().zip(b.iter()).map(|(a, b)| {
+            let offset = match (a.offset, b.offset) {
+                (Some(a), Some(b)) => Some(a.interpolate(&b, t)),
+                (None, None) => None,
+                _ => return None,
+            };
+            Some(GradientStopPre { offset: offset, color: interpolate_color_f(&a.color, &b.color, t) })
+        }).collect()
+    }
+
+    match (a, b) {
+        (Background::LinearGradient(a), Background::LinearGradient(b)) if a.extend_mode == b.extend_mode => {
+            let direction = match (a.direction, b.direction) {
+                (Direction::Angle(a), Direction::Angle(b)) => Direction::Angle(interpolate_f32(a, b, t)),
+                (a, b) if a == b => a,
+                _ => return None,
+            };
+            Some(Background::LinearGradient(LinearGradientPreInfo {
+                direction: direction,
+                extend_mode: a.extend_mode,
+                stops: interpolate_stops(&a.stops, &b.stops, t)?,
+            }))
+        },
+        (Background::RadialGradient(a), Background::RadialGradient(b)) if a.extend_mode == b.extend_mode && a.shape == b.shape => {
+            let size = match (a.size, b.size) {
+                (RadialGradientSize::Exact(ax, ay), RadialGradientSize::Exact(bx, by)) => RadialGradientSize::Exact(ax.interpolate(&bx, t), ay.interpolate(&by, t)),
+                (a, b) if a == b => a,
+                _ => return None,
+            };
+            Some(Background::RadialGradient(RadialGradientPreInfo {
+                shape: a.shape,
+                size: size,
+                position: interpolate_background_position(&a.position, &b.position, t)?,
+                extend_mode: a.extend_mode,
+                stops: interpolate_stops(&a.stops, &b.stops, t)?,
+            }))
+        },
+        _ => None,
+    }
+}
+
+/// Interpolates the background layers of two values of `background`, `background-size`
+/// or `background-position`, both values need to have the same number of layers
+fn interpolate_layers<T, F: Fn(&T, &T, f32) -> Option<T>>(a: &[T], b: &[T], t: f32, interpolate: F) -> Option<Vec<T>> {
+    if a.len() != b.len() {
+        return None;
+    }
+    a.iter().zip(b.iter()).map(|(a, b)| interpolate(a, b, t)).collect()
+}
+
+/// Interpolates two `background-size`s, `auto` can only be interpolated with `auto`
+fn interpolate_background_size(a: &BackgroundSize, b: &BackgroundSize, t: f32) -> Option<BackgroundSize> {
+    fn interpolate_length(a: Option<PixelValue>, b: Option<PixelValue>, t: f32) -> Option<Option<PixelValue>> {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(Some(a.interpolate(&b, t))),
+            (None, None) => Some(None),
+            _ => None,
+        }
+    }
+
+    match (*a, *b) {
+        (BackgroundSize::Exact(width_a, height_a), BackgroundSize::Exact(width_b, height_b)) => {
+            Some(BackgroundSize::Exact(interpolate_length(width_a, width_b, t)?, interpolate_length(height_a, height_b, t)?))
+        },
+        (a, b) if a == b => Some(a),
+        _ => None,
+    }
+}
+
+fn interpolate_background_position(a: &BackgroundPosition, b: &BackgroundPosition, t: f32) -> Option<BackgroundPosition> {
+    Some(BackgroundPosition {
+        horizontal: a.horizontal.interpolate(&b.horizontal, t),
+        vertical: a.vertical.interpolate(&b.vertical, t),
+    })
+}
+
+/// Interpolates two lists of transform or filter functions. A `none` (an empty list) is
+/// interpolated with the identity functions of the other list, i.e. `none` to `scale(2)`
+/// is `scale(1.5)` halfway through. The functions of both lists have to be of the same type.
+fn interpolate_functions<F>(a: &[F], b: &[F], t: f32, identity: fn(&F) -> F, interpolate: fn(&F, &F, f32) -> Option<F>)
+-> Option<Vec<F>>
+{
+    if a.is_empty() {
+        return b.iter().map(|b| interpolate(&identity(b), b, t)).collect();
+    }
+    if b.is_empty() {
+        return a.iter().map(|a| interpolate(a, &identity(a), t)).collect();
+    }
+    if a.len() != b.len() {
+        return None;
+    }
+    a.iter().zip(b.iter()).map(|(a, b)| interpolate(a, b, t)).collect()
+}
+
 /// Wrapper for the `overflow-{x,y}` + `overflow` property
 #[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct LayoutOverflow {
@@ -216,6 +784,18 @@ impl LayoutOverflow {
         merge_property(&mut self.vertical, &other.vertical);
     }
 
+    // replaces the directions that are set in `self` with the ones of `other`, if they are set there as well
+    fn replace_set_directions(&mut self, other: &LayoutOverflow) {
+        fn replace_property(p: &mut TextOverflowBehaviour, other: &TextOverflowBehaviour) {
+            if *p != TextOverflowBehaviour::NotModified && *other != TextOverflowBehaviour::NotModified {
+                *p = *other;
+            }
+        }
+
+        replace_property(&mut self.horizontal, &other.horizontal);
+        replace_property(&mut self.vertical, &other.vertical);
+    }
+
     pub fn allows_horizontal_overflow(&self) -> bool {
         use self::TextOverflowBehaviourInner::*;
         match self.horizontal {
@@ -229,12 +809,25 @@ impl LayoutOverflow {
     }
 }
 
+/// Writes the overflow of the direction that is set. If both directions are set to
+/// different values, this can't be expressed as one value, the horizontal one is used.
+impl ToCssString for LayoutOverflow {
+    fn to_css_string(&self) -> String {
+        match (self.horizontal, self.vertical) {
+            (TextOverflowBehaviour::Modified(overflow), _) |
+            (TextOverflowBehaviour::NotModified, TextOverflowBehaviour::Modified(overflow)) => overflow.to_css_string(),
+            (TextOverflowBehaviour::NotModified, TextOverflowBehaviour::NotModified) => TextOverflowBehaviourInner::default().to_css_string(),
+        }
+    }
+}
+
 /// Error containing all sub-errors that could happen during CSS parsing
 ///
 /// Usually we want to crash on the first error, to notify the user of the problem.
 #[derive(Debug, Clone, PartialEq)]
 pub enum CssParsingError<'a> {
     CssBorderParseError(CssBorderParseError<'a>),
+    CssBorderImageParseError(CssBorderImageParseError<'a>),
     CssShadowParseError(CssShadowParseError<'a>),
     InvalidValueErr(InvalidValueErr<'a>),
     PixelParseError(PixelParseError<'a>),
@@ -244,12 +837,17 @@ pub enum CssParsingError<'a> {
     CssBackgroundParseError(CssBackgroundParseError<'a>),
     CssColorParseError(CssColorParseError<'a>),
     CssBorderRadiusParseError(CssBorderRadiusParseError<'a>),
+    CssSideOffsetsParseError(CssSideOffsetsParseError<'a>),
+    CssAnimationParseError(CssAnimationParseError<'a>),
+    CssTransformParseError(CssTransformParseError<'a>),
+    CssFilterParseError(CssFilterParseError<'a>),
     /// Key is not supported, i.e. `#div { aldfjasdflk: 400px }` results in an
     /// `UnsupportedCssKey("aldfjasdflk", "400px")` error
     UnsupportedCssKey(&'a str, &'a str),
 }
 
 impl_from!(CssBorderParseError, CssParsingError::CssBorderParseError);
+impl_from!(CssBorderImageParseError, CssParsingError::CssBorderImageParseError);
 impl_from!(CssShadowParseError, CssParsingError::CssShadowParseError);
 impl_from!(CssColorParseError, CssParsingError::CssColorParseError);
 impl_from!(InvalidValueErr, CssParsingError::InvalidValueErr);
@@ -258,6 +856,10 @@ impl_from!(CssImageParseError, CssParsingError::CssImageParseError);
 impl_from!(CssFontFamilyParseError, CssParsingError::CssFontFamilyParseError);
 impl_from!(CssBackgroundParseError, CssParsingError::CssBackgroundParseError);
 impl_from!(CssBorderRadiusParseError, CssParsingError::CssBorderRadiusParseError);
+impl_from!(CssSideOffsetsParseError, CssParsingError::CssSideOffsetsParseError);
+impl_from!(CssAnimationParseError, CssParsingError::CssAnimationParseError);
+impl_from!(CssTransformParseError, CssParsingError::CssTransformParseError);
+impl_from!(CssFilterParseError, CssParsingError::CssFilterParseError);
 
 impl<'a> From<(&'a str, &'a str)> for CssParsingError<'a> {
     fn from((a, b): (&'a str, &'a str)) -> Self {
@@ -275,28 +877,135 @@ impl<'a> From<PercentageParseError> for CssParsingError<'a> {
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct InvalidValueErr<'a>(pub &'a str);
 
+/// A length such as `5px`, `50%` or `calc(100% - 2em)`. Relative lengths
+/// can only be resolved during layout, see `PixelValue::resolve`.
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
 pub struct PixelValue {
-    metric: CssMetric,
+    /// The number of every `CssMetric` (indexed by `CssMetric as usize`), since a
+    /// `calc()` expression can mix metrics, i.e. `calc(100% - 2em)` is stored as
+    /// `100%` + `-2em`.
+    ///
     /// Has to be divided by 1000.0 - PixelValue needs to implement Hash,
     /// but Hash is not possible for floating-point values
-    number: isize,
+    numbers: [isize; CSS_METRIC_COUNT],
 }
 
-impl PixelValue {
+/// The values that relative lengths (`em`, `rem`, `%`, `vw`, `vh`) are resolved against
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct LengthContext {
+    /// The font size of the node, in pixels
+    pub(crate) font_size: f32,
+    /// The font size of the root node, in pixels
+    pub(crate) root_font_size: f32,
+    /// The size of the window, in pixels
+    pub(crate) viewport: LayoutSize,
+}
 
-    pub fn from_metric(metric: CssMetric, value: f32) -> Self {
+impl Default for LengthContext {
+    fn default() -> Self {
         Self {
-            metric: metric,
-            number: (value * 1000.0) as isize,
+            font_size: EM_HEIGHT,
+            root_font_size: EM_HEIGHT,
+            viewport: LayoutSize::zero(),
+        }
+    }
+}
+
+impl LengthContext {
+    /// Returns the context of a child node with the given `font-size`, which
+    /// is resolved against the font size of the parent (the current context)
+    pub(crate) fn with_font_size(&self, font_size: Option<FontSize>) -> Self {
+        match font_size {
+            Some(font_size) => Self { font_size: font_size.0.resolve(self, self.font_size), .. *self },
+            None => *self,
         }
     }
+}
+
+impl PixelValue {
+
+    /// A value of `0px`
+    pub fn zero() -> Self {
+        Self { numbers: [0; CSS_METRIC_COUNT] }
+    }
+
+    pub fn from_metric(metric: CssMetric, value: f32) -> Self {
+        let mut numbers = [0; CSS_METRIC_COUNT];
+        numbers[metric as usize] = (value * 1000.0) as isize;
+        Self { numbers }
+    }
+
+    /// Returns the number of the given metric, i.e. `50.0` for `CssMetric::Percent` in `calc(50% + 5px)`
+    pub fn get(&self, metric: CssMetric) -> f32 {
+        self.numbers[metric as usize] as f32 / 1000.0
+    }
 
+    /// Converts the value to pixels without a layout context: `em` and `rem` are
+    /// relative to the default font size, `%`, `vw` and `vh` resolve to 0
     pub fn to_pixels(&self) -> f32 {
-        match self.metric {
-            CssMetric::Px => { self.number as f32 / 1000.0 },
-            CssMetric::Pt => { (self.number as f32 / 1000.0) * PT_TO_PX },
-            CssMetric::Em => { (self.number as f32 / 1000.0) * EM_HEIGHT },
+        self.resolve(&LengthContext::default(), 0.0)
+    }
+
+    /// Converts the value to pixels, `%` is relative to the `percent_reference`
+    /// (i.e. the width of the containing block for a `width`)
+    pub(crate) fn resolve(&self, context: &LengthContext, percent_reference: f32) -> f32 {
+        CSS_METRICS.iter().map(|metric| {
+            let number = self.get(*metric);
+            match *metric {
+                CssMetric::Px => number,
+                CssMetric::Pt => number * PT_TO_PX,
+                CssMetric::Em => number * context.font_size,
+                CssMetric::Rem => number * context.root_font_size,
+                CssMetric::Percent => number / 100.0 * percent_reference,
+                CssMetric::Vw => number / 100.0 * context.viewport.width,
+                CssMetric::Vh => number / 100.0 * context.viewport.height,
+            }
+        }).sum()
+    }
+
+    fn add(&self, other: &PixelValue) -> Self {
+        let mut numbers = self.numbers;
+        for (number, other) in numbers.iter_mut().zip(other.numbers.iter()) {
+            *number += other;
+        }
+        Self { numbers }
+    }
+
+    fn scale(&self, factor: f32) -> Self {
+        let mut numbers = self.numbers;
+        for number in numbers.iter_mut() {
+            *number = (*number as f32 * factor) as isize;
+        }
+        Self { numbers }
+    }
+
+    /// Interpolates every metric on its own, so that `5px` to `50%` is
+    /// `calc(2.5px + 25%)` halfway through
+    pub(crate) fn interpolate(&self, other: &PixelValue, t: f32) -> Self {
+        self.scale(1.0 - t).add(&other.scale(t))
+    }
+}
+
+/// Writes the value as `5px`, or as `calc(100% - 2em)` if it mixes multiple metrics
+impl ToCssString for PixelValue {
+    fn to_css_string(&self) -> String {
+        let components = CSS_METRICS.iter()
+            .filter(|metric| self.numbers[**metric as usize] != 0)
+            .map(|metric| (self.get(*metric), *metric))
+            .collect::<Vec<(f32, CssMetric)>>();
+
+        match components.len() {
+            0 => String::from("0px"),
+            1 => format!("{}{}", components[0].0, components[0].1.to_css_string()),
+            _ => {
+                let mut calc = format!("calc({}{}", components[0].0, components[0].1.to_css_string());
+                for (number, metric) in &components[1..] {
+                    let operator = if *number < 0.0 { '-' } else { '+' };
+                    calc.push_str(&format!(" {} {}{}", operator, number.abs(), metric.to_css_string()));
+                }
+                calc.push(')');
+                calc
+            }
         }
     }
 }
@@ -312,7 +1021,36 @@ pub struct PercentageValue {
 pub enum CssMetric {
     Px,
     Pt,
+    /// Relative to the font size of the node
     Em,
+    /// Relative to the font size of the root node
+    Rem,
+    /// Relative to the size of the containing block
+    Percent,
+    /// Relative to the width of the window
+    Vw,
+    /// Relative to the height of the window
+    Vh,
+}
+
+const CSS_METRIC_COUNT: usize = 7;
+const CSS_METRICS: [CssMetric; CSS_METRIC_COUNT] = [
+    CssMetric::Px, CssMetric::Pt, CssMetric::Em, CssMetric::Rem,
+    CssMetric::Percent, CssMetric::Vw, CssMetric::Vh,
+];
+
+impl ToCssString for CssMetric {
+    fn to_css_string(&self) -> String {
+        match *self {
+            CssMetric::Px => "px",
+            CssMetric::Pt => "pt",
+            CssMetric::Em => "em",
+            CssMetric::Rem => "rem",
+            CssMetric::Percent => "%",
+            CssMetric::Vw => "vw",
+            CssMetric::Vh => "vh",
+        }.to_string()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -323,11 +1061,35 @@ pub enum CssBorderRadiusParseError<'a> {
 
 impl_from!(PixelParseError, CssBorderRadiusParseError::PixelParseError);
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CssSideOffsetsParseError<'a> {
+    TooManyValues(&'a str),
+    PixelParseError(PixelParseError<'a>),
+}
+
+impl_from!(PixelParseError, CssSideOffsetsParseError::PixelParseError);
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum CssColorParseError<'a> {
+    /// Neither a named color, a hex color nor a color function, i.e. `bluish` or `lab(50% 0 0)`
     InvalidColor(&'a str),
+    /// A hex color contains a character that isn't a hex digit, i.e. `#ff00gg`
     InvalidColorComponent(u8),
     ValueParseErr(ParseIntError),
+    /// A hex color doesn't have 3, 4, 6 or 8 digits, i.e. `#ff00f`
+    InvalidHexLength(&'a str),
+    /// A color function is missing its closing parenthesis, i.e. `rgb(255, 0, 0`
+    UnclosedColorFunction(&'a str),
+    /// A color function doesn't have 3 components (or 4, including the alpha), i.e. `rgb(255, 0)`
+    WrongNumberOfComponents(&'a str),
+    /// A component of a color function isn't a number or percentage (or an angle, for
+    /// the hue of `hsl()`), i.e. the `red` in `rgb(red, 0, 0)`
+    InvalidComponent(&'a str),
+    /// The saturation and lightness of `hsl()` have to be percentages, i.e. the `1` in `hsl(0, 1, 50%)`
+    ExpectedPercentage(&'a str),
+    /// `currentColor` refers to the `color` of the node, so it can only be used in
+    /// a stylesheet (where it is resolved during the cascade), not in a parsed value
+    UnresolvedCurrentColor(&'a str),
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -352,6 +1114,18 @@ pub enum CssBorderParseError<'a> {
     ColorParseError(CssColorParseError<'a>),
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum CssBorderImageParseError<'a> {
+    /// The border image isn't an `image("id")`
+    InvalidSource(&'a str),
+    /// The slices aren't one to four whole numbers
+    InvalidSlice(&'a str),
+    InvalidRepeat(InvalidValueErr<'a>),
+    TooManyValues(&'a str),
+}
+
+impl_from!(InvalidValueErr, CssBorderImageParseError::InvalidRepeat);
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum CssShadowParseError<'a> {
     InvalidSingleStatement(&'a str),
@@ -363,6 +1137,19 @@ pub enum CssShadowParseError<'a> {
 impl_from!(PixelParseError, CssShadowParseError::ValueParseErr);
 impl_from!(CssColorParseError, CssShadowParseError::ColorParseError);
 
+/// Writes a single value if all corners are equal, otherwise the four
+/// corners (top-left, top-right, bottom-right, bottom-left)
+impl ToCssString for BorderRadius {
+    fn to_css_string(&self) -> String {
+        let corners = [self.top_left.width, self.top_right.width, self.bottom_right.width, self.bottom_left.width];
+        if corners.iter().all(|corner| *corner == corners[0]) {
+            format!("{}px", corners[0])
+        } else {
+            corners.iter().map(|corner| format!("{}px", corner)).collect::<Vec<String>>().join(" ")
+        }
+    }
+}
+
 /// parse the border-radius like "5px 10px" or "5px 10px 6px 10px"
 fn parse_css_border_radius<'a>(input: &'a str)
 -> Result<BorderRadius, CssBorderRadiusParseError<'a>>
@@ -437,12 +1224,24 @@ fn parse_css_border_radius<'a>(input: &'a str)
 pub enum PixelParseError<'a> {
     InvalidComponent(&'a str),
     ValueParseErr(ParseFloatError),
+    /// The `calc()` expression is invalid, i.e. `calc(5px * 5px)` or `calc(5px +)`
+    InvalidCalc(&'a str),
 }
 
-/// parse a single value such as "15px"
-fn parse_pixel_value<'a>(input: &'a str)
+/// parse a single value such as "15px", "50%" or "calc(100% - 15px)"
+pub(crate) fn parse_pixel_value<'a>(input: &'a str)
 -> Result<PixelValue, PixelParseError<'a>>
 {
+    const CALC: &str = "calc(";
+
+    if input.starts_with(CALC) && input.ends_with(')') {
+        let mut tokens = &tokenize_calc(&input[CALC.len()..input.len() - 1])[..];
+        return match parse_calc_sum(&mut tokens) {
+            Some(CalcValue::Length(length)) if tokens.is_empty() => Ok(length),
+            _ => Err(PixelParseError::InvalidCalc(input)),
+        };
+    }
+
     let mut split_pos = 0;
     for (idx, ch) in input.char_indices() {
         if ch.is_numeric() || ch == '.' {
@@ -456,16 +1255,115 @@ fn parse_pixel_value<'a>(input: &'a str)
     let unit = match unit {
         "px" => CssMetric::Px,
         "em" => CssMetric::Em,
-        "ept" => CssMetric::Pt,
+        "pt" => CssMetric::Pt,
+        "rem" => CssMetric::Rem,
+        "%" => CssMetric::Percent,
+        "vw" => CssMetric::Vw,
+        "vh" => CssMetric::Vh,
         _ => { return Err(PixelParseError::InvalidComponent(&input[(split_pos - 1)..])); }
     };
 
     let number = input[..split_pos].parse::<f32>().map_err(|e| PixelParseError::ValueParseErr(e))?;
 
-    Ok(PixelValue {
-        metric: unit,
-        number: (number * 1000.0) as isize,
-    })
+    Ok(PixelValue::from_metric(unit, number))
+}
+
+/// An intermediate value of a `calc()` expression
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum CalcValue {
+    Number(f32),
+    Length(PixelValue),
+}
+
+/// Splits the inside of a `calc()` expression into numbers, lengths, operators and parentheses.
+/// Like in CSS, `+` and `-` have to be followed by whitespace, otherwise they are a sign (`-5px`).
+fn tokenize_calc(input: &str) -> Vec<&str> {
+    const NESTED_CALC: &str = "calc(";
+
+    let mut tokens = Vec::new();
+    let mut rest = input.trim_left();
+
+    while !rest.is_empty() {
+        let token_length = if rest.starts_with(NESTED_CALC) {
+            NESTED_CALC.len()
+        } else {
+            match rest.as_bytes()[0] {
+                b'(' | b')' | b'*' | b'/' => 1,
+                b'+' | b'-' if rest[1..].starts_with(char::is_whitespace) => 1,
+                _ => rest.find(|c: char| c.is_whitespace() || "()*/".contains(c)).unwrap_or(rest.len()),
+            }
+        };
+        tokens.push(&rest[..token_length]);
+        rest = rest[token_length..].trim_left();
+    }
+
+    tokens
+}
+
+/// Parses a sum of products, i.e. `100% - 2 * 5px`
+fn parse_calc_sum(tokens: &mut &[&str]) -> Option<CalcValue> {
+    let mut sum = parse_calc_product(tokens)?;
+
+    while let Some(&operator) = tokens.first() {
+        let sign = match operator {
+            "+" => 1.0,
+            "-" => -1.0,
+            _ => break,
+        };
+        *tokens = &tokens[1..];
+        sum = match (sum, parse_calc_product(tokens)?) {
+            (CalcValue::Number(a), CalcValue::Number(b)) => CalcValue::Number(a + sign * b),
+            (CalcValue::Length(a), CalcValue::Length(b)) => CalcValue::Length(a.add(&b.scale(sign))),
+            // numbers and lengths can't be added
+            _ => return None,
+        };
+    }
+
+    Some(sum)
+}
+
+/// Parses a product, i.e. `2 * 5px` or `100% / 3`
+fn parse_calc_product(tokens: &mut &[&str]) -> Option<CalcValue> {
+    use self::CalcValue::*;
+
+    let mut product = parse_calc_factor(tokens)?;
+
+    while let Some(&operator) = tokens.first() {
+        if operator != "*" && operator != "/" {
+            break;
+        }
+        *tokens = &tokens[1..];
+        product = match (operator, product, parse_calc_factor(tokens)?) {
+            ("*", Number(a), Number(b)) => Number(a * b),
+            ("*", Number(n), Length(l)) | ("*", Length(l), Number(n)) => Length(l.scale(n)),
+            ("/", Number(a), Number(b)) if b != 0.0 => Number(a / b),
+            ("/", Length(l), Number(n)) if n != 0.0 => Length(l.scale(1.0 / n)),
+            // lengths can't be multiplied with or divided by lengths
+            _ => return None,
+        };
+    }
+
+    Some(product)
+}
+
+/// Parses a number, a length or an expression in parentheses
+fn parse_calc_factor(tokens: &mut &[&str]) -> Option<CalcValue> {
+    let (&token, rest) = tokens.split_first()?;
+    *tokens = rest;
+
+    match token {
+        "(" | "calc(" => {
+            let value = parse_calc_sum(tokens)?;
+            let (&closing_brace, rest) = tokens.split_first()?;
+            if closing_brace != ")" {
+                return None;
+            }
+            *tokens = rest;
+            Some(value)
+        },
+        _ => token.parse::<f32>().ok().and_then(|n| Some(CalcValue::Number(n)))
+            .or_else(|| parse_pixel_value(token).ok().and_then(|l| Some(CalcValue::Length(l)))),
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -498,20 +1396,148 @@ fn parse_percentage_value(input: &str)
     })
 }
 
+impl ToCssString for PercentageValue {
+    fn to_css_string(&self) -> String {
+        format!("{}", self.number)
+    }
+}
+
+/// Writes the color as `#rrggbb`, or as `#rrggbbaa` if it is transparent
+impl ToCssString for ColorU {
+    fn to_css_string(&self) -> String {
+        if self.a == 255 {
+            format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+        }
+    }
+}
+
+impl ToCssString for ColorF {
+    fn to_css_string(&self) -> String {
+        ColorU::from(*self).to_css_string()
+    }
+}
+
+/// The `currentColor` keyword, which can be used instead of any color
+pub(crate) const CURRENT_COLOR: &str = "currentcolor";
+
 /// Parse any valid CSS color, INCLUDING THE HASH
 ///
 /// "blue" -> "00FF00" -> ColorF { r: 0, g: 255, b: 0 })
 /// "#00FF00" -> ColorF { r: 0, g: 255, b: 0 })
+/// "rgba(0, 255, 0, 50%)" -> ColorF { r: 0, g: 255, b: 0, a: 128 })
+/// "hsl(120deg 100% 50%)" -> ColorF { r: 0, g: 255, b: 0 })
 pub(crate) fn parse_css_color<'a>(input: &'a str)
 -> Result<ColorU, CssColorParseError<'a>>
 {
     if input.starts_with('#') {
         parse_color_no_hash(&input[1..])
+    } else if input.contains('(') {
+        parse_color_function(input)
+    } else if input.eq_ignore_ascii_case(CURRENT_COLOR) {
+        Err(CssColorParseError::UnresolvedCurrentColor(input))
     } else {
         parse_color_builtin(input)
     }
 }
 
+// parses "rgb(255, 0, 0)", "rgba(100%, 0%, 0%, 0.5)", "hsl(0deg, 100%, 50%)", "hsla(0, 100%, 50%, 50%)"
+// and the space-separated syntax "rgb(255 0 0 / 50%)"
+fn parse_color_function<'a>(input: &'a str)
+-> Result<ColorU, CssColorParseError<'a>>
+{
+    let (name, arguments) = parse_css_function(input).ok_or(CssColorParseError::UnclosedColorFunction(input))?;
+
+    let is_rgb = match name {
+        "rgb" | "rgba" => true,
+        "hsl" | "hsla" => false,
+        _ => return Err(CssColorParseError::InvalidColor(input)),
+    };
+
+    let components = match arguments.len() {
+        // "255 0 0 / 50%"
+        1 => {
+            let mut parts = arguments[0].splitn(2, '/');
+            let mut components = split_values(parts.next().unwrap_or(""));
+            if let Some(alpha) = parts.next() {
+                components.push(alpha.trim());
+            }
+            components
+        },
+        _ => arguments,
+    };
+
+    let alpha = match components.len() {
+        3 => 1.0,
+        4 => parse_color_component(components[3], 1.0)?,
+        _ => return Err(CssColorParseError::WrongNumberOfComponents(input)),
+    };
+
+    let (r, g, b) = if is_rgb {
+        (parse_color_component(components[0], 255.0)?,
+         parse_color_component(components[1], 255.0)?,
+         parse_color_component(components[2], 255.0)?)
+    } else {
+        let hue = parse_angle(components[0])
+            .or_else(|| components[0].parse::<f32>().ok())
+            .ok_or(CssColorParseError::InvalidComponent(components[0]))?;
+        let percentage = |component: &'a str| {
+            if component.ends_with('%') {
+                parse_color_component(component, 1.0)
+            } else {
+                Err(CssColorParseError::ExpectedPercentage(component))
+            }
+        };
+        let (r, g, b) = hsl_to_rgb(hue, percentage(components[1])?, percentage(components[2])?);
+        (r * 255.0, g * 255.0, b * 255.0)
+    };
+
+    Ok(ColorU {
+        r: r.round() as u8,
+        g: g.round() as u8,
+        b: b.round() as u8,
+        a: (alpha * 255.0).round() as u8,
+    })
+}
+
+/// Parses a number or a percentage of the `max` value, i.e. "255" or "100%".
+/// Like in CSS, values outside of `0..max` are clamped.
+fn parse_color_component<'a>(input: &'a str, max: f32)
+-> Result<f32, CssColorParseError<'a>>
+{
+    let (number, scale) = if input.ends_with('%') {
+        (&input[..input.len() - 1], max / 100.0)
+    } else {
+        (input, 1.0)
+    };
+
+    match number.parse::<f32>() {
+        Ok(number) if number.is_finite() => Ok((number * scale).max(0.0).min(max)),
+        _ => Err(CssColorParseError::InvalidComponent(input)),
+    }
+}
+
+/// Converts a color from HSL (the hue in degrees, the saturation and
+/// lightness between 0.0 and 1.0) to RGB (between 0.0 and 1.0)
+fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> (f32, f32, f32) {
+    let sector = (hue % 360.0 + 360.0) % 360.0 / 60.0;
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    let m = lightness - chroma / 2.0;
+    (r + m, g + m, b + m)
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct BackgroundColor(pub ColorU);
 
@@ -685,7 +1711,7 @@ fn parse_color_builtin<'a>(input: &'a str)
         "WhiteSmoke"             | "white-smoke"                =>  "F5F5F5",
         "Yellow"                 | "yellow"                     =>  "FFFF00",
         "YellowGreen"            | "yellow-green"               =>  "9ACD32",
-        "Transparent"            | "transparent"                =>  "FFFFFFFF",
+        "Transparent"            | "transparent"                =>  "00000000",
         _ => { return Err(CssColorParseError::InvalidColor(input)); }
     };
     parse_color_no_hash(color)
@@ -764,61 +1790,254 @@ fn parse_color_no_hash<'a>(input: &'a str)
                 a: (input & 255) as u8,
             })
         },
-        _ => { Err(CssColorParseError::InvalidColor(input)) }
+        _ => { Err(CssColorParseError::InvalidHexLength(input)) }
     }
 }
 
-/// Parse a CSS border such as
-///
-/// "5px solid red"
-fn parse_css_border<'a>(input: &'a str)
--> Result<(BorderWidths, BorderDetails), CssBorderParseError<'a>>
-{
-    let mut input_iter = input.split_whitespace();
+impl_to_css_string_newtype!(BackgroundColor, TextColor);
+
+/// The width of a border side if the width isn't set, i.e. for `border: solid`
+const DEFAULT_BORDER_WIDTH: f32 = 1.0;
+/// The color of a border side if the color isn't set, i.e. for `border: 1px solid`
+const DEFAULT_BORDER_COLOR: ColorU = ColorU { r: 0, g: 0, b: 0, a: 255 };
+
+/// `border`, `border-top`, `border-width`, etc. Like `LayoutSideOffsets`, only the sides
+/// (and the parts of a side) that are set by the property are `Some`, i.e. `border-top`
+/// only sets the `top` side and `border-color` only sets the colors of all sides.
+#[derive(Debug, Default, PartialEq, Copy, Clone)]
+pub struct StyleBorder {
+    pub top: StyleBorderSide,
+    pub right: StyleBorderSide,
+    pub bottom: StyleBorderSide,
+    pub left: StyleBorderSide,
+}
 
-    let (thickness, style, color);
+/// The width, style and color of one side of a border
+#[derive(Debug, Default, PartialEq, Copy, Clone)]
+pub struct StyleBorderSide {
+    pub width: Option<PixelValue>,
+    pub style: Option<BorderStyle>,
+    pub color: Option<ColorU>,
+}
 
-    match input_iter.clone().count() {
-        1 => {
-            style = parse_border_style(input_iter.next().unwrap())
-                            .map_err(|e| CssBorderParseError::InvalidBorderStyle(e))?;
-            thickness = 1.0;
-            color = ColorU { r: 0, g: 0, b: 0, a: 255 };
-        },
-        3 => {
-            thickness = parse_pixel_value(input_iter.next().unwrap())
-                           .map_err(|e| CssBorderParseError::ThicknessParseError(e))?.to_pixels();
-            style = parse_border_style(input_iter.next().unwrap())
-                           .map_err(|e| CssBorderParseError::InvalidBorderStyle(e))?;
-            color = parse_css_color(input_iter.next().unwrap())
-                           .map_err(|e| CssBorderParseError::ColorParseError(e))?;
-       },
-       _ => {
-            return Err(CssBorderParseError::InvalidBorderDeclaration(input));
-       }
+impl StyleBorderSide {
+
+    // "merges" two sides, the parts that are set in `other` win
+    pub fn merge(&mut self, other: &StyleBorderSide) {
+        if other.width.is_some() { self.width = other.width; }
+        if other.style.is_some() { self.style = other.style; }
+        if other.color.is_some() { self.color = other.color; }
     }
 
-    let border_widths = BorderWidths {
-        top: thickness,
-        left: thickness,
-        right: thickness,
-        bottom: thickness,
-    };
+    // replaces the parts that are set in `self` with the ones of `other`, if they are set there as well
+    fn replace_set_parts(&mut self, other: &StyleBorderSide) {
+        if self.width.is_some() && other.width.is_some() { self.width = other.width; }
+        if self.style.is_some() && other.style.is_some() { self.style = other.style; }
+        if self.color.is_some() && other.color.is_some() { self.color = other.color; }
+    }
 
-    let border_side = BorderSide {
-        color: color.into(),
-        style: style,
-    };
+    fn is_set(&self) -> bool {
+        *self != StyleBorderSide::default()
+    }
+
+    fn interpolate(&self, other: &StyleBorderSide, t: f32) -> Option<Self> {
+        let width = match (self.width, other.width) {
+            (Some(a), Some(b)) => Some(a.interpolate(&b, t)),
+            (None, None) => None,
+            _ => return None,
+        };
+        let color = match (self.color, other.color) {
+            (Some(a), Some(b)) => Some(interpolate_color_u(&a, &b, t)),
+            (None, None) => None,
+            _ => return None,
+        };
+        // border styles can't be interpolated
+        if self.style != other.style {
+            return None;
+        }
+        Some(StyleBorderSide { width, style: self.style, color })
+    }
+}
+
+/// Writes the parts of the side that are set, in the order `width style color`
+impl ToCssString for StyleBorderSide {
+    fn to_css_string(&self) -> String {
+        let mut values = Vec::new();
+        if let Some(width) = self.width { values.push(width.to_css_string()); }
+        if let Some(style) = self.style { values.push(style.to_css_string()); }
+        if let Some(color) = self.color { values.push(color.to_css_string()); }
+        values.join(" ")
+    }
+}
+
+impl StyleBorder {
+
+    pub fn uniform(side: StyleBorderSide) -> Self {
+        Self { top: side, right: side, bottom: side, left: side }
+    }
+
+    /// Creates a border from the values of the four sides (top, right, bottom, left)
+    fn from_sides<T: Copy, F: Fn(T) -> StyleBorderSide>(values: [T; 4], to_side: F) -> Self {
+        Self { top: to_side(values[0]), right: to_side(values[1]), bottom: to_side(values[2]), left: to_side(values[3]) }
+    }
+
+    fn sides(&self) -> [StyleBorderSide; 4] {
+        [self.top, self.right, self.bottom, self.left]
+    }
+
+    // "merges" two borders, the sides and parts of sides that are set in `other` win
+    pub fn merge(&mut self, other: &StyleBorder) {
+        self.top.merge(&other.top);
+        self.right.merge(&other.right);
+        self.bottom.merge(&other.bottom);
+        self.left.merge(&other.left);
+    }
+
+    // replaces the sides and parts of sides that are set in `self` with the ones of `other`, if they are set there as well
+    fn replace_set_parts(&mut self, other: &StyleBorder) {
+        self.top.replace_set_parts(&other.top);
+        self.right.replace_set_parts(&other.right);
+        self.bottom.replace_set_parts(&other.bottom);
+        self.left.replace_set_parts(&other.left);
+    }
+
+    /// Returns the widths of the sides in pixels. Sides with a style of `none` or `hidden`
+    /// (or without a style) have no width, like in CSS.
+    pub(crate) fn get_widths(&self, context: &LengthContext) -> BorderWidths {
+        let width = |side: StyleBorderSide| match side.style {
+            None | Some(BorderStyle::None) | Some(BorderStyle::Hidden) => 0.0,
+            Some(_) => side.width.and_then(|w| Some(w.resolve(context, 0.0))).unwrap_or(DEFAULT_BORDER_WIDTH),
+        };
+        BorderWidths { top: width(self.top), right: width(self.right), bottom: width(self.bottom), left: width(self.left) }
+    }
+
+    /// Returns the styles and colors of the sides as a webrender border, without a radius
+    pub(crate) fn get_normal_border(&self) -> NormalBorder {
+        let side = |side: StyleBorderSide| BorderSide {
+            color: side.color.unwrap_or(DEFAULT_BORDER_COLOR).into(),
+            style: side.style.unwrap_or(BorderStyle::None),
+        };
+        NormalBorder {
+            top: side(self.top),
+            right: side(self.right),
+            bottom: side(self.bottom),
+            left: side(self.left),
+            radius: BorderRadius::zero(),
+        }
+    }
+
+    /// Interpolates the widths and colors of the sides, returns `None` if the
+    /// border styles or the sides that are set differ
+    pub(crate) fn interpolate(&self, other: &StyleBorder, t: f32) -> Option<Self> {
+        Some(Self {
+            top: self.top.interpolate(&other.top, t)?,
+            right: self.right.interpolate(&other.right, t)?,
+            bottom: self.bottom.interpolate(&other.bottom, t)?,
+            left: self.left.interpolate(&other.left, t)?,
+        })
+    }
+
+    /// Returns the key of the longhand if only one side (`border-top`) or only one
+    /// part of all sides (`border-width`) is set, otherwise `"border"`
+    fn get_key(&self) -> &'static str {
+        let sides = self.sides();
+        match (self.top.is_set(), self.right.is_set(), self.bottom.is_set(), self.left.is_set()) {
+            (true, false, false, false) => BORDER_KEYS[1],
+            (false, true, false, false) => BORDER_KEYS[2],
+            (false, false, true, false) => BORDER_KEYS[3],
+            (false, false, false, true) => BORDER_KEYS[4],
+            _ if sides.iter().all(|side| side.style.is_none() && side.color.is_none()) => BORDER_KEYS[5],
+            _ if sides.iter().all(|side| side.width.is_none() && side.color.is_none()) => BORDER_KEYS[6],
+            _ if sides.iter().all(|side| side.width.is_none() && side.style.is_none()) => BORDER_KEYS[7],
+            _ => BORDER_KEYS[0],
+        }
+    }
+}
+
+/// Writes the value of the longhand if only one side is set, otherwise a single value if
+/// all sides are equal, or the four values of `border-width`, `-style` or `-color`
+impl ToCssString for StyleBorder {
+    fn to_css_string(&self) -> String {
+        let sides = self.sides();
+        let set_sides = sides.iter().filter(|side| side.is_set()).collect::<Vec<_>>();
+        if set_sides.len() == 1 || sides.iter().all(|side| *side == sides[0]) {
+            set_sides.first().map(|side| side.to_css_string()).unwrap_or_default()
+        } else {
+            sides.iter().map(|side| side.to_css_string()).collect::<Vec<String>>().join(" ")
+        }
+    }
+}
+
+/// Parses the `border` shorthand or one side of it (`border-top`), such as
+///
+/// "5px solid red", "solid" or "dashed #ccc"
+///
+/// The values can be in any order. Like in CSS, the values that aren't given are
+/// reset, to a width of 1px, the style `none` and black.
+fn parse_css_border<'a>(input: &'a str)
+-> Result<StyleBorderSide, CssBorderParseError<'a>>
+{
+    let (mut width, mut style, mut color) = (None, None, None);
+
+    for value in split_values(input) {
+        if let (None, Ok(s)) = (style, parse_border_style(value)) {
+            style = Some(s);
+        } else if let (None, Ok(w)) = (width, parse_pixel_value(value)) {
+            width = Some(w);
+        } else if let (None, Ok(c)) = (color, parse_css_color(value)) {
+            color = Some(c);
+        } else {
+            return Err(CssBorderParseError::InvalidBorderDeclaration(input));
+        }
+    }
 
-    let border_details = BorderDetails::Normal(NormalBorder {
-        top: border_side,
-        left: border_side,
-        right: border_side,
-        bottom: border_side,
-        radius: BorderRadius::zero(),
-    });
+    if width.is_none() && style.is_none() && color.is_none() {
+        return Err(CssBorderParseError::InvalidBorderDeclaration(input));
+    }
+
+    Ok(StyleBorderSide {
+        width: Some(width.unwrap_or(PixelValue::from_metric(CssMetric::Px, DEFAULT_BORDER_WIDTH))),
+        style: Some(style.unwrap_or(BorderStyle::None)),
+        color: Some(color.unwrap_or(DEFAULT_BORDER_COLOR)),
+    })
+}
+
+/// Parses the one to four values (top, right, bottom, left) of `border-width`,
+/// `border-style` or `border-color`, see `expand_sides`
+fn parse_border_sides<'a, T: Copy, F: Fn(&'a str) -> Result<T, CssBorderParseError<'a>>>(input: &'a str, parse_value: F)
+-> Result<[T; 4], CssBorderParseError<'a>>
+{
+    let values = split_values(input).into_iter()
+        .map(parse_value)
+        .collect::<Result<Vec<T>, CssBorderParseError<'a>>>()?;
+    expand_sides(&values).ok_or(CssBorderParseError::InvalidBorderDeclaration(input))
+}
+
+/// Parses `border-width`, i.e. "1px" or "1px 2px 1px 0px"
+fn parse_css_border_width<'a>(input: &'a str)
+-> Result<StyleBorder, CssBorderParseError<'a>>
+{
+    let widths = parse_border_sides(input, |value| parse_pixel_value(value)
+        .map_err(|e| CssBorderParseError::ThicknessParseError(e)))?;
+    Ok(StyleBorder::from_sides(widths, |width| StyleBorderSide { width: Some(width), .. Default::default() }))
+}
+
+/// Parses `border-style`, i.e. "solid" or "solid none"
+fn parse_css_border_style<'a>(input: &'a str)
+-> Result<StyleBorder, CssBorderParseError<'a>>
+{
+    let styles = parse_border_sides(input, |value| parse_border_style(value)
+        .map_err(|e| CssBorderParseError::InvalidBorderStyle(e)))?;
+    Ok(StyleBorder::from_sides(styles, |style| StyleBorderSide { style: Some(style), .. Default::default() }))
+}
 
-    Ok((border_widths, border_details))
+/// Parses `border-color`, i.e. "red" or "red #00ff00 blue"
+fn parse_css_border_color<'a>(input: &'a str)
+-> Result<StyleBorder, CssBorderParseError<'a>>
+{
+    let colors = parse_border_sides(input, |value| parse_css_color(value)
+        .map_err(|e| CssBorderParseError::ColorParseError(e)))?;
+    Ok(StyleBorder::from_sides(colors, |color| StyleBorderSide { color: Some(color), .. Default::default() }))
 }
 
 /// Parse a border style such as "none", "dotted", etc.
@@ -836,6 +2055,111 @@ multi_type_parser!(parse_border_style, BorderStyle,
     ["inset", Inset],
     ["outset", Outset]);
 
+/// `border-image`, i.e. `border-image: image("frame") 10 fill round`. The image is cut into
+/// nine parts by the slices: the corners are drawn into the corners of the border, the edges
+/// are stretched or repeated along the sides and the center is only drawn with `fill`.
+///
+/// The widths of the border come from the `border` properties, like in CSS.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyleBorderImage {
+    /// The ID of the image, see `App::add_image`
+    pub source: CssImageId,
+    /// The distances of the slices from the edges of the image, in pixels of the image
+    pub slice: SideOffsets2D<u32>,
+    pub fill: bool,
+    pub repeat_horizontal: RepeatMode,
+    pub repeat_vertical: RepeatMode,
+}
+
+impl ToCssString for StyleBorderImage {
+    fn to_css_string(&self) -> String {
+        let slices = [self.slice.top, self.slice.right, self.slice.bottom, self.slice.left];
+        let mut values = vec![Background::Image(self.source.clone()).to_css_string()];
+        if slices.iter().all(|slice| *slice == slices[0]) {
+            values.push(slices[0].to_string());
+        } else {
+            values.extend(slices.iter().map(|slice| slice.to_string()));
+        }
+        if self.fill {
+            values.push("fill".to_string());
+        }
+        values.push(self.repeat_horizontal.to_css_string());
+        if self.repeat_vertical != self.repeat_horizontal {
+            values.push(self.repeat_vertical.to_css_string());
+        }
+        values.join(" ")
+    }
+}
+
+impl ToCssString for Option<StyleBorderImage> {
+    fn to_css_string(&self) -> String {
+        match *self {
+            Some(ref border_image) => border_image.to_css_string(),
+            None => "none".to_string(),
+        }
+    }
+}
+
+/// Parses a `border-image`: the image, one to four slices (top, right, bottom, left, in pixels
+/// of the image, see `expand_sides`), optionally `fill` and one or two repeat modes (horizontal
+/// and vertical, `stretch` by default), such as
+///
+/// "image("frame") 10", "image("frame") 10 20 fill" or "image("frame") 10 round stretch"
+fn parse_css_border_image<'a>(input: &'a str)
+-> Result<Option<StyleBorderImage>, CssBorderImageParseError<'a>>
+{
+    if input == "none" {
+        return Ok(None);
+    }
+
+    let values = split_values(input);
+
+    let source = match values.first().and_then(|value| parse_css_background(value).ok()) {
+        Some(Background::Image(image_id)) => image_id,
+        _ => return Err(CssBorderImageParseError::InvalidSource(input)),
+    };
+
+    let mut values = values.into_iter().skip(1).peekable();
+
+    let mut slices = Vec::new();
+    while let Some(slice) = values.peek().and_then(|value| value.parse::<u32>().ok()) {
+        slices.push(slice);
+        values.next();
+    }
+    let slice = expand_sides(&slices).ok_or(CssBorderImageParseError::InvalidSlice(input))?;
+
+    let fill = values.peek() == Some(&"fill");
+    if fill {
+        values.next();
+    }
+
+    let repeats = values
+        .map(parse_border_image_repeat)
+        .collect::<Result<Vec<RepeatMode>, InvalidValueErr<'a>>>()?;
+    let (repeat_horizontal, repeat_vertical) = match repeats.len() {
+        0 => (RepeatMode::Stretch, RepeatMode::Stretch),
+        1 => (repeats[0], repeats[0]),
+        2 => (repeats[0], repeats[1]),
+        _ => return Err(CssBorderImageParseError::TooManyValues(input)),
+    };
+
+    Ok(Some(StyleBorderImage {
+        source: source,
+        slice: SideOffsets2D::new(slice[0], slice[1], slice[2], slice[3]),
+        fill: fill,
+        repeat_horizontal: repeat_horizontal,
+        repeat_vertical: repeat_vertical,
+    }))
+}
+
+// Parses how the edges of a `border-image` are drawn along the sides:
+// "stretch", "repeat", "round" or "space"
+multi_type_parser!(parse_border_image_repeat, RepeatMode,
+    ["stretch", Stretch],
+    ["repeat", Repeat],
+    ["round", Round],
+    ["space", Space]);
+
 // missing BorderRadius & LayoutRect
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct BoxShadowPreDisplayItem {
@@ -846,116 +2170,104 @@ pub struct BoxShadowPreDisplayItem {
     pub clip_mode: BoxShadowClipMode,
 }
 
-/// Parses a CSS box-shadow
-fn parse_css_box_shadow<'a>(input: &'a str)
--> Result<Option<BoxShadowPreDisplayItem>, CssShadowParseError<'a>>
-{
-    let mut input_iter = input.split_whitespace();
-    let count = input_iter.clone().count();
+impl ToCssString for BoxShadowPreDisplayItem {
+    fn to_css_string(&self) -> String {
+        let box_shadow = format!("{}px {}px {}px {}px {}",
+            self.offset.x, self.offset.y, self.blur_radius, self.spread_radius, self.color.to_css_string());
+        match self.clip_mode {
+            BoxShadowClipMode::Outset => box_shadow,
+            BoxShadowClipMode::Inset => format!("{} inset", box_shadow),
+        }
+    }
+}
 
-    let mut box_shadow = BoxShadowPreDisplayItem {
-        offset: LayoutVector2D::zero(),
-        color: ColorF { r: 0.0, g: 0.0, b: 0.0, a: 1.0 },
-        blur_radius: 0.0,
-        spread_radius: 0.0,
-        clip_mode: BoxShadowClipMode::Outset,
-    };
+impl ToCssString for Option<BoxShadowPreDisplayItem> {
+    fn to_css_string(&self) -> String {
+        match *self {
+            Some(ref box_shadow) => box_shadow.to_css_string(),
+            None => "none".to_string(),
+        }
+    }
+}
 
-    let last_val = input_iter.clone().rev().next();
-    let is_inset = last_val == Some("inset") || last_val == Some("outset");
+/// The `box-shadow` property, a comma-separated list of shadows, i.e.
+/// `box-shadow: 0px 1px 3px #0000001f, 0px 1px 2px #0000003d`. The first shadow is
+/// painted on top of the other shadows. An empty list is `box-shadow: none`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StyleBoxShadows(pub Vec<BoxShadowPreDisplayItem>);
 
-    if count > 2 && is_inset {
-        let l_val = last_val.unwrap();
-        if l_val == "outset" {
-            box_shadow.clip_mode = BoxShadowClipMode::Outset;
-        } else if l_val == "inset" {
-            box_shadow.clip_mode = BoxShadowClipMode::Inset;
+impl ToCssString for StyleBoxShadows {
+    fn to_css_string(&self) -> String {
+        if self.0.is_empty() {
+            return "none".to_string();
         }
+        self.0.iter().map(|shadow| shadow.to_css_string()).collect::<Vec<String>>().join(", ")
     }
+}
 
-    match count {
-        1 => {
-            // box-shadow: none;
-            match input_iter.next().unwrap() {
-                "none" => return Ok(None),
-                _ => return Err(CssShadowParseError::InvalidSingleStatement(input)),
-            }
-        },
-        2 => {
-            // box-shadow: 5px 10px; (h_offset, v_offset)
-            let h_offset = parse_pixel_value(input_iter.next().unwrap())?.to_pixels();
-            let v_offset = parse_pixel_value(input_iter.next().unwrap())?.to_pixels();
-            box_shadow.offset.x = h_offset;
-            box_shadow.offset.y = v_offset;
-        },
-        3 => {
-            // box-shadow: 5px 10px inset; (h_offset, v_offset, inset)
-            let h_offset = parse_pixel_value(input_iter.next().unwrap())?.to_pixels();
-            let v_offset = parse_pixel_value(input_iter.next().unwrap())?.to_pixels();
-            box_shadow.offset.x = h_offset;
-            box_shadow.offset.y = v_offset;
-
-            if !is_inset {
-                // box-shadow: 5px 10px #888888; (h_offset, v_offset, color)
-                let color = parse_css_color(input_iter.next().unwrap())?;
-                box_shadow.color = ColorF::from(color);
-            }
-        },
-        4 => {
-            let h_offset = parse_pixel_value(input_iter.next().unwrap())?.to_pixels();
-            let v_offset = parse_pixel_value(input_iter.next().unwrap())?.to_pixels();
-            box_shadow.offset.x = h_offset;
-            box_shadow.offset.y = v_offset;
-
-            if !is_inset {
-                let blur = parse_pixel_value(input_iter.next().unwrap())?.to_pixels();
-                box_shadow.blur_radius = blur.into();
-            }
-
-            let color = parse_css_color(input_iter.next().unwrap())?;
-            box_shadow.color = ColorF::from(color);
-        },
-        5 => {
-            // box-shadow: 5px 10px 5px 10px #888888; (h_offset, v_offset, blur, spread, color)
-            // box-shadow: 5px 10px 5px #888888 inset; (h_offset, v_offset, blur, color, inset)
-            let h_offset = parse_pixel_value(input_iter.next().unwrap())?.to_pixels();
-            let v_offset = parse_pixel_value(input_iter.next().unwrap())?.to_pixels();
-            box_shadow.offset.x = h_offset;
-            box_shadow.offset.y = v_offset;
-
-            let blur = parse_pixel_value(input_iter.next().unwrap())?.to_pixels();
-            box_shadow.blur_radius = blur.into();
-
-            if !is_inset {
-                let spread = parse_pixel_value(input_iter.next().unwrap())?.to_pixels();
-                box_shadow.spread_radius = spread.into();
-            }
+/// Parses the comma-separated shadows of a `box-shadow`, i.e.
+/// "0px 3px 1px -2px #00000033, 0px 2px 2px #00000024 inset"
+fn parse_css_box_shadows<'a>(input: &'a str)
+-> Result<StyleBoxShadows, CssShadowParseError<'a>>
+{
+    if input == "none" {
+        return Ok(StyleBoxShadows::default());
+    }
 
-            let color = parse_css_color(input_iter.next().unwrap())?;
-            box_shadow.color = ColorF::from(color);
-        },
-        6 => {
-            // box-shadow: 5px 10px 5px 10px #888888 inset; (h_offset, v_offset, blur, spread, color, inset)
-            let h_offset = parse_pixel_value(input_iter.next().unwrap())?.to_pixels();
-            let v_offset = parse_pixel_value(input_iter.next().unwrap())?.to_pixels();
-            box_shadow.offset.x = h_offset;
-            box_shadow.offset.y = v_offset;
+    split_unnested(input, |c| c == ',').into_iter()
+        .map(|shadow| match parse_css_box_shadow(shadow.trim())? {
+            Some(shadow) => Ok(shadow),
+            // `none` can't be part of a list of shadows
+            None => Err(CssShadowParseError::InvalidSingleStatement(input)),
+        })
+        .collect::<Result<Vec<BoxShadowPreDisplayItem>, _>>()
+        .and_then(|shadows| Ok(StyleBoxShadows(shadows)))
+}
 
-            let blur = parse_pixel_value(input_iter.next().unwrap())?.to_pixels();
-            box_shadow.blur_radius = blur.into();
+/// Parses a single shadow such as
+///
+/// "5px 10px", "5px 10px 5px -2px #888888" or "inset 0px 1px 3px red"
+///
+/// The two to four lengths are the horizontal and vertical offset, the blur radius and the
+/// spread radius. The color (black by default) and `inset` / `outset` can be before or after
+/// the lengths. Returns `None` for `none`.
+fn parse_css_box_shadow<'a>(input: &'a str)
+-> Result<Option<BoxShadowPreDisplayItem>, CssShadowParseError<'a>>
+{
+    let values = split_values(input);
 
-            let spread = parse_pixel_value(input_iter.next().unwrap())?.to_pixels();
-            box_shadow.spread_radius = spread.into();
+    if values.len() == 1 && values[0] == "none" {
+        return Ok(None);
+    }
 
-            let color = parse_css_color(input_iter.next().unwrap())?;
-            box_shadow.color = ColorF::from(color);
-        }
-        _ => {
-            return Err(CssShadowParseError::TooManyComponents(input));
+    let mut lengths = Vec::new();
+    let mut color = None;
+    let mut clip_mode = None;
+
+    for value in values {
+        match value {
+            "inset" if clip_mode.is_none() => clip_mode = Some(BoxShadowClipMode::Inset),
+            "outset" if clip_mode.is_none() => clip_mode = Some(BoxShadowClipMode::Outset),
+            _ => match parse_pixel_value(value) {
+                Ok(length) if lengths.len() < 4 => lengths.push(length.to_pixels()),
+                Ok(_) => return Err(CssShadowParseError::TooManyComponents(input)),
+                Err(_) if color.is_none() => color = Some(parse_css_color(value)?),
+                Err(_) => return Err(CssShadowParseError::TooManyComponents(input)),
+            },
         }
     }
 
-    Ok(Some(box_shadow))
+    if lengths.len() < 2 {
+        return Err(CssShadowParseError::InvalidSingleStatement(input));
+    }
+
+    Ok(Some(BoxShadowPreDisplayItem {
+        offset: LayoutVector2D::new(lengths[0], lengths[1]),
+        color: color.unwrap_or(ColorU { r: 0, g: 0, b: 0, a: 255 }).into(),
+        blur_radius: lengths.get(2).cloned().unwrap_or(0.0),
+        spread_radius: lengths.get(3).cloned().unwrap_or(0.0),
+        clip_mode: clip_mode.unwrap_or(BoxShadowClipMode::Outset),
+    }))
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -969,6 +2281,12 @@ pub enum CssBackgroundParseError<'a> {
     GradientParseError(CssGradientStopParseError<'a>),
     ShapeParseError(CssShapeParseError<'a>),
     ImageParseError(CssImageParseError<'a>),
+    /// A layer of `background-size` that isn't `cover`, `contain` or one or two lengths
+    InvalidBackgroundSize(&'a str),
+    /// A layer of `background-position` that isn't one or two lengths or keywords
+    InvalidBackgroundPosition(&'a str),
+    /// A layer of `background-repeat` that isn't `repeat`, `repeat-x`, `repeat-y` or `no-repeat`
+    InvalidBackgroundRepeat(&'a str),
 }
 
 impl_from!(CssDirectionParseError, CssBackgroundParseError::DirectionParseError);
@@ -983,12 +2301,138 @@ pub enum Background {
     Image(CssImageId)
 }
 
+impl ToCssString for Background {
+    fn to_css_string(&self) -> String {
+        fn repeating(extend_mode: ExtendMode) -> &'static str {
+            match extend_mode {
+                ExtendMode::Clamp => "",
+                ExtendMode::Repeat => "repeating-",
+            }
+        }
+
+        fn stops_to_css_string(stops: &[GradientStopPre]) -> String {
+            stops.iter().map(|stop| stop.to_css_string()).collect::<Vec<String>>().join(", ")
+        }
+
+        match *self {
+            Background::LinearGradient(ref gradient) => format!("{}linear-gradient({}, {})",
+                repeating(gradient.extend_mode), gradient.direction.to_css_string(), stops_to_css_string(&gradient.stops)),
+            Background::RadialGradient(ref gradient) => format!("{}radial-gradient({} {} at {}, {})",
+                repeating(gradient.extend_mode), gradient.shape.to_css_string(), gradient.size.to_css_string(),
+                gradient.position.to_css_string(), stops_to_css_string(&gradient.stops)),
+            Background::Image(ref image_id) => format!("image(\"{}\")", image_id.0),
+        }
+    }
+}
+
 impl<'a> From<CssImageId> for Background {
     fn from(id: CssImageId) -> Self {
         Background::Image(id)
     }
 }
 
+/// The `background` property, a comma-separated list of layers, i.e.
+/// `background: image("logo"), linear-gradient(red, blue)`. The first layer is
+/// painted on top of the other layers. An empty list is `background: none`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StyleBackgrounds(pub Vec<Background>);
+
+impl ToCssString for StyleBackgrounds {
+    fn to_css_string(&self) -> String {
+        if self.0.is_empty() {
+            return "none".to_string();
+        }
+        self.0.iter().map(|layer| layer.to_css_string()).collect::<Vec<String>>().join(", ")
+    }
+}
+
+/// The size of the tiles of a background layer
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BackgroundSize {
+    /// Scales the tile (keeping its aspect ratio) so that it covers the whole node
+    Cover,
+    /// Scales the tile (keeping its aspect ratio) so that it fits into the node
+    Contain,
+    /// Width and height of the tile, `%` is relative to the size of the node. `None` is
+    /// `auto`: images keep their aspect ratio, gradients are as large as the node
+    Exact(Option<PixelValue>, Option<PixelValue>),
+}
+
+impl Default for BackgroundSize {
+    fn default() -> Self {
+        BackgroundSize::Exact(None, None)
+    }
+}
+
+impl ToCssString for BackgroundSize {
+    fn to_css_string(&self) -> String {
+        let length = |length: Option<PixelValue>| length.map(|l| l.to_css_string()).unwrap_or("auto".to_string());
+        match *self {
+            BackgroundSize::Cover => "cover".to_string(),
+            BackgroundSize::Contain => "contain".to_string(),
+            BackgroundSize::Exact(width, height) => format!("{} {}", length(width), length(height)),
+        }
+    }
+}
+
+/// The position of the tiles of a background layer. `%` is relative to the size of
+/// the node minus the size of the tile, so that `100% 100%` aligns the tile with
+/// the bottom right corner of the node.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BackgroundPosition {
+    pub horizontal: PixelValue,
+    pub vertical: PixelValue,
+}
+
+impl Default for BackgroundPosition {
+    fn default() -> Self {
+        Self { horizontal: PixelValue::zero(), vertical: PixelValue::zero() }
+    }
+}
+
+impl BackgroundPosition {
+    /// `center`, i.e. `50% 50%`
+    pub(crate) fn center() -> Self {
+        let half = PixelValue::from_metric(CssMetric::Percent, 50.0);
+        Self { horizontal: half, vertical: half }
+    }
+}
+
+impl ToCssString for BackgroundPosition {
+    fn to_css_string(&self) -> String {
+        format!("{} {}", self.horizontal.to_css_string(), self.vertical.to_css_string())
+    }
+}
+
+/// Whether the tiles of a background layer are repeated in horizontal and / or vertical direction
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BackgroundRepeat {
+    Repeat,
+    RepeatX,
+    RepeatY,
+    NoRepeat,
+}
+
+impl Default for BackgroundRepeat {
+    fn default() -> Self {
+        BackgroundRepeat::Repeat
+    }
+}
+
+/// The `background-size` property, one size per background layer
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StyleBackgroundSizes(pub Vec<BackgroundSize>);
+
+/// The `background-position` property, one position per background layer
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StyleBackgroundPositions(pub Vec<BackgroundPosition>);
+
+/// The `background-repeat` property, one value per background layer
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StyleBackgroundRepeats(pub Vec<BackgroundRepeat>);
+
+impl_to_css_string_comma_list!(StyleBackgroundSizes, StyleBackgroundPositions, StyleBackgroundRepeats);
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct LinearGradientPreInfo {
     pub direction: Direction,
@@ -999,10 +2443,93 @@ pub struct LinearGradientPreInfo {
 #[derive(Debug, Clone, PartialEq)]
 pub struct RadialGradientPreInfo {
     pub shape: Shape,
+    pub size: RadialGradientSize,
+    /// The center of the gradient, `%` is relative to the size of the node
+    pub position: BackgroundPosition,
     pub extend_mode: ExtendMode,
     pub stops: Vec<GradientStopPre>,
 }
 
+impl RadialGradientPreInfo {
+    /// Calculates the center and the horizontal / vertical radius of the gradient, relative
+    /// to the top left corner of the (tile of the) node with the given size
+    pub(crate) fn get_center_and_radii(&self, size: LayoutSize, context: &LengthContext) -> (LayoutPoint, LayoutSize) {
+        use std::f32::consts::SQRT_2;
+        use self::RadialGradientSize::*;
+
+        let center = LayoutPoint::new(
+            self.position.horizontal.resolve(context, size.width),
+            self.position.vertical.resolve(context, size.height));
+
+        // distances from the center to the closest and farthest side on each axis
+        let (left, right) = (center.x.abs(), (size.width - center.x).abs());
+        let (top, bottom) = (center.y.abs(), (size.height - center.y).abs());
+        let (closest_x, closest_y) = (left.min(right), top.min(bottom));
+        let (farthest_x, farthest_y) = (left.max(right), top.max(bottom));
+
+        let radii = match (self.size, self.shape) {
+            (ClosestSide, Shape::Circle) => { let r = closest_x.min(closest_y); LayoutSize::new(r, r) },
+            (FarthestSide, Shape::Circle) => { let r = farthest_x.max(farthest_y); LayoutSize::new(r, r) },
+            (ClosestCorner, Shape::Circle) => { let r = closest_x.hypot(closest_y); LayoutSize::new(r, r) },
+            (FarthestCorner, Shape::Circle) => { let r = farthest_x.hypot(farthest_y); LayoutSize::new(r, r) },
+            (ClosestSide, Shape::Ellipse) => LayoutSize::new(closest_x, closest_y),
+            (FarthestSide, Shape::Ellipse) => LayoutSize::new(farthest_x, farthest_y),
+            // an ellipse with the aspect ratio of the `-side` ellipse that goes through the corner
+            (ClosestCorner, Shape::Ellipse) => LayoutSize::new(closest_x * SQRT_2, closest_y * SQRT_2),
+            (FarthestCorner, Shape::Ellipse) => LayoutSize::new(farthest_x * SQRT_2, farthest_y * SQRT_2),
+            (Exact(x, y), _) => LayoutSize::new(x.resolve(context, size.width), y.resolve(context, size.height)),
+        };
+
+        (center, radii)
+    }
+}
+
+/// The size of the ending shape of a radial gradient
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RadialGradientSize {
+    ClosestSide,
+    ClosestCorner,
+    FarthestSide,
+    /// Default value
+    FarthestCorner,
+    /// The horizontal and vertical radius (both are the same for a circle),
+    /// `%` is relative to the size of the node
+    Exact(PixelValue, PixelValue),
+}
+
+impl Default for RadialGradientSize {
+    fn default() -> Self {
+        RadialGradientSize::FarthestCorner
+    }
+}
+
+impl ToCssString for RadialGradientSize {
+    fn to_css_string(&self) -> String {
+        use self::RadialGradientSize::*;
+        match *self {
+            ClosestSide => "closest-side".to_string(),
+            ClosestCorner => "closest-corner".to_string(),
+            FarthestSide => "farthest-side".to_string(),
+            FarthestCorner => "farthest-corner".to_string(),
+            Exact(x, y) if x == y => x.to_css_string(),
+            Exact(x, y) => format!("{} {}", x.to_css_string(), y.to_css_string()),
+        }
+    }
+}
+
+fn parse_radial_gradient_size_keyword<'a>(input: &'a str)
+-> Result<RadialGradientSize, InvalidValueErr<'a>>
+{
+    use self::RadialGradientSize::*;
+    match input {
+        "closest-side" => Ok(ClosestSide),
+        "closest-corner" => Ok(ClosestCorner),
+        "farthest-side" => Ok(FarthestSide),
+        "farthest-corner" => Ok(FarthestCorner),
+        _ => Err(InvalidValueErr(input)),
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Direction {
     Angle(f32),
@@ -1016,10 +2543,13 @@ impl Direction {
     {
         match *self {
             Direction::Angle(ref deg) => {
-                // todo!!
-                let mut point: LayoutPoint = TypedPoint2D::new(rect.size.width, rect.size.height);
-                let rot = TypedRotation2D::new(Angle::radians(deg.to_radians()));
-                (LayoutPoint::zero(), rot.transform_point(&point))
+                // `0deg` points up, `90deg` to the right. The gradient line goes through the
+                // center and is long enough that the corners get the color of the first / last stop
+                let (sin, cos) = deg.to_radians().sin_cos();
+                let length = (rect.size.width * sin).abs() + (rect.size.height * cos).abs();
+                let center = LayoutPoint::new(rect.size.width / 2.0, rect.size.height / 2.0);
+                let half_line = LayoutVector2D::new(sin * length / 2.0, -cos * length / 2.0);
+                (center - half_line, center + half_line)
             },
             Direction::FromTo(ref from, ref to) => {
                 (from.to_point(rect), to.to_point(rect))
@@ -1028,6 +2558,17 @@ impl Direction {
     }
 }
 
+/// Writes the direction as an angle (`50deg`) or as the corner
+/// that the gradient goes to (`to top right`)
+impl ToCssString for Direction {
+    fn to_css_string(&self) -> String {
+        match *self {
+            Direction::Angle(deg) => format!("{}deg", deg),
+            Direction::FromTo(_, to) => format!("to {}", to.to_css_string()),
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Shape {
     Ellipse,
@@ -1046,6 +2587,22 @@ pub enum DirectionCorner {
     BottomLeft,
 }
 
+impl ToCssString for DirectionCorner {
+    fn to_css_string(&self) -> String {
+        use self::DirectionCorner::*;
+        match *self {
+            Right => "right",
+            Left => "left",
+            Top => "top",
+            Bottom => "bottom",
+            TopRight => "top right",
+            TopLeft => "top left",
+            BottomRight => "bottom right",
+            BottomLeft => "bottom left",
+        }.to_string()
+    }
+}
+
 impl DirectionCorner {
 
     pub fn opposite(&self) -> Self {
@@ -1137,147 +2694,176 @@ fn parse_css_background<'a>(input: &'a str)
         return Ok(image.into());
     }
 
-    let mut brace_iterator = brace_contents.split(',');
-
-    let mut gradient_stop_count = brace_iterator.clone().count();
+    let mut arguments = split_unnested(brace_contents, |c| c == ',').into_iter().map(|argument| argument.trim()).peekable();
+    // "50deg", "to right bottom", "circle at center", etc.
+    let first_argument = *arguments.peek().ok_or(CssBackgroundParseError::NoDirection(input))?;
 
-    // "50deg", "to right bottom", etc.
-    let first_brace_item = match brace_iterator.next() {
-        Some(s) => s,
-        None => return Err(CssBackgroundParseError::NoDirection(input)),
+    let is_linear_gradient = background_type == LinearGradient || background_type == RepeatingLinearGradient;
+    let extend_mode = match background_type {
+        RepeatingLinearGradient | RepeatingRadialGradient => ExtendMode::Repeat,
+        _ => ExtendMode::Clamp,
     };
 
-    // default shape: ellipse
-    let mut shape = Shape::Ellipse;
     // default gradient: from top to bottom
     let mut direction = Direction::FromTo(DirectionCorner::Top, DirectionCorner::Bottom);
+    // default shape: an ellipse around the center that touches the farthest corner
+    let mut shape = Shape::Ellipse;
+    let mut size = RadialGradientSize::default();
+    let mut position = BackgroundPosition::center();
 
-    let mut first_is_direction = false;
-    let mut first_is_shape = false;
-    let is_linear_gradient = background_type == LinearGradient || background_type == RepeatingLinearGradient;
-    let is_radial_gradient = background_type == RadialGradient || background_type == RepeatingRadialGradient;
-
+    // The first argument is either the direction / geometry or the first stop of the gradient
     if is_linear_gradient {
-        if let Ok(dir) = parse_direction(first_brace_item) {
+        if let Ok(dir) = parse_direction(first_argument) {
             direction = dir;
-            first_is_direction = true;
+            arguments.next();
         }
+    } else if let Ok((sh, sz, pos)) = parse_radial_gradient_geometry(first_argument) {
+        shape = sh;
+        size = sz;
+        position = pos;
+        arguments.next();
     }
 
-    if is_radial_gradient {
-        if let Ok(sh) = parse_shape(first_brace_item) {
-            shape = sh;
-            first_is_shape = true;
-        }
+    let stops = arguments.map(parse_gradient_stop).collect::<Result<Vec<GradientStopPre>, _>>()?;
+    if stops.len() < 2 {
+        return Err(CssBackgroundParseError::TooFewGradientStops(input));
     }
 
-    let mut first_item_doesnt_count = false;
-    if (is_linear_gradient && first_is_direction) || (is_radial_gradient && first_is_shape) {
-        gradient_stop_count -= 1; // first item is not a gradient stop
-        first_item_doesnt_count = true;
+    if is_linear_gradient {
+        Ok(Background::LinearGradient(LinearGradientPreInfo { direction, extend_mode, stops }))
+    } else {
+        Ok(Background::RadialGradient(RadialGradientPreInfo { shape, size, position, extend_mode, stops }))
     }
+}
 
-    if gradient_stop_count < 2 {
-        return Err(CssBackgroundParseError::TooFewGradientStops(input));
-    }
+// parses "circle", "ellipse closest-side at 30% 20%", "20px at left top"
+fn parse_radial_gradient_geometry<'a>(input: &'a str)
+-> Result<(Shape, RadialGradientSize, BackgroundPosition), CssShapeParseError<'a>>
+{
+    let values = split_values(input);
+    let (geometry, position) = match values.iter().position(|value| *value == "at") {
+        Some(at) => (&values[..at], parse_position(&values[(at + 1)..]).ok_or(CssShapeParseError::InvalidPosition(input))?),
+        None => (&values[..], BackgroundPosition::center()),
+    };
 
-    let mut color_stops = Vec::<GradientStopPre>::with_capacity(gradient_stop_count);
-    if !first_item_doesnt_count {
-        color_stops.push(parse_gradient_stop(first_brace_item)?);
-    }
+    let mut shape = None;
+    let mut size_keyword = None;
+    let mut lengths = Vec::new();
 
-    for stop in brace_iterator {
-        color_stops.push(parse_gradient_stop(stop)?);
+    for value in geometry {
+        if let Ok(sh) = parse_shape(value) {
+            if shape.is_some() {
+                return Err(CssShapeParseError::ShapeErr(InvalidValueErr(value)));
+            }
+            shape = Some(sh);
+        } else if let Ok(keyword) = parse_radial_gradient_size_keyword(value) {
+            if size_keyword.is_some() {
+                return Err(CssShapeParseError::InvalidSize(input));
+            }
+            size_keyword = Some(keyword);
+        } else {
+            lengths.push(parse_pixel_value(value).map_err(|_| CssShapeParseError::InvalidSize(input))?);
+        }
     }
 
-    // correct percentages
-    let mut last_stop = 0.0_f32;
-    let mut increase_stop_cnt: Option<f32> = None;
-
-    let color_stop_len = color_stops.len();
-    'outer: for i in 0..color_stop_len {
-        let offset = color_stops[i].offset;
-        match offset {
-            Some(s) => {
-                last_stop = s;
-                increase_stop_cnt = None;
-            },
-            None => {
-                let (_, next) = color_stops.split_at_mut(i);
+    // `radial-gradient(10px, ...)` is a circle, `radial-gradient(10px 20px, ...)` an ellipse
+    let shape = shape.unwrap_or(if lengths.len() == 1 { Shape::Circle } else { Shape::Ellipse });
 
-                if let Some(increase_stop_cnt) = increase_stop_cnt {
-                    last_stop += increase_stop_cnt;
-                    next[0].offset = Some(last_stop);
-                    continue 'outer;
-                }
+    let size = match (size_keyword, shape, lengths.len()) {
+        (Some(keyword), _, 0) => keyword,
+        (None, _, 0) => RadialGradientSize::default(),
+        (None, Shape::Circle, 1) => RadialGradientSize::Exact(lengths[0], lengths[0]),
+        (None, Shape::Ellipse, 2) => RadialGradientSize::Exact(lengths[0], lengths[1]),
+        _ => return Err(CssShapeParseError::InvalidSize(input)),
+    };
 
-                let mut next_count: u32 = 0;
-                let mut next_value = None;
-
-                // iterate until we find a value where the offset isn't none
-                {
-                    let mut next_iter = next.iter();
-                    next_iter.next();
-                    'inner: for next_stop in next_iter {
-                        if let Some(off) = next_stop.offset {
-                            next_value = Some(off);
-                            break 'inner;
-                        } else {
-                            next_count += 1;
-                        }
-                    }
-                }
+    Ok((shape, size, position))
+}
 
-                let next_value = next_value.unwrap_or(1.0_f32);
-                let increase = (next_value - last_stop) / (next_count as f32);
-                increase_stop_cnt = Some(increase);
-                if next_count == 1 && (color_stop_len - i) == 1 {
-                    next[0].offset = Some(last_stop);
-                } else {
-                    if i == 0 {
-                        next[0].offset = Some(0.0);
-                    } else {
-                        next[0].offset = Some(last_stop);
-                        // last_stop += increase;
-                    }
-                }
-            }
-        }
+/// Parses the comma-separated layers of a `background`, i.e. `image("logo"), linear-gradient(red, blue)`
+fn parse_css_backgrounds<'a>(input: &'a str)
+-> Result<StyleBackgrounds, CssBackgroundParseError<'a>>
+{
+    if input == "none" {
+        return Ok(StyleBackgrounds::default());
     }
 
-    match background_type {
-        LinearGradient => {
-            Ok(Background::LinearGradient(LinearGradientPreInfo {
-                direction: direction,
-                extend_mode: ExtendMode::Clamp,
-                stops: color_stops,
-            }))
-        },
-        RepeatingLinearGradient => {
-            Ok(Background::LinearGradient(LinearGradientPreInfo {
-                direction: direction,
-                extend_mode: ExtendMode::Repeat,
-                stops: color_stops,
-            }))
-        },
-        RadialGradient => {
-            Ok(Background::RadialGradient(RadialGradientPreInfo {
-                shape: shape,
-                extend_mode: ExtendMode::Clamp,
-                stops: color_stops,
-            }))
-        },
-        RepeatingRadialGradient => {
-            Ok(Background::RadialGradient(RadialGradientPreInfo {
-                shape: shape,
-                extend_mode: ExtendMode::Repeat,
-                stops: color_stops,
-            }))
+    split_unnested(input, |c| c == ',').into_iter()
+        .map(|layer| parse_css_background(layer.trim()))
+        .collect::<Result<Vec<Background>, _>>()
+        .and_then(|layers| Ok(StyleBackgrounds(layers)))
+}
+
+// parses a "cover, 50% auto, 10px 20px"
+fn parse_css_background_size<'a>(input: &'a str)
+-> Result<StyleBackgroundSizes, CssBackgroundParseError<'a>>
+{
+    split_unnested(input, |c| c == ',').into_iter().map(|layer| {
+        let layer = layer.trim();
+        let length = |value: &'a str| parse_layout_offset(value).map_err(|_| CssBackgroundParseError::InvalidBackgroundSize(layer));
+        let values = split_values(layer);
+        match (layer, values.len()) {
+            ("cover", _) => Ok(BackgroundSize::Cover),
+            ("contain", _) => Ok(BackgroundSize::Contain),
+            // `background-size: 50%` is `50% auto`
+            (_, 1) => Ok(BackgroundSize::Exact(length(values[0])?, None)),
+            (_, 2) => Ok(BackgroundSize::Exact(length(values[0])?, length(values[1])?)),
+            _ => Err(CssBackgroundParseError::InvalidBackgroundSize(layer)),
+        }
+    }).collect::<Result<Vec<BackgroundSize>, _>>().and_then(|sizes| Ok(StyleBackgroundSizes(sizes)))
+}
+
+// parses a "center, right 10px, 25% 75%"
+fn parse_css_background_position<'a>(input: &'a str)
+-> Result<StyleBackgroundPositions, CssBackgroundParseError<'a>>
+{
+    split_unnested(input, |c| c == ',').into_iter().map(|layer| {
+        let layer = layer.trim();
+        parse_position(&split_values(layer)).ok_or(CssBackgroundParseError::InvalidBackgroundPosition(layer))
+    }).collect::<Result<Vec<BackgroundPosition>, _>>().and_then(|positions| Ok(StyleBackgroundPositions(positions)))
+}
+
+/// Parses one or two lengths or keywords of a `background-position` or of the
+/// center of a radial gradient, i.e. `["right", "10px"]` or `["center"]`
+fn parse_position(values: &[&str]) -> Option<BackgroundPosition> {
+    // Parses a length or one of the keywords, i.e. `left`, `center` or `right` for the horizontal position
+    fn parse_component(input: &str, start: &str, end: &str) -> Option<PixelValue> {
+        match input {
+            "center" => Some(PixelValue::from_metric(CssMetric::Percent, 50.0)),
+            s if s == start => Some(PixelValue::from_metric(CssMetric::Percent, 0.0)),
+            s if s == end => Some(PixelValue::from_metric(CssMetric::Percent, 100.0)),
+            _ => parse_pixel_value(input).ok(),
+        }
+    }
+
+    let parse_components = |horizontal: &str, vertical: &str| {
+        let horizontal = parse_component(horizontal, "left", "right")?;
+        let vertical = parse_component(vertical, "top", "bottom")?;
+        Some(BackgroundPosition { horizontal, vertical })
+    };
+
+    match values.len() {
+        // A single value is centered on the other axis
+        1 => match values[0] {
+            "top" | "bottom" => parse_components("center", values[0]),
+            _ => parse_components(values[0], "center"),
         },
-        Image => unreachable!(),
+        // The keywords can be swapped, i.e. `top left` is the same as `left top`
+        2 => parse_components(values[0], values[1]).or_else(|| parse_components(values[1], values[0])),
+        _ => None,
     }
 }
 
+// parses a "no-repeat, repeat-x"
+fn parse_css_background_repeat<'a>(input: &'a str)
+-> Result<StyleBackgroundRepeats, CssBackgroundParseError<'a>>
+{
+    split_unnested(input, |c| c == ',').into_iter().map(|layer| {
+        let layer = layer.trim();
+        parse_background_repeat(layer).map_err(|_| CssBackgroundParseError::InvalidBackgroundRepeat(layer))
+    }).collect::<Result<Vec<BackgroundRepeat>, _>>().and_then(|repeats| Ok(StyleBackgroundRepeats(repeats)))
+}
+
 /// Note: In theory, we could take a String here,
 /// but this leads to horrible lifetime issues. Also
 /// since we only parse the CSS once (at startup),
@@ -1341,40 +2927,86 @@ fn strip_quotes<'a>(input: &'a str) -> Result<QuoteStripped<'a>, UnclosedQuotesE
 pub enum CssGradientStopParseError<'a> {
     Error(&'a str),
     ColorParseError(CssColorParseError<'a>),
+    PixelParseError(PixelParseError<'a>),
 }
 
+impl_from!(PixelParseError, CssGradientStopParseError::PixelParseError);
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct GradientStopPre {
-    pub offset: Option<f32>, // this is set to None if there was no offset that could be parsed
+    /// The position of the stop on the gradient line, `%` is relative to the length of the
+    /// gradient line. `None` if the stop has no position, see `resolve_gradient_stops`
+    pub offset: Option<PixelValue>,
     pub color: ColorF,
 }
 
-// parses "red" , "red 5%"
+impl ToCssString for GradientStopPre {
+    fn to_css_string(&self) -> String {
+        match self.offset {
+            Some(offset) => format!("{} {}", self.color.to_css_string(), offset.to_css_string()),
+            None => self.color.to_css_string(),
+        }
+    }
+}
+
+// parses "red" , "red 5%", "red 10px"
 fn parse_gradient_stop<'a>(input: &'a str)
 -> Result<GradientStopPre, CssGradientStopParseError<'a>>
 {
-    let mut input_iter = input.split_whitespace();
-    let first_item = input_iter.next().ok_or(CssGradientStopParseError::Error(input))?;
+    let values = split_values(input);
+    let first_item = values.get(0).ok_or(CssGradientStopParseError::Error(input))?;
     let color = ColorF::from(parse_css_color(first_item).map_err(|e| CssGradientStopParseError::ColorParseError(e))?);
-    let second_item = match input_iter.next() {
-        None => return Ok(GradientStopPre { offset: None, color: color }),
-        Some(s) => s,
-    };
-    let percentage = parse_percentage(second_item);
-    Ok(GradientStopPre { offset: percentage, color: color })
+    match values.len() {
+        1 => Ok(GradientStopPre { offset: None, color: color }),
+        2 => Ok(GradientStopPre { offset: Some(parse_pixel_value(values[1])?), color: color }),
+        _ => Err(CssGradientStopParseError::Error(input)),
+    }
 }
 
-// parses "5%" -> 5
-fn parse_percentage(input: &str)
--> Option<f32>
-{
-    let mut input_iter = input.rsplitn(2, '%');
-    let perc = input_iter.next();
-    if perc.is_none() {
-        None
-    } else {
-        input_iter.next()?.parse::<f32>().ok()
+/// Converts the positions of the stops to offsets on the gradient line (`0.0` is the start,
+/// `1.0` the end of the line), the `length` of the gradient line is needed to resolve `px`:
+///
+/// - The first stop is at `0%` and the last stop at `100%` if they don't have a position
+/// - A stop can't be in front of the stops before it, i.e. `red 50%, blue 10%` is `red 50%, blue 50%`
+/// - Stops without a position are spread evenly between the stops around them
+pub(crate) fn resolve_gradient_stops(stops: &[GradientStopPre], length: f32, context: &LengthContext) -> Vec<GradientStop> {
+    let last_idx = stops.len().saturating_sub(1);
+    let mut offsets = stops.iter().enumerate().map(|(idx, stop)| match stop.offset {
+        Some(offset) if length > 0.0 => Some(offset.resolve(context, length) / length),
+        Some(offset) => Some(offset.get(CssMetric::Percent) / 100.0),
+        None if idx == 0 => Some(0.0),
+        None if idx == last_idx => Some(1.0),
+        None => None,
+    }).collect::<Vec<Option<f32>>>();
+
+    let mut max_offset = ::std::f32::MIN;
+    for offset in offsets.iter_mut() {
+        if let Some(ref mut offset) = *offset {
+            *offset = offset.max(max_offset);
+            max_offset = *offset;
+        }
+    }
+
+    let mut idx = 0;
+    while idx < offsets.len() {
+        if offsets[idx].is_some() {
+            idx += 1;
+            continue;
+        }
+        // the first and last offset are always set, so the stop has a neighbour on both sides
+        let start = offsets[idx - 1].unwrap_or(0.0);
+        let next_idx = (idx..offsets.len()).find(|i| offsets[*i].is_some()).unwrap_or(last_idx);
+        let end = offsets[next_idx].unwrap_or(start);
+        let step = (end - start) / (next_idx - idx + 1) as f32;
+        for (n, offset) in offsets[idx..next_idx].iter_mut().enumerate() {
+            *offset = Some(start + step * (n + 1) as f32);
+        }
+        idx = next_idx;
     }
+
+    stops.iter().zip(offsets.into_iter()).map(|(stop, offset)| {
+        GradientStop { offset: offset.unwrap_or(0.0), color: stop.color }
+    }).collect()
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -1401,33 +3033,17 @@ impl<'a> From<CssDirectionCornerParseError<'a>> for CssDirectionParseError<'a> {
 fn parse_direction<'a>(input: &'a str)
 -> Result<Direction, CssDirectionParseError<'a>>
 {
-    use std::f32::consts::PI;
-
     let input_iter = input.split_whitespace();
     let count = input_iter.clone().count();
     let mut first_input_iter = input_iter.clone();
     // "50deg" | "to" | "right"
     let first_input = first_input_iter.next().ok_or(CssDirectionParseError::Error(input))?;
 
-    enum AngleType {
-        Deg,
-        Rad,
-        Gon,
-    }
-
-    let angle = {
-        if first_input.ends_with("deg") { Some(AngleType::Deg) }
-        else if first_input.ends_with("rad") { Some(AngleType::Rad) }
-        else if first_input.ends_with("grad") { Some(AngleType::Gon) }
-        else { None }
-    };
-
-    if let Some(angle_type) = angle {
-        match angle_type {
-            AngleType::Deg => { return Ok(Direction::Angle(first_input.split("deg").next().unwrap().parse::<f32>()?)); }
-            AngleType::Rad => { return Ok(Direction::Angle(first_input.split("rad").next().unwrap().parse::<f32>()? * 180.0 * PI)); }
-            AngleType::Gon => { return Ok(Direction::Angle(first_input.split("grad").next().unwrap().parse::<f32>()?  / 400.0 * 360.0)); }
+    if let Some(angle) = parse_angle(first_input) {
+        if count != 1 {
+            return Err(CssDirectionParseError::InvalidArguments(input));
         }
+        return Ok(Direction::Angle(angle));
     }
 
     // if we get here, the input is definitely not an angle
@@ -1479,6 +3095,11 @@ fn parse_direction_corner<'a>(input: &'a str)
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum CssShapeParseError<'a> {
     ShapeErr(InvalidValueErr<'a>),
+    /// Size keywords mixed with lengths, or a length that doesn't fit the
+    /// shape (a circle has one radius, an ellipse two)
+    InvalidSize(&'a str),
+    /// A position after the `at` that isn't one or two lengths or keywords
+    InvalidPosition(&'a str),
 }
 
 #[derive(Debug, PartialEq, Copy, Clone)]
@@ -1494,21 +3115,258 @@ pub struct LayoutMinHeight(pub PixelValue);
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub struct LayoutMaxHeight(pub PixelValue);
 
+/// `top`, `right`, `bottom` and `left` of a positioned node, `auto` is `None`
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct LayoutTop(pub Option<PixelValue>);
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct LayoutRight(pub Option<PixelValue>);
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct LayoutBottom(pub Option<PixelValue>);
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct LayoutLeft(pub Option<PixelValue>);
+
+macro_rules! impl_to_css_string_offset {
+    ($($type:ident),+) => ($(
+        impl ToCssString for $type {
+            fn to_css_string(&self) -> String {
+                match self.0 {
+                    Some(ref length) => length.to_css_string(),
+                    None => "auto".to_string(),
+                }
+            }
+        }
+    )+)
+}
+
+impl_to_css_string_offset!(LayoutTop, LayoutRight, LayoutBottom, LayoutLeft);
+
+/// Parses the value of `top`, `right`, `bottom` or `left`, i.e. "auto" or "10px"
+fn parse_layout_offset<'a>(input: &'a str)
+-> Result<Option<PixelValue>, PixelParseError<'a>>
+{
+    match input {
+        "auto" => Ok(None),
+        _ => parse_pixel_value(input).and_then(|e| Ok(Some(e))),
+    }
+}
+
+/// The `z-index` property, `auto` is `None`
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct LayoutZIndex(pub Option<i32>);
+
+impl ToCssString for LayoutZIndex {
+    fn to_css_string(&self) -> String {
+        match self.0 {
+            Some(z_index) => z_index.to_string(),
+            None => "auto".to_string(),
+        }
+    }
+}
+
+// parses "auto", "5", "-1"
+fn parse_layout_z_index<'a>(input: &'a str)
+-> Result<LayoutZIndex, InvalidValueErr<'a>>
+{
+    match input {
+        "auto" => Ok(LayoutZIndex(None)),
+        _ => match input.parse::<i32>() {
+            Ok(z_index) => Ok(LayoutZIndex(Some(z_index))),
+            Err(_) => Err(InvalidValueErr(input)),
+        },
+    }
+}
+
+/// `padding`, `padding-top`, etc.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct LayoutPadding(pub LayoutSideOffsets);
+/// `margin`, `margin-top`, etc.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct LayoutMargin(pub LayoutSideOffsets);
+
+const PADDING_KEYS: [&str; 5] = ["padding", "padding-top", "padding-right", "padding-bottom", "padding-left"];
+const MARGIN_KEYS: [&str; 5] = ["margin", "margin-top", "margin-right", "margin-bottom", "margin-left"];
+
+/// The four sides of a `padding` or `margin`. A side is `None` if it isn't set,
+/// i.e. `padding-left: 5px` only sets the `left` side.
+#[derive(Debug, Default, PartialEq, Copy, Clone)]
+pub struct LayoutSideOffsets {
+    pub top: Option<PixelValue>,
+    pub right: Option<PixelValue>,
+    pub bottom: Option<PixelValue>,
+    pub left: Option<PixelValue>,
+}
+
+impl LayoutSideOffsets {
+
+    pub fn uniform(value: PixelValue) -> Self {
+        Self { top: Some(value), right: Some(value), bottom: Some(value), left: Some(value) }
+    }
+
+    pub fn top(value: PixelValue) -> Self {
+        Self { top: Some(value), .. Default::default() }
+    }
+
+    pub fn right(value: PixelValue) -> Self {
+        Self { right: Some(value), .. Default::default() }
+    }
+
+    pub fn bottom(value: PixelValue) -> Self {
+        Self { bottom: Some(value), .. Default::default() }
+    }
+
+    pub fn left(value: PixelValue) -> Self {
+        Self { left: Some(value), .. Default::default() }
+    }
+
+    // "merges" two side offsets, the sides that are set in `other` win
+    pub fn merge(&mut self, other: &LayoutSideOffsets) {
+        fn merge_side(side: &mut Option<PixelValue>, other: &Option<PixelValue>) {
+            if other.is_some() {
+                *side = *other;
+            }
+        }
+
+        merge_side(&mut self.top, &other.top);
+        merge_side(&mut self.right, &other.right);
+        merge_side(&mut self.bottom, &other.bottom);
+        merge_side(&mut self.left, &other.left);
+    }
+
+    // replaces the sides that are set in `self` with the ones of `other`, if they are set there as well
+    fn replace_set_sides(&mut self, other: &LayoutSideOffsets) {
+        fn replace_side(side: &mut Option<PixelValue>, other: &Option<PixelValue>) {
+            if side.is_some() && other.is_some() {
+                *side = *other;
+            }
+        }
+
+        replace_side(&mut self.top, &other.top);
+        replace_side(&mut self.right, &other.right);
+        replace_side(&mut self.bottom, &other.bottom);
+        replace_side(&mut self.left, &other.left);
+    }
+
+    /// Returns the sides in pixels, unset sides are 0. Like in CSS, percentages
+    /// of all four sides are relative to the width of the containing block.
+    pub(crate) fn resolve(&self, context: &LengthContext, containing_block_width: f32) -> SideOffsets2D<f32> {
+        let side = |value: Option<PixelValue>| value.and_then(|v| Some(v.resolve(context, containing_block_width))).unwrap_or(0.0);
+        SideOffsets2D::new(side(self.top), side(self.right), side(self.bottom), side(self.left))
+    }
+
+    /// Interpolates the sides, returns `None` if the sides that are set differ,
+    /// i.e. for a `padding-top` and a `padding-left`
+    pub(crate) fn interpolate(&self, other: &LayoutSideOffsets, t: f32) -> Option<Self> {
+        fn interpolate_side(a: Option<PixelValue>, b: Option<PixelValue>, t: f32) -> Result<Option<PixelValue>, ()> {
+            match (a, b) {
+                (Some(a), Some(b)) => Ok(Some(a.interpolate(&b, t))),
+                (None, None) => Ok(None),
+                _ => Err(()),
+            }
+        }
+
+        Some(Self {
+            top: interpolate_side(self.top, other.top, t).ok()?,
+            right: interpolate_side(self.right, other.right, t).ok()?,
+            bottom: interpolate_side(self.bottom, other.bottom, t).ok()?,
+            left: interpolate_side(self.left, other.left, t).ok()?,
+        })
+    }
+
+    /// Returns the key of the shorthand (`keys[0]`) or, if only one side is set,
+    /// the key of the longhand (`keys[1..5]`, in the order top, right, bottom, left)
+    fn get_key(&self, keys: &[&'static str; 5]) -> &'static str {
+        match (self.top, self.right, self.bottom, self.left) {
+            (Some(_), None, None, None) => keys[1],
+            (None, Some(_), None, None) => keys[2],
+            (None, None, Some(_), None) => keys[3],
+            (None, None, None, Some(_)) => keys[4],
+            _ => keys[0],
+        }
+    }
+}
+
+/// Writes the value of the longhand if only one side is set, otherwise the
+/// value of the shorthand (a single value if all sides are equal)
+impl ToCssString for LayoutSideOffsets {
+    fn to_css_string(&self) -> String {
+        let zero = PixelValue::from_metric(CssMetric::Px, 0.0);
+        match (self.top, self.right, self.bottom, self.left) {
+            (Some(side), None, None, None) | (None, Some(side), None, None) |
+            (None, None, Some(side), None) | (None, None, None, Some(side)) => side.to_css_string(),
+            (top, right, bottom, left) => {
+                let sides = [top.unwrap_or(zero), right.unwrap_or(zero), bottom.unwrap_or(zero), left.unwrap_or(zero)];
+                if sides.iter().all(|side| *side == sides[0]) {
+                    sides[0].to_css_string()
+                } else {
+                    sides.iter().map(|side| side.to_css_string()).collect::<Vec<String>>().join(" ")
+                }
+            }
+        }
+    }
+}
+
+/// Parses the `padding` or `margin` shorthand, i.e. "5px", "5px 10px",
+/// "5px 10px 6px" or "5px 10px 6px 10px" (top, right, bottom, left)
+fn parse_layout_side_offsets<'a>(input: &'a str)
+-> Result<LayoutSideOffsets, CssSideOffsetsParseError<'a>>
+{
+    let components = input.split_whitespace()
+        .map(parse_pixel_value)
+        .collect::<Result<Vec<PixelValue>, PixelParseError<'a>>>()?;
+
+    let sides = expand_sides(&components).ok_or(CssSideOffsetsParseError::TooManyValues(input))?;
+
+    Ok(LayoutSideOffsets { top: Some(sides[0]), right: Some(sides[1]), bottom: Some(sides[2]), left: Some(sides[3]) })
+}
+
+/// Expands the one to four values of a property like `padding` or `border-color` to the
+/// four sides (top, right, bottom, left), returns `None` for no or more than four values
+fn expand_sides<T: Copy>(values: &[T]) -> Option<[T; 4]> {
+    match values.len() {
+        // One value applies to all four sides
+        1 => Some([values[0], values[0], values[0], values[0]]),
+        // Two values: top and bottom, right and left
+        2 => Some([values[0], values[1], values[0], values[1]]),
+        // Three values: top, right and left, bottom
+        3 => Some([values[0], values[1], values[2], values[1]]),
+        4 => Some([values[0], values[1], values[2], values[3]]),
+        _ => None,
+    }
+}
+
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub struct LineHeight(pub PercentageValue);
 
+impl_to_css_string_newtype!(
+    LayoutWidth, LayoutMinWidth, LayoutMaxWidth,
+    LayoutHeight, LayoutMinHeight, LayoutMaxHeight,
+    LayoutPadding, LayoutMargin,
+    LineHeight, FontSize);
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum LayoutDirection {
     Horizontal,
     Vertical,
 }
 
+impl Default for LayoutDirection {
+    fn default() -> Self {
+        LayoutDirection::Vertical
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum LayoutWrap {
     Wrap,
     NoWrap,
 }
 
+impl Default for LayoutWrap {
+    fn default() -> Self {
+        LayoutWrap::NoWrap
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum LayoutJustifyContent {
     /// Default value. Items are positioned at the beginning of the container
@@ -1523,6 +3381,12 @@ pub enum LayoutJustifyContent {
     SpaceAround,
 }
 
+impl Default for LayoutJustifyContent {
+    fn default() -> Self {
+        LayoutJustifyContent::Start
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum LayoutAlignItems {
     /// Items are stretched to fit the container
@@ -1535,6 +3399,12 @@ pub enum LayoutAlignItems {
     End,
 }
 
+impl Default for LayoutAlignItems {
+    fn default() -> Self {
+        LayoutAlignItems::Stretch
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum LayoutAlignContent {
     /// Default value. Lines stretch to take up the remaining space
@@ -1551,6 +3421,104 @@ pub enum LayoutAlignContent {
     SpaceAround,
 }
 
+impl Default for LayoutAlignContent {
+    fn default() -> Self {
+        LayoutAlignContent::Stretch
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LayoutPosition {
+    /// Default value. The node is laid out by its parent, `top` / `left` / etc. are ignored
+    Static,
+    /// The node is laid out by its parent, then moved by its `top` / `left` / etc.,
+    /// without affecting the other nodes
+    Relative,
+    /// The node is taken out of the layout of its parent and placed by its `top` / `left` / etc.
+    /// inside of the nearest positioned ancestor (or the window)
+    Absolute,
+}
+
+impl Default for LayoutPosition {
+    fn default() -> Self {
+        LayoutPosition::Static
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LayoutDisplay {
+    /// Default value. The node is laid out as a flex container
+    Flex,
+    /// The node and its children are removed from the layout and aren't painted
+    None,
+}
+
+impl Default for LayoutDisplay {
+    fn default() -> Self {
+        LayoutDisplay::Flex
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StyleVisibility {
+    Visible,
+    /// The node still takes up its space in the layout, but isn't painted or hit-tested
+    Hidden,
+}
+
+impl Default for StyleVisibility {
+    fn default() -> Self {
+        StyleVisibility::Visible
+    }
+}
+
+/// The mouse cursor that is shown while the mouse is over the node
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StyleCursor {
+    /// Default value. Shows the cursor that is set in the `WindowState` of the window
+    Auto,
+    Default,
+    Pointer,
+    Text,
+    VerticalText,
+    Move,
+    Crosshair,
+    Wait,
+    Progress,
+    Help,
+    ContextMenu,
+    Cell,
+    Alias,
+    Copy,
+    NoDrop,
+    NotAllowed,
+    Grab,
+    Grabbing,
+    AllScroll,
+    ZoomIn,
+    ZoomOut,
+    NResize,
+    EResize,
+    SResize,
+    WResize,
+    NeResize,
+    NwResize,
+    SeResize,
+    SwResize,
+    EwResize,
+    NsResize,
+    NeswResize,
+    NwseResize,
+    ColResize,
+    RowResize,
+}
+
+impl Default for StyleCursor {
+    fn default() -> Self {
+        StyleCursor::Auto
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum TextOverflowBehaviour {
     NotModified,
@@ -1611,12 +3579,20 @@ impl Default for TextAlignmentVert {
 pub(crate) struct RectStyle {
     /// Background color of this rectangle
     pub(crate) background_color: Option<BackgroundColor>,
-    /// Shadow color
-    pub(crate) box_shadow: Option<BoxShadowPreDisplayItem>,
-    /// Gradient (location) + stops
-    pub(crate) background: Option<Background>,
-    /// Border
-    pub(crate) border: Option<(BorderWidths, BorderDetails)>,
+    /// Box shadows, the first shadow is painted on top
+    pub(crate) box_shadow: Option<StyleBoxShadows>,
+    /// Background layers (gradients or images)
+    pub(crate) background: Option<StyleBackgrounds>,
+    /// `background-size` property
+    pub(crate) background_size: Option<StyleBackgroundSizes>,
+    /// `background-position` property
+    pub(crate) background_position: Option<StyleBackgroundPositions>,
+    /// `background-repeat` property
+    pub(crate) background_repeat: Option<StyleBackgroundRepeats>,
+    /// Border, merged from `border` and its longhands
+    pub(crate) border: Option<StyleBorder>,
+    /// `border-image` property
+    pub(crate) border_image: Option<StyleBorderImage>,
     /// Border radius
     pub(crate) border_radius: Option<BorderRadius>,
     /// Font size
@@ -1631,6 +3607,45 @@ pub(crate) struct RectStyle {
     pub(crate) overflow: Option<LayoutOverflow>,
     /// `line-height` property
     pub(crate) line_height: Option<LineHeight>,
+    /// `font-weight` property
+    pub(crate) font_weight: Option<FontWeight>,
+    /// `font-style` property
+    pub(crate) font_style: Option<FontStyle>,
+    /// `letter-spacing` property
+    pub(crate) letter_spacing: Option<LetterSpacing>,
+    /// `word-spacing` property
+    pub(crate) word_spacing: Option<WordSpacing>,
+    /// `text-decoration` property
+    pub(crate) text_decoration: Option<TextDecoration>,
+    /// `text-transform` property
+    pub(crate) text_transform: Option<TextTransform>,
+    /// `vertical-align` property, overrides the alignment from the `justify-content` of the parent
+    pub(crate) vertical_align: Option<TextAlignmentVert>,
+    /// `opacity` property
+    pub(crate) opacity: Option<StyleOpacity>,
+    /// `transform` property
+    pub(crate) transform: Option<StyleTransforms>,
+    /// `filter` property
+    pub(crate) filter: Option<StyleFilters>,
+    /// `visibility` property
+    pub(crate) visibility: Option<StyleVisibility>,
+    /// `cursor` property
+    pub(crate) cursor: Option<StyleCursor>,
+}
+
+impl RectStyle {
+    /// Returns whether the `opacity`, `transform` or `filter` of the node wraps the node and
+    /// its children in a stacking context, so that they are painted as one group
+    pub(crate) fn has_stacking_context(&self) -> bool {
+        self.opacity.map(|o| o.0 < 1.0).unwrap_or(false) ||
+        self.transform.as_ref().map(|t| !t.0.is_empty()).unwrap_or(false) ||
+        self.filter.as_ref().map(|f| !f.0.is_empty()).unwrap_or(false)
+    }
+
+    /// Returns whether the node is painted, see `StyleVisibility`
+    pub(crate) fn is_visible(&self) -> bool {
+        self.visibility.unwrap_or_default() == StyleVisibility::Visible
+    }
 }
 
 // Layout constraints for a given rectangle, such as ""
@@ -1647,148 +3662,1191 @@ pub struct RectLayout {
     pub justify_content: Option<LayoutJustifyContent>,
     pub align_items: Option<LayoutAlignItems>,
     pub align_content: Option<LayoutAlignContent>,
+    pub padding: Option<LayoutPadding>,
+    pub margin: Option<LayoutMargin>,
+    /// The font size, which `em` lengths are relative to
+    pub font_size: Option<FontSize>,
+    pub position: Option<LayoutPosition>,
+    pub top: Option<LayoutTop>,
+    pub right: Option<LayoutRight>,
+    pub bottom: Option<LayoutBottom>,
+    pub left: Option<LayoutLeft>,
+    pub z_index: Option<LayoutZIndex>,
+    pub display: Option<LayoutDisplay>,
+}
+
+typed_pixel_value_parser!(parse_layout_width, LayoutWidth);
+typed_pixel_value_parser!(parse_layout_height, LayoutHeight);
+typed_pixel_value_parser!(parse_layout_min_height, LayoutMinHeight);
+typed_pixel_value_parser!(parse_layout_min_width, LayoutMinWidth);
+typed_pixel_value_parser!(parse_layout_max_width, LayoutMaxWidth);
+typed_pixel_value_parser!(parse_layout_max_height, LayoutMaxHeight);
+
+fn parse_line_height(input: &str)
+-> Result<LineHeight, PercentageParseError>
+{
+    parse_percentage_value(input).and_then(|e| Ok(LineHeight(e)))
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub struct FontSize(pub(crate) PixelValue);
+
+typed_pixel_value_parser!(parse_css_font_size, FontSize);
+
+impl FontSize {
+    /// Creates the font size in pixel
+    pub fn px(value: f32) -> Self {
+        FontSize(PixelValue::from_metric(CssMetric::Px, value))
+    }
+
+    /// Creates the font size in em
+    pub fn em(value: f32) -> Self {
+        FontSize(PixelValue::from_metric(CssMetric::Em, value))
+    }
+
+    /// Creates the font size in point (pt)
+    pub fn pt(value: f32) -> Self {
+        FontSize(PixelValue::from_metric(CssMetric::Pt, value))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FontFamily {
+    // parsed fonts, in order, i.e. "Webly Sleeky UI", "monospace", etc.
+    pub(crate) fonts: Vec<FontId>
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum FontId {
+    BuiltinFont(&'static str),
+    ExternalFont(String),
+    /// The bold and / or italic face of a font family, loaded from the system fonts.
+    /// Selected by the `font-weight` and `font-style`, see `AppResources::get_font_face`
+    SystemFontFace { family: String, bold: bool, style: FontStyle },
+}
+
+impl ToCssString for FontFamily {
+    fn to_css_string(&self) -> String {
+        self.fonts.iter().map(|font| match *font {
+            FontId::BuiltinFont(name) => name.to_string(),
+            FontId::ExternalFont(ref name) => format!("\"{}\"", name),
+            FontId::SystemFontFace { ref family, .. } => family.clone(),
+        }).collect::<Vec<String>>().join(", ")
+    }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum CssFontFamilyParseError<'a> {
+    InvalidFontFamily(&'a str),
+    UnrecognizedBuiltinFont(&'a str),
+    UnclosedQuotes(&'a str),
+}
+
+impl<'a> From<UnclosedQuotesError<'a>> for CssFontFamilyParseError<'a> {
+    fn from(err: UnclosedQuotesError<'a>) -> Self {
+        CssFontFamilyParseError::UnclosedQuotes(err.0)
+    }
+}
+
+// parses a "font-family" declaration, such as:
+//
+// "Webly Sleeky UI", monospace
+// 'Webly Sleeky Ui', monospace
+// sans-serif
+pub(crate) fn parse_css_font_family<'a>(input: &'a str) -> Result<FontFamily, CssFontFamilyParseError<'a>> {
+    let multiple_fonts = input.split(',');
+    let mut fonts = Vec::with_capacity(1);
+
+    for font in multiple_fonts {
+        let font = font.trim();
+
+        let mut double_quote_iter = font.splitn(2, '"');
+        double_quote_iter.next();
+        let mut single_quote_iter = font.splitn(2, '\'');
+        single_quote_iter.next();
+
+        if double_quote_iter.next().is_some() || single_quote_iter.next().is_some() {
+            let stripped_font = strip_quotes(font)?;
+            fonts.push(FontId::ExternalFont(stripped_font.0.into()));
+        } else {
+            match font {
+                "serif"      => fonts.push(FontId::BuiltinFont("serif")),
+                "sans-serif" => fonts.push(FontId::BuiltinFont("sans-serif")),
+                "monospace"  => fonts.push(FontId::BuiltinFont("monospace")),
+                "fantasy"    => fonts.push(FontId::BuiltinFont("fantasy")),
+                "cursive"    => fonts.push(FontId::BuiltinFont("cursive")),
+                _ => return Err(CssFontFamilyParseError::UnrecognizedBuiltinFont(font)),
+            }
+        }
+    }
+
+    Ok(FontFamily {
+        fonts: fonts,
+    })
+}
+
+multi_type_parser!(parse_layout_direction, LayoutDirection,
+                    ["row", Horizontal],
+                    ["column", Vertical]);
+
+multi_type_parser!(parse_layout_wrap, LayoutWrap,
+                    ["wrap", Wrap],
+                    ["nowrap", NoWrap]);
+
+multi_type_parser!(parse_layout_justify_content, LayoutJustifyContent,
+                    ["start", Start],
+                    ["end", End],
+                    ["center", Center],
+                    ["space-between", SpaceBetween],
+                    ["space-around", SpaceAround]);
+
+multi_type_parser!(parse_layout_align_items, LayoutAlignItems,
+                    ["stretch", Stretch],
+                    ["start", Start],
+                    ["end", End],
+                    ["center", Center]);
+
+multi_type_parser!(parse_layout_align_content, LayoutAlignContent,
+                    ["stretch", Stretch],
+                    ["start", Start],
+                    ["end", End],
+                    ["center", Center],
+                    ["space-between", SpaceBetween],
+                    ["space-around", SpaceAround]);
+
+multi_type_parser!(parse_layout_position, LayoutPosition,
+                    ["static", Static],
+                    ["relative", Relative],
+                    ["absolute", Absolute]);
+
+multi_type_parser!(parse_layout_display, LayoutDisplay,
+                    ["flex", Flex],
+                    ["none", None]);
+
+multi_type_parser!(parse_style_visibility, StyleVisibility,
+                    ["visible", Visible],
+                    ["hidden", Hidden]);
+
+multi_type_parser!(parse_style_cursor, StyleCursor,
+                    ["auto", Auto],
+                    ["default", Default],
+                    ["pointer", Pointer],
+                    ["text", Text],
+                    ["vertical-text", VerticalText],
+                    ["move", Move],
+                    ["crosshair", Crosshair],
+                    ["wait", Wait],
+                    ["progress", Progress],
+                    ["help", Help],
+                    ["context-menu", ContextMenu],
+                    ["cell", Cell],
+                    ["alias", Alias],
+                    ["copy", Copy],
+                    ["no-drop", NoDrop],
+                    ["not-allowed", NotAllowed],
+                    ["grab", Grab],
+                    ["grabbing", Grabbing],
+                    ["all-scroll", AllScroll],
+                    ["zoom-in", ZoomIn],
+                    ["zoom-out", ZoomOut],
+                    ["n-resize", NResize],
+                    ["e-resize", EResize],
+                    ["s-resize", SResize],
+                    ["w-resize", WResize],
+                    ["ne-resize", NeResize],
+                    ["nw-resize", NwResize],
+                    ["se-resize", SeResize],
+                    ["sw-resize", SwResize],
+                    ["ew-resize", EwResize],
+                    ["ns-resize", NsResize],
+                    ["nesw-resize", NeswResize],
+                    ["nwse-resize", NwseResize],
+                    ["col-resize", ColResize],
+                    ["row-resize", RowResize]);
+
+multi_type_parser!(parse_background_repeat, BackgroundRepeat,
+                    ["repeat", Repeat],
+                    ["repeat-x", RepeatX],
+                    ["repeat-y", RepeatY],
+                    ["no-repeat", NoRepeat]);
+
+multi_type_parser!(parse_shape, Shape,
+                    ["circle", Circle],
+                    ["ellipse", Ellipse]);
+
+multi_type_parser!(parse_layout_text_overflow, TextOverflowBehaviourInner,
+                    ["auto", Auto],
+                    ["scroll", Scroll],
+                    ["visible", Visible],
+                    ["hidden", Hidden]);
+
+multi_type_parser!(parse_layout_text_align, TextAlignmentHorz,
+                    ["center", Center],
+                    ["left", Left],
+                    ["right", Right]);
+
+multi_type_parser!(parse_layout_vertical_align, TextAlignmentVert,
+                    ["top", Top],
+                    ["middle", Center],
+                    ["bottom", Bottom]);
+
+/// The `font-weight` property, between `1` and `1000`. `normal` is `400`, `bold` is `700`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FontWeight(pub u16);
+
+impl FontWeight {
+    pub const NORMAL: FontWeight = FontWeight(400);
+    pub const BOLD: FontWeight = FontWeight(700);
+
+    /// Returns whether the bold face of a font should be used for this weight
+    pub fn is_bold(&self) -> bool {
+        self.0 >= 600
+    }
+}
+
+impl Default for FontWeight {
+    fn default() -> Self {
+        FontWeight::NORMAL
+    }
+}
+
+impl ToCssString for FontWeight {
+    fn to_css_string(&self) -> String {
+        match *self {
+            FontWeight::NORMAL => "normal".to_string(),
+            FontWeight::BOLD => "bold".to_string(),
+            FontWeight(weight) => weight.to_string(),
+        }
+    }
+}
+
+// parses "normal", "bold", "300"
+fn parse_font_weight<'a>(input: &'a str)
+-> Result<FontWeight, InvalidValueErr<'a>>
+{
+    match input {
+        "normal" => Ok(FontWeight::NORMAL),
+        "bold" => Ok(FontWeight::BOLD),
+        _ => match input.parse::<u16>() {
+            Ok(weight) if weight >= 1 && weight <= 1000 => Ok(FontWeight(weight)),
+            _ => Err(InvalidValueErr(input)),
+        },
+    }
+}
+
+/// The `font-style` property
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FontStyle {
+    Normal,
+    Italic,
+    Oblique,
+}
+
+impl Default for FontStyle {
+    fn default() -> Self {
+        FontStyle::Normal
+    }
+}
+
+multi_type_parser!(parse_font_style, FontStyle,
+                    ["normal", Normal],
+                    ["italic", Italic],
+                    ["oblique", Oblique]);
+
+/// The `letter-spacing` property, the space that is added after every character
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct LetterSpacing(pub PixelValue);
+
+/// The `word-spacing` property, the space that is added to every space between two words
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct WordSpacing(pub PixelValue);
+
+impl_to_css_string_newtype!(LetterSpacing, WordSpacing);
+
+// parses "normal", "2px", "0.1em"
+fn parse_text_spacing<'a>(input: &'a str)
+-> Result<PixelValue, PixelParseError<'a>>
+{
+    match input {
+        "normal" => Ok(PixelValue::zero()),
+        _ => parse_pixel_value(input),
+    }
+}
+
+/// The `text-decoration` property, i.e. `text-decoration: underline line-through`.
+/// The lines are drawn in the color of the text.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TextDecoration {
+    pub underline: bool,
+    pub overline: bool,
+    pub line_through: bool,
+}
+
+impl TextDecoration {
+    /// Returns whether the decoration doesn't draw any line, i.e. `text-decoration: none`
+    pub fn is_none(&self) -> bool {
+        !self.underline && !self.overline && !self.line_through
+    }
+}
+
+impl ToCssString for TextDecoration {
+    fn to_css_string(&self) -> String {
+        if self.is_none() {
+            return "none".to_string();
+        }
+        let lines = [(self.underline, "underline"), (self.overline, "overline"), (self.line_through, "line-through")];
+        lines.iter().filter(|(is_set, _)| *is_set).map(|(_, line)| *line).collect::<Vec<&str>>().join(" ")
+    }
+}
+
+// parses "none", "underline", "underline line-through"
+fn parse_text_decoration<'a>(input: &'a str)
+-> Result<TextDecoration, InvalidValueErr<'a>>
+{
+    let mut decoration = TextDecoration::default();
+
+    if input == "none" {
+        return Ok(decoration);
+    }
+
+    for line in input.split_whitespace() {
+        let is_set = match line {
+            "underline" => &mut decoration.underline,
+            "overline" => &mut decoration.overline,
+            "line-through" => &mut decoration.line_through,
+            _ => return Err(InvalidValueErr(line)),
+        };
+        // the same line can't be set twice, i.e. `underline underline`
+        if *is_set {
+            return Err(InvalidValueErr(line));
+        }
+        *is_set = true;
+    }
+
+    Ok(decoration)
+}
+
+/// The `text-transform` property
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TextTransform {
+    None,
+    Uppercase,
+    Lowercase,
+    /// Converts the first letter of every word to uppercase
+    Capitalize,
+}
+
+impl Default for TextTransform {
+    fn default() -> Self {
+        TextTransform::None
+    }
+}
+
+impl TextTransform {
+    /// Returns the transformed text, i.e. `"HELLO WORLD"` for `Uppercase`
+    pub fn apply(&self, text: &str) -> String {
+        match *self {
+            TextTransform::None => text.to_string(),
+            TextTransform::Uppercase => text.to_uppercase(),
+            TextTransform::Lowercase => text.to_lowercase(),
+            TextTransform::Capitalize => {
+                let mut capitalized = String::with_capacity(text.len());
+                let mut is_word_start = true;
+                for c in text.chars() {
+                    if is_word_start {
+                        capitalized.extend(c.to_uppercase());
+                    } else {
+                        capitalized.push(c);
+                    }
+                    is_word_start = c.is_whitespace();
+                }
+                capitalized
+            },
+        }
+    }
+}
+
+multi_type_parser!(parse_text_transform, TextTransform,
+                    ["none", None],
+                    ["uppercase", Uppercase],
+                    ["lowercase", Lowercase],
+                    ["capitalize", Capitalize]);
+
+/// The `opacity` property, between `0.0` (transparent) and `1.0` (opaque)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct StyleOpacity(pub f32);
+
+impl Default for StyleOpacity {
+    fn default() -> Self {
+        StyleOpacity(1.0)
+    }
+}
+
+impl ToCssString for StyleOpacity {
+    fn to_css_string(&self) -> String {
+        format!("{}", self.0)
+    }
+}
+
+// parses "0.5", "50%"
+fn parse_style_opacity(input: &str)
+-> Result<StyleOpacity, PercentageParseError>
+{
+    // values outside of the range are valid, but clamped
+    parse_percentage_value(input).and_then(|p| Ok(StyleOpacity(p.number.max(0.0).min(1.0))))
+}
+
+/// The `transform` property, i.e. `transform: translate(10px, 5px) rotate(45deg)`.
+/// An empty list is `transform: none`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StyleTransforms(pub Vec<TransformFunction>);
+
+/// A single function of the `transform` property. `translateX(5px)` and `scaleY(2)`
+/// are parsed as a `Translate` / `Scale` that doesn't change the other axis.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TransformFunction {
+    /// Translation in x and y direction, `%` is relative to the size of the node itself
+    Translate(PixelValue, PixelValue),
+    /// Scale factor in x and y direction
+    Scale(f32, f32),
+    /// Clockwise rotation, in degrees
+    Rotate(f32),
+    /// Skew angles along the x and y axis, in degrees
+    Skew(f32, f32),
+}
+
+impl TransformFunction {
+    /// Returns the function with the same type that doesn't transform anything,
+    /// i.e. `scale(1, 1)` for a `scale(2, 3)`
+    fn identity(&self) -> Self {
+        use self::TransformFunction::*;
+        match *self {
+            Translate(_, _) => Translate(PixelValue::zero(), PixelValue::zero()),
+            Scale(_, _) => Scale(1.0, 1.0),
+            Rotate(_) => Rotate(0.0),
+            Skew(_, _) => Skew(0.0, 0.0),
+        }
+    }
+
+    fn interpolate(&self, other: &Self, t: f32) -> Option<Self> {
+        use self::TransformFunction::*;
+        match (*self, *other) {
+            (Translate(ax, ay), Translate(bx, by)) => Some(Translate(ax.interpolate(&bx, t), ay.interpolate(&by, t))),
+            (Scale(ax, ay), Scale(bx, by)) => Some(Scale(interpolate_f32(ax, bx, t), interpolate_f32(ay, by, t))),
+            (Rotate(a), Rotate(b)) => Some(Rotate(interpolate_f32(a, b, t))),
+            (Skew(ax, ay), Skew(bx, by)) => Some(Skew(interpolate_f32(ax, bx, t), interpolate_f32(ay, by, t))),
+            _ => None,
+        }
+    }
+
+    /// Returns the transformation matrix of this function
+    fn to_layout_transform(&self, size: &LayoutSize, context: &LengthContext) -> LayoutTransform {
+        use self::TransformFunction::*;
+        match *self {
+            Translate(x, y) => LayoutTransform::create_translation(x.resolve(context, size.width), y.resolve(context, size.height), 0.0),
+            Scale(x, y) => LayoutTransform::create_scale(x, y, 1.0),
+            // euclid rotates counter-clockwise, since its y axis points up
+            Rotate(deg) => LayoutTransform::create_rotation(0.0, 0.0, 1.0, Angle::radians(-deg.to_radians())),
+            Skew(x, y) => LayoutTransform::create_skew(Angle::radians(x.to_radians()), Angle::radians(y.to_radians())),
+        }
+    }
+}
+
+impl StyleTransforms {
+    /// Returns the transformation matrix of a node with the given `bounds`. The transform
+    /// origin is the center of the node, i.e. `rotate(45deg)` rotates around the center.
+    pub(crate) fn to_layout_transform(&self, bounds: &LayoutRect, context: &LengthContext) -> LayoutTransform {
+        let center = bounds.center();
+        // The first function is the outermost one, i.e. for `translate(10px) rotate(45deg)`
+        // the node is rotated first and then translated
+        self.0.iter().fold(LayoutTransform::identity(), |transform, function| {
+            transform.pre_mul(&function.to_layout_transform(&bounds.size, context))
+        })
+        .pre_translate(TypedVector3D::new(-center.x, -center.y, 0.0))
+        .post_translate(TypedVector3D::new(center.x, center.y, 0.0))
+    }
+}
+
+impl ToCssString for TransformFunction {
+    fn to_css_string(&self) -> String {
+        use self::TransformFunction::*;
+        match *self {
+            Translate(x, y) => format!("translate({}, {})", x.to_css_string(), y.to_css_string()),
+            Scale(x, y) => format!("scale({}, {})", x, y),
+            Rotate(deg) => format!("rotate({}deg)", deg),
+            Skew(x, y) => format!("skew({}deg, {}deg)", x, y),
+        }
+    }
+}
+
+impl ToCssString for StyleTransforms {
+    fn to_css_string(&self) -> String {
+        if self.0.is_empty() {
+            return "none".to_string();
+        }
+        self.0.iter().map(|function| function.to_css_string()).collect::<Vec<String>>().join(" ")
+    }
+}
+
+/// The `filter` property, i.e. `filter: blur(5px) grayscale(100%)`.
+/// An empty list is `filter: none`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StyleFilters(pub Vec<FilterFunction>);
+
+/// A single function of the `filter` property. Amounts are normalized,
+/// i.e. `grayscale(50%)` is stored as `Grayscale(0.5)`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FilterFunction {
+    /// Gaussian blur, the value is the standard deviation
+    Blur(PixelValue),
+    Brightness(f32),
+    Contrast(f32),
+    Grayscale(f32),
+    /// Rotation of the hue, in degrees
+    HueRotate(f32),
+    Invert(f32),
+    Opacity(f32),
+    Saturate(f32),
+    Sepia(f32),
+}
+
+impl FilterFunction {
+    /// Returns the function with the same type that doesn't change the
+    /// rendered image, i.e. `grayscale(0)` for a `grayscale(1)`
+    fn identity(&self) -> Self {
+        use self::FilterFunction::*;
+        match *self {
+            Blur(_) => Blur(PixelValue::zero()),
+            Brightness(_) => Brightness(1.0),
+            Contrast(_) => Contrast(1.0),
+            Grayscale(_) => Grayscale(0.0),
+            HueRotate(_) => HueRotate(0.0),
+            Invert(_) => Invert(0.0),
+            Opacity(_) => Opacity(1.0),
+            Saturate(_) => Saturate(1.0),
+            Sepia(_) => Sepia(0.0),
+        }
+    }
+
+    fn interpolate(&self, other: &Self, t: f32) -> Option<Self> {
+        use self::FilterFunction::*;
+        let amount = |a: f32, b: f32| interpolate_f32(a, b, t);
+        match (*self, *other) {
+            (Blur(a), Blur(b)) => Some(Blur(a.interpolate(&b, t))),
+            (Brightness(a), Brightness(b)) => Some(Brightness(amount(a, b))),
+            (Contrast(a), Contrast(b)) => Some(Contrast(amount(a, b))),
+            (Grayscale(a), Grayscale(b)) => Some(Grayscale(amount(a, b))),
+            (HueRotate(a), HueRotate(b)) => Some(HueRotate(amount(a, b))),
+            (Invert(a), Invert(b)) => Some(Invert(amount(a, b))),
+            (Opacity(a), Opacity(b)) => Some(Opacity(amount(a, b))),
+            (Saturate(a), Saturate(b)) => Some(Saturate(amount(a, b))),
+            (Sepia(a), Sepia(b)) => Some(Sepia(amount(a, b))),
+            _ => None,
+        }
+    }
+}
+
+impl ToCssString for FilterFunction {
+    fn to_css_string(&self) -> String {
+        use self::FilterFunction::*;
+        match *self {
+            Blur(radius) => format!("blur({})", radius.to_css_string()),
+            Brightness(amount) => format!("brightness({})", amount),
+            Contrast(amount) => format!("contrast({})", amount),
+            Grayscale(amount) => format!("grayscale({})", amount),
+            HueRotate(deg) => format!("hue-rotate({}deg)", deg),
+            Invert(amount) => format!("invert({})", amount),
+            Opacity(amount) => format!("opacity({})", amount),
+            Saturate(amount) => format!("saturate({})", amount),
+            Sepia(amount) => format!("sepia({})", amount),
+        }
+    }
+}
+
+impl ToCssString for StyleFilters {
+    fn to_css_string(&self) -> String {
+        if self.0.is_empty() {
+            return "none".to_string();
+        }
+        self.0.iter().map(|function| function.to_css_string()).collect::<Vec<String>>().join(" ")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CssTransformParseError<'a> {
+    /// A value that isn't a transform function, i.e. `perspective(5px)` or `rotate 45deg`
+    UnknownFunction(&'a str),
+    /// A function with too few or too many arguments, i.e. `rotate(45deg, 5deg)`
+    WrongNumberOfArguments(&'a str),
+    /// An angle without a unit or with an unknown unit, i.e. `rotate(45)`
+    InvalidAngle(&'a str),
+    /// A scale factor that is not a number, i.e. `scale(5px)`
+    InvalidNumber(&'a str),
+    PixelParseError(PixelParseError<'a>),
+}
+
+impl_from!(PixelParseError, CssTransformParseError::PixelParseError);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CssFilterParseError<'a> {
+    /// A value that isn't a filter function, i.e. `url(#svg-filter)` or `drop-shadow(...)`
+    UnknownFunction(&'a str),
+    /// A function with more than one argument, i.e. `blur(5px, 2px)`
+    WrongNumberOfArguments(&'a str),
+    /// An angle without a unit or with an unknown unit, i.e. `hue-rotate(90)`
+    InvalidAngle(&'a str),
+    /// An amount that is negative or not a number / percentage, i.e. `grayscale(-1)`
+    InvalidAmount(&'a str),
+    PixelParseError(PixelParseError<'a>),
+}
+
+impl_from!(PixelParseError, CssFilterParseError::PixelParseError);
+
+/// Splits a CSS function into its name and its (trimmed) arguments,
+/// i.e. `"rotate(45deg)"` into `("rotate", vec!["45deg"])`.
+///
+/// A function without arguments (`grayscale()`) returns an empty `Vec`.
+fn parse_css_function<'a>(input: &'a str) -> Option<(&'a str, Vec<&'a str>)> {
+    let open_paren = input.find('(')?;
+    if !input.ends_with(')') {
+        return None;
+    }
+    let name = &input[..open_paren];
+    let arguments = input[open_paren + 1..input.len() - 1].trim();
+    if arguments.is_empty() {
+        return Some((name, Vec::new()));
+    }
+    Some((name, split_unnested(arguments, |c| c == ',').into_iter().map(|argument| argument.trim()).collect()))
+}
+
+// parses "45deg", "0.5turn", "1.2rad", "100grad" and "0", returns the angle in degrees
+fn parse_angle(input: &str) -> Option<f32> {
+    use std::f32::consts::PI;
+
+    let (number, degrees_per_unit) =
+        if input.ends_with("deg") { (&input[..input.len() - 3], 1.0) }
+        else if input.ends_with("grad") { (&input[..input.len() - 4], 360.0 / 400.0) }
+        else if input.ends_with("rad") { (&input[..input.len() - 3], 180.0 / PI) }
+        else if input.ends_with("turn") { (&input[..input.len() - 4], 360.0) }
+        else if input == "0" { return Some(0.0); }
+        else { return None; };
+
+    number.parse::<f32>().ok().and_then(|number| Some(number * degrees_per_unit))
+}
+
+// parses "translate(10px, 50%) rotate(45deg) scale(1.5)"
+fn parse_css_transform<'a>(input: &'a str)
+-> Result<StyleTransforms, CssTransformParseError<'a>>
+{
+    if input == "none" {
+        return Ok(StyleTransforms::default());
+    }
+
+    split_values(input).into_iter().map(|value| {
+        use self::TransformFunction::*;
+
+        let (name, arguments) = parse_css_function(value).ok_or(CssTransformParseError::UnknownFunction(value))?;
+
+        let angle = |argument: &'a str| parse_angle(argument).ok_or(CssTransformParseError::InvalidAngle(argument));
+        let number = |argument: &'a str| argument.parse::<f32>().map_err(|_| CssTransformParseError::InvalidNumber(argument));
+        let wrong_arguments = CssTransformParseError::WrongNumberOfArguments(value);
+
+        match (name, arguments.len()) {
+            ("translate", 1) => Ok(Translate(parse_pixel_value(arguments[0])?, PixelValue::zero())),
+            ("translate", 2) => Ok(Translate(parse_pixel_value(arguments[0])?, parse_pixel_value(arguments[1])?)),
+            ("translateX", 1) => Ok(Translate(parse_pixel_value(arguments[0])?, PixelValue::zero())),
+            ("translateY", 1) => Ok(Translate(PixelValue::zero(), parse_pixel_value(arguments[0])?)),
+            ("scale", 1) => { let factor = number(arguments[0])?; Ok(Scale(factor, factor)) },
+            ("scale", 2) => Ok(Scale(number(arguments[0])?, number(arguments[1])?)),
+            ("scaleX", 1) => Ok(Scale(number(arguments[0])?, 1.0)),
+            ("scaleY", 1) => Ok(Scale(1.0, number(arguments[0])?)),
+            ("rotate", 1) => Ok(Rotate(angle(arguments[0])?)),
+            ("skew", 1) => Ok(Skew(angle(arguments[0])?, 0.0)),
+            ("skew", 2) => Ok(Skew(angle(arguments[0])?, angle(arguments[1])?)),
+            ("skewX", 1) => Ok(Skew(angle(arguments[0])?, 0.0)),
+            ("skewY", 1) => Ok(Skew(0.0, angle(arguments[0])?)),
+            ("translate", _) | ("translateX", _) | ("translateY", _) |
+            ("scale", _) | ("scaleX", _) | ("scaleY", _) | ("rotate", _) |
+            ("skew", _) | ("skewX", _) | ("skewY", _) => Err(wrong_arguments),
+            _ => Err(CssTransformParseError::UnknownFunction(value)),
+        }
+    }).collect::<Result<Vec<TransformFunction>, _>>().and_then(|functions| Ok(StyleTransforms(functions)))
+}
+
+// parses "blur(5px) grayscale(100%) hue-rotate(90deg)"
+fn parse_css_filter<'a>(input: &'a str)
+-> Result<StyleFilters, CssFilterParseError<'a>>
+{
+    if input == "none" {
+        return Ok(StyleFilters::default());
+    }
+
+    split_values(input).into_iter().map(|value| {
+        use self::FilterFunction::*;
+
+        let (name, arguments) = parse_css_function(value).ok_or(CssFilterParseError::UnknownFunction(value))?;
+
+        // A missing argument is the value that changes the image the most, i.e. `grayscale()` is `grayscale(1)`
+        let argument = match arguments.len() {
+            0 => None,
+            1 => Some(arguments[0]),
+            _ => return Err(CssFilterParseError::WrongNumberOfArguments(value)),
+        };
+
+        let amount = |default: f32| match argument {
+            None => Ok(default),
+            Some(argument) => match parse_percentage_value(argument) {
+                Ok(PercentageValue { number }) if number >= 0.0 => Ok(number),
+                _ => Err(CssFilterParseError::InvalidAmount(argument)),
+            },
+        };
+
+        // Amounts above 100% are clamped for the filters where they don't make sense
+        let clamped_amount = |default: f32| amount(default).and_then(|amount| Ok(amount.min(1.0)));
+
+        match name {
+            "blur" => Ok(Blur(match argument { Some(radius) => parse_pixel_value(radius)?, None => PixelValue::zero() })),
+            "brightness" => Ok(Brightness(amount(1.0)?)),
+            "contrast" => Ok(Contrast(amount(1.0)?)),
+            "grayscale" => Ok(Grayscale(clamped_amount(1.0)?)),
+            "hue-rotate" => Ok(HueRotate(match argument {
+                Some(angle) => parse_angle(angle).ok_or(CssFilterParseError::InvalidAngle(angle))?,
+                None => 0.0,
+            })),
+            "invert" => Ok(Invert(clamped_amount(1.0)?)),
+            "opacity" => Ok(Opacity(clamped_amount(1.0)?)),
+            "saturate" => Ok(Saturate(amount(1.0)?)),
+            "sepia" => Ok(Sepia(clamped_amount(1.0)?)),
+            _ => Err(CssFilterParseError::UnknownFunction(value)),
+        }
+    }).collect::<Result<Vec<FilterFunction>, _>>().and_then(|functions| Ok(StyleFilters(functions)))
+}
+
+/// The `transition` property, i.e. `transition: background-color 200ms ease-out, width 1s`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StyleTransitions(pub Vec<Transition>);
+
+/// A single transition of the `transition` property
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    /// The key of the transitioned property, i.e. `"background-color"`, or `"all"`
+    pub property: String,
+    pub duration: Duration,
+    pub timing_function: TimingFunction,
+    pub delay: Duration,
+}
+
+impl Transition {
+    /// Returns whether the transition applies to the property with the given key,
+    /// i.e. a transition of `padding` also applies to `padding-top`
+    pub fn applies_to(&self, key: &str) -> bool {
+        self.property == "all" || self.property == key || self.property == get_property_name(key)
+    }
+}
+
+/// The `animation` property, i.e. `animation: spin 1s linear infinite`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StyleAnimations(pub Vec<Animation>);
+
+/// A single animation of the `animation` property
+#[derive(Debug, Clone, PartialEq)]
+pub struct Animation {
+    /// The name of the `@keyframes` rule that is played
+    pub name: String,
+    pub duration: Duration,
+    /// The easing between two keyframes (not across the whole animation)
+    pub timing_function: TimingFunction,
+    pub delay: Duration,
+    pub iteration_count: AnimationIterationCount,
+    pub direction: AnimationDirection,
+    pub fill_mode: AnimationFillMode,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AnimationIterationCount {
+    Infinite,
+    Count(f32),
+}
+
+impl Default for AnimationIterationCount {
+    fn default() -> Self {
+        AnimationIterationCount::Count(1.0)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AnimationDirection {
+    Normal,
+    Reverse,
+    /// Every second iteration is played in reverse, starting with the second
+    Alternate,
+    /// Every second iteration is played in reverse, starting with the first
+    AlternateReverse,
+}
+
+impl Default for AnimationDirection {
+    fn default() -> Self {
+        AnimationDirection::Normal
+    }
+}
+
+/// Whether the values of the animation are applied before it starts
+/// (during the delay) and after it has ended
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AnimationFillMode {
+    None,
+    Forwards,
+    Backwards,
+    Both,
+}
+
+impl Default for AnimationFillMode {
+    fn default() -> Self {
+        AnimationFillMode::None
+    }
+}
+
+/// The easing curve of a transition or animation
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TimingFunction {
+    Linear,
+    Ease,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// `cubic-bezier(x1, y1, x2, y2)`, `x1` and `x2` are between 0 and 1
+    CubicBezier(f32, f32, f32, f32),
+}
+
+impl Default for TimingFunction {
+    fn default() -> Self {
+        TimingFunction::Ease
+    }
+}
+
+impl TimingFunction {
+
+    /// Maps the elapsed part of the duration (0.0 - 1.0) to the progress of the
+    /// transition, which can be outside of 0.0 - 1.0 for a `cubic-bezier()` that overshoots
+    pub fn evaluate(&self, t: f32) -> f32 {
+        let (x1, y1, x2, y2) = match *self {
+            TimingFunction::Linear => return t,
+            TimingFunction::Ease => (0.25, 0.1, 0.25, 1.0),
+            TimingFunction::EaseIn => (0.42, 0.0, 1.0, 1.0),
+            TimingFunction::EaseOut => (0.0, 0.0, 0.58, 1.0),
+            TimingFunction::EaseInOut => (0.42, 0.0, 0.58, 1.0),
+            TimingFunction::CubicBezier(x1, y1, x2, y2) => (x1, y1, x2, y2),
+        };
+
+        if t <= 0.0 {
+            return 0.0;
+        } else if t >= 1.0 {
+            return 1.0;
+        }
+
+        // One coordinate of the curve from (0, 0) to (1, 1), at the curve parameter `s`
+        fn bezier(p1: f32, p2: f32, s: f32) -> f32 {
+            let inv = 1.0 - s;
+            3.0 * inv * inv * s * p1 + 3.0 * inv * s * s * p2 + s * s * s
+        }
+
+        // x(s) is monotonic, since x1 and x2 are between 0 and 1, so the
+        // parameter for the x coordinate `t` can be found by bisection
+        let (mut low, mut high) = (0.0, 1.0);
+        for _ in 0..32 {
+            let mid = (low + high) / 2.0;
+            if bezier(x1, x2, mid) < t {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+
+        bezier(y1, y2, (low + high) / 2.0)
+    }
 }
 
-typed_pixel_value_parser!(parse_layout_width, LayoutWidth);
-typed_pixel_value_parser!(parse_layout_height, LayoutHeight);
-typed_pixel_value_parser!(parse_layout_min_height, LayoutMinHeight);
-typed_pixel_value_parser!(parse_layout_min_width, LayoutMinWidth);
-typed_pixel_value_parser!(parse_layout_max_width, LayoutMaxWidth);
-typed_pixel_value_parser!(parse_layout_max_height, LayoutMaxHeight);
+impl ToCssString for TimingFunction {
+    fn to_css_string(&self) -> String {
+        match *self {
+            TimingFunction::Linear => "linear".to_string(),
+            TimingFunction::Ease => "ease".to_string(),
+            TimingFunction::EaseIn => "ease-in".to_string(),
+            TimingFunction::EaseOut => "ease-out".to_string(),
+            TimingFunction::EaseInOut => "ease-in-out".to_string(),
+            TimingFunction::CubicBezier(x1, y1, x2, y2) => format!("cubic-bezier({}, {}, {}, {})", x1, y1, x2, y2),
+        }
+    }
+}
 
-fn parse_line_height(input: &str)
--> Result<LineHeight, PercentageParseError>
-{
-    parse_percentage_value(input).and_then(|e| Ok(LineHeight(e)))
+impl ToCssString for Duration {
+    fn to_css_string(&self) -> String {
+        format!("{}ms", self.as_secs() as f32 * 1000.0 + self.subsec_nanos() as f32 / 1_000_000.0)
+    }
 }
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
-pub struct FontSize(pub(crate) PixelValue);
+impl ToCssString for AnimationIterationCount {
+    fn to_css_string(&self) -> String {
+        match *self {
+            AnimationIterationCount::Infinite => "infinite".to_string(),
+            AnimationIterationCount::Count(count) => format!("{}", count),
+        }
+    }
+}
 
-typed_pixel_value_parser!(parse_css_font_size, FontSize);
+impl ToCssString for Transition {
+    fn to_css_string(&self) -> String {
+        format!("{} {} {} {}", self.property, self.duration.to_css_string(),
+            self.timing_function.to_css_string(), self.delay.to_css_string())
+    }
+}
 
-impl FontSize {
-    /// Creates the font size in pixel
-    pub fn px(value: f32) -> Self {
-        FontSize(PixelValue::from_metric(CssMetric::Px, value))
+impl ToCssString for Animation {
+    fn to_css_string(&self) -> String {
+        format!("{} {} {} {} {} {} {}", self.name, self.duration.to_css_string(),
+            self.timing_function.to_css_string(), self.delay.to_css_string(),
+            self.iteration_count.to_css_string(), self.direction.to_css_string(),
+            self.fill_mode.to_css_string())
     }
+}
 
-    /// Creates the font size in em
-    pub fn em(value: f32) -> Self {
-        FontSize(PixelValue::from_metric(CssMetric::Em, value))
+impl ToCssString for StyleTransitions {
+    fn to_css_string(&self) -> String {
+        if self.0.is_empty() {
+            return "none".to_string();
+        }
+        self.0.iter().map(|transition| transition.to_css_string()).collect::<Vec<String>>().join(", ")
     }
+}
 
-    /// Creates the font size in point (pt)
-    pub fn pt(value: f32) -> Self {
-        FontSize(PixelValue::from_metric(CssMetric::Pt, value))
+impl ToCssString for StyleAnimations {
+    fn to_css_string(&self) -> String {
+        if self.0.is_empty() {
+            return "none".to_string();
+        }
+        self.0.iter().map(|animation| animation.to_css_string()).collect::<Vec<String>>().join(", ")
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct FontFamily {
-    // parsed fonts, in order, i.e. "Webly Sleeky UI", "monospace", etc.
-    pub(crate) fonts: Vec<FontId>
+#[derive(Debug, Clone, PartialEq)]
+pub enum CssAnimationParseError<'a> {
+    /// A time without a unit or a negative time, i.e. `200` instead of `200ms`
+    InvalidTime(&'a str),
+    /// An unknown easing or a `cubic-bezier()` whose x coordinates are not between 0 and 1
+    InvalidTimingFunction(&'a str),
+    /// A negative iteration count, i.e. `-1`
+    InvalidIterationCount(&'a str),
+    /// An animation without the name of its `@keyframes`, i.e. `animation: 1s linear`
+    MissingAnimationName(&'a str),
+    /// A value that isn't expected at this position, i.e. a transition with two properties
+    UnexpectedValue(&'a str),
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Hash)]
-pub enum FontId {
-    BuiltinFont(&'static str),
-    ExternalFont(String),
+/// Splits the `input` at the characters that match the `separator`, but not at the
+/// ones in parentheses, i.e. not at the commas of `cubic-bezier(0.1, 0.7, 1.0, 0.1)`
+fn split_unnested<'a, F: Fn(char) -> bool>(input: &'a str, separator: F) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut depth = 0_usize;
+    let mut part_start = 0;
+
+    for (idx, c) in input.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            c if depth == 0 && separator(c) => {
+                parts.push(&input[part_start..idx]);
+                part_start = idx + c.len_utf8();
+            },
+            _ => { },
+        }
+    }
+
+    parts.push(&input[part_start..]);
+    parts
 }
 
-#[derive(Debug, PartialEq, Copy, Clone)]
-pub enum CssFontFamilyParseError<'a> {
-    InvalidFontFamily(&'a str),
-    UnrecognizedBuiltinFont(&'a str),
-    UnclosedQuotes(&'a str),
+/// Splits the `input` at the whitespace that isn't in parentheses, i.e. the values of
+/// a single transition (`width 1s ease-in`) or a border (`1px solid rgb(0, 0, 0)`)
+fn split_values<'a>(input: &'a str) -> Vec<&'a str> {
+    split_unnested(input, char::is_whitespace).into_iter().filter(|value| !value.is_empty()).collect()
 }
 
-impl<'a> From<UnclosedQuotesError<'a>> for CssFontFamilyParseError<'a> {
-    fn from(err: UnclosedQuotesError<'a>) -> Self {
-        CssFontFamilyParseError::UnclosedQuotes(err.0)
+// parses "200ms", "0.2s"
+fn parse_time<'a>(input: &'a str) -> Result<Duration, CssAnimationParseError<'a>> {
+    let (number, millis_per_unit) = if input.ends_with("ms") {
+        (&input[..input.len() - 2], 1.0)
+    } else if input.ends_with('s') {
+        (&input[..input.len() - 1], 1000.0)
+    } else {
+        return Err(CssAnimationParseError::InvalidTime(input));
+    };
+
+    let millis = number.parse::<f32>().map_err(|_| CssAnimationParseError::InvalidTime(input))? * millis_per_unit;
+    if millis < 0.0 || !millis.is_finite() {
+        return Err(CssAnimationParseError::InvalidTime(input));
     }
+
+    Ok(Duration::from_micros((millis * 1000.0) as u64))
 }
 
-// parses a "font-family" declaration, such as:
-//
-// "Webly Sleeky UI", monospace
-// 'Webly Sleeky Ui', monospace
-// sans-serif
-pub(crate) fn parse_css_font_family<'a>(input: &'a str) -> Result<FontFamily, CssFontFamilyParseError<'a>> {
-    let multiple_fonts = input.split(',');
-    let mut fonts = Vec::with_capacity(1);
+// parses "ease-out", "cubic-bezier(0.1, 0.7, 1.0, 0.1)"
+fn parse_timing_function<'a>(input: &'a str) -> Result<TimingFunction, CssAnimationParseError<'a>> {
+    match input {
+        "linear" => return Ok(TimingFunction::Linear),
+        "ease" => return Ok(TimingFunction::Ease),
+        "ease-in" => return Ok(TimingFunction::EaseIn),
+        "ease-out" => return Ok(TimingFunction::EaseOut),
+        "ease-in-out" => return Ok(TimingFunction::EaseInOut),
+        _ => { },
+    }
 
-    for font in multiple_fonts {
-        let font = font.trim();
+    if !input.starts_with("cubic-bezier(") || !input.ends_with(')') {
+        return Err(CssAnimationParseError::InvalidTimingFunction(input));
+    }
 
-        let mut double_quote_iter = font.splitn(2, '"');
-        double_quote_iter.next();
-        let mut single_quote_iter = font.splitn(2, '\'');
-        single_quote_iter.next();
+    let points = input["cubic-bezier(".len()..input.len() - 1].split(',')
+        .map(|point| point.trim().parse::<f32>())
+        .collect::<Result<Vec<f32>, ParseFloatError>>()
+        .map_err(|_| CssAnimationParseError::InvalidTimingFunction(input))?;
 
-        if double_quote_iter.next().is_some() || single_quote_iter.next().is_some() {
-            let stripped_font = strip_quotes(font)?;
-            fonts.push(FontId::ExternalFont(stripped_font.0.into()));
-        } else {
-            match font {
-                "serif"      => fonts.push(FontId::BuiltinFont("serif")),
-                "sans-serif" => fonts.push(FontId::BuiltinFont("sans-serif")),
-                "monospace"  => fonts.push(FontId::BuiltinFont("monospace")),
-                "fantasy"    => fonts.push(FontId::BuiltinFont("fantasy")),
-                "cursive"    => fonts.push(FontId::BuiltinFont("cursive")),
-                _ => return Err(CssFontFamilyParseError::UnrecognizedBuiltinFont(font)),
-            }
-        }
+    let is_valid_x = |x: f32| x >= 0.0 && x <= 1.0;
+    if points.len() != 4 || !is_valid_x(points[0]) || !is_valid_x(points[2]) {
+        return Err(CssAnimationParseError::InvalidTimingFunction(input));
     }
 
-    Ok(FontFamily {
-        fonts: fonts,
-    })
+    Ok(TimingFunction::CubicBezier(points[0], points[1], points[2], points[3]))
 }
 
-multi_type_parser!(parse_layout_direction, LayoutDirection,
-                    ["row", Horizontal],
-                    ["column", Vertical]);
+/// Returns whether the value is a number with an optional unit, i.e. `-1s` or `200`
+fn is_number(input: &str) -> bool {
+    input.trim_right_matches(char::is_alphabetic).parse::<f32>().is_ok()
+}
 
-multi_type_parser!(parse_layout_wrap, LayoutWrap,
-                    ["wrap", Wrap],
-                    ["nowrap", NoWrap]);
+/// Returns whether the value is a name (of a property or a `@keyframes` rule)
+fn is_identifier(input: &str) -> bool {
+    input.chars().next().map_or(false, |c| !c.is_numeric()) &&
+    input.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
 
-multi_type_parser!(parse_layout_justify_content, LayoutJustifyContent,
-                    ["start", Start],
-                    ["end", End],
-                    ["center", Center],
-                    ["space-between", SpaceBetween],
-                    ["space-around", SpaceAround]);
+/// Sets the value, but only if it hasn't been set before (i.e. a transition with two properties)
+fn set_once<'a, T>(value: &mut Option<T>, new_value: T, input: &'a str) -> Result<(), CssAnimationParseError<'a>> {
+    if value.is_some() {
+        return Err(CssAnimationParseError::UnexpectedValue(input));
+    }
+    *value = Some(new_value);
+    Ok(())
+}
 
-multi_type_parser!(parse_layout_align_items, LayoutAlignItems,
-                    ["stretch", Stretch],
-                    ["start", Start],
-                    ["end", End],
-                    ["center", Center]);
+// parses "background-color 200ms ease-out, width 1s 100ms"
+fn parse_css_transition<'a>(input: &'a str) -> Result<StyleTransitions, CssAnimationParseError<'a>> {
+    if input == "none" {
+        return Ok(StyleTransitions::default());
+    }
 
-multi_type_parser!(parse_layout_align_content, LayoutAlignContent,
-                    ["stretch", Stretch],
-                    ["start", Start],
-                    ["end", End],
-                    ["center", Center],
-                    ["space-between", SpaceBetween],
-                    ["space-around", SpaceAround]);
+    split_unnested(input, |c| c == ',').into_iter().map(|transition| {
+        let mut property = None;
+        let mut duration = None;
+        let mut delay = None;
+        let mut timing_function = None;
 
-multi_type_parser!(parse_shape, Shape,
-                    ["circle", Circle],
-                    ["ellipse", Ellipse]);
+        for value in split_values(transition) {
+            if let Ok(time) = parse_time(value) {
+                if duration.is_none() {
+                    duration = Some(time);
+                } else {
+                    set_once(&mut delay, time, value)?;
+                }
+            } else if let Ok(function) = parse_timing_function(value) {
+                set_once(&mut timing_function, function, value)?;
+            } else if value.starts_with("cubic-bezier(") {
+                return Err(CssAnimationParseError::InvalidTimingFunction(value));
+            } else if is_number(value) {
+                return Err(CssAnimationParseError::InvalidTime(value));
+            } else if is_identifier(value) {
+                set_once(&mut property, value.to_string(), value)?;
+            } else {
+                return Err(CssAnimationParseError::UnexpectedValue(value));
+            }
+        }
 
-multi_type_parser!(parse_layout_text_overflow, TextOverflowBehaviourInner,
-                    ["auto", Auto],
-                    ["scroll", Scroll],
-                    ["visible", Visible],
-                    ["hidden", Hidden]);
+        Ok(Transition {
+            property: property.unwrap_or_else(|| "all".to_string()),
+            duration: duration.unwrap_or_default(),
+            timing_function: timing_function.unwrap_or_default(),
+            delay: delay.unwrap_or_default(),
+        })
+    }).collect::<Result<Vec<Transition>, _>>().and_then(|transitions| Ok(StyleTransitions(transitions)))
+}
 
-multi_type_parser!(parse_layout_text_align, TextAlignmentHorz,
-                    ["center", Center],
-                    ["left", Left],
-                    ["right", Right]);
+// parses "spin 1s linear infinite, fade 200ms ease-out 1s 2 alternate forwards"
+fn parse_css_animation<'a>(input: &'a str) -> Result<StyleAnimations, CssAnimationParseError<'a>> {
+    if input == "none" {
+        return Ok(StyleAnimations::default());
+    }
+
+    split_unnested(input, |c| c == ',').into_iter().map(|animation| {
+        let mut name = None;
+        let mut duration = None;
+        let mut delay = None;
+        let mut timing_function = None;
+        let mut iteration_count = None;
+        let mut direction = None;
+        let mut fill_mode = None;
+
+        for value in split_values(animation) {
+            if let Ok(time) = parse_time(value) {
+                if duration.is_none() {
+                    duration = Some(time);
+                } else {
+                    set_once(&mut delay, time, value)?;
+                }
+            } else if let Ok(function) = parse_timing_function(value) {
+                set_once(&mut timing_function, function, value)?;
+            } else if value.starts_with("cubic-bezier(") {
+                return Err(CssAnimationParseError::InvalidTimingFunction(value));
+            } else if value == "infinite" {
+                set_once(&mut iteration_count, AnimationIterationCount::Infinite, value)?;
+            } else if let Ok(count) = value.parse::<f32>() {
+                if count < 0.0 || !count.is_finite() {
+                    return Err(CssAnimationParseError::InvalidIterationCount(value));
+                }
+                set_once(&mut iteration_count, AnimationIterationCount::Count(count), value)?;
+            } else if is_number(value) {
+                return Err(CssAnimationParseError::InvalidTime(value));
+            } else if let Ok(d) = parse_animation_direction(value) {
+                set_once(&mut direction, d, value)?;
+            } else if let Ok(f) = parse_animation_fill_mode(value) {
+                set_once(&mut fill_mode, f, value)?;
+            } else if is_identifier(value) {
+                set_once(&mut name, value.to_string(), value)?;
+            } else {
+                return Err(CssAnimationParseError::UnexpectedValue(value));
+            }
+        }
+
+        Ok(Animation {
+            name: name.ok_or(CssAnimationParseError::MissingAnimationName(animation.trim()))?,
+            duration: duration.unwrap_or_default(),
+            timing_function: timing_function.unwrap_or_default(),
+            delay: delay.unwrap_or_default(),
+            iteration_count: iteration_count.unwrap_or_default(),
+            direction: direction.unwrap_or_default(),
+            fill_mode: fill_mode.unwrap_or_default(),
+        })
+    }).collect::<Result<Vec<Animation>, _>>().and_then(|animations| Ok(StyleAnimations(animations)))
+}
+
+multi_type_parser!(parse_animation_direction, AnimationDirection,
+                    ["normal", Normal],
+                    ["reverse", Reverse],
+                    ["alternate", Alternate],
+                    ["alternate-reverse", AlternateReverse]);
+
+multi_type_parser!(parse_animation_fill_mode, AnimationFillMode,
+                    ["none", None],
+                    ["forwards", Forwards],
+                    ["backwards", Backwards],
+                    ["both", Both]);
 
 #[cfg(test)]
 mod css_tests {
@@ -1897,14 +4955,43 @@ mod css_tests {
         })));
     }
 
+    #[test]
+    fn test_parse_box_shadows() {
+        assert_eq!(parse_css_box_shadows("none"), Ok(StyleBoxShadows(Vec::new())));
+        assert_eq!(parse_css_box_shadows("0px 3px 1px -2px rgba(0, 0, 0, 0.2), inset #ff0000 0px 2px"), Ok(StyleBoxShadows(vec![
+            BoxShadowPreDisplayItem {
+                offset: LayoutVector2D::new(0.0, 3.0),
+                color: ColorU { r: 0, g: 0, b: 0, a: 51 }.into(),
+                blur_radius: 1.0,
+                spread_radius: -2.0,
+                clip_mode: BoxShadowClipMode::Outset,
+            },
+            BoxShadowPreDisplayItem {
+                offset: LayoutVector2D::new(0.0, 2.0),
+                color: ColorU { r: 255, g: 0, b: 0, a: 255 }.into(),
+                blur_radius: 0.0,
+                spread_radius: 0.0,
+                clip_mode: BoxShadowClipMode::Inset,
+            },
+        ])));
+        assert_eq!(parse_css_box_shadow("5px 10px 5px 10px inset").unwrap().unwrap().spread_radius, 10.0);
+        assert!(parse_css_box_shadows("5px 10px, none").is_err());
+        assert!(parse_css_box_shadows("5px 10px,").is_err());
+        assert!(parse_css_box_shadow("5px").is_err());
+        assert!(parse_css_box_shadow("1px 2px 3px 4px 5px").is_err());
+        assert!(parse_css_box_shadow("1px 2px red blue").is_err());
+    }
+
     #[test]
     fn test_parse_css_border_1() {
-        assert_eq!(parse_css_border("5px solid red"), Ok((BorderWidths {
+        let border = StyleBorder::uniform(parse_css_border("5px solid red").unwrap());
+        assert_eq!(border.get_widths(&LengthContext::default()), BorderWidths {
             top: 5.0,
             bottom: 5.0,
             left: 5.0,
             right: 5.0,
-        }, BorderDetails::Normal(NormalBorder {
+        });
+        assert_eq!(border.get_normal_border(), NormalBorder {
             left: BorderSide {
                 color: ColorF { r: 1.0, g: 0.0, b: 0.0, a: 1.0 },
                 style: BorderStyle::Solid,
@@ -1922,17 +5009,19 @@ mod css_tests {
                 style: BorderStyle::Solid,
             },
             radius: BorderRadius::zero(),
-        }))));
+        });
     }
 
     #[test]
     fn test_parse_css_border_2() {
-        assert_eq!(parse_css_border("double"), Ok((BorderWidths {
+        let border = StyleBorder::uniform(parse_css_border("double").unwrap());
+        assert_eq!(border.get_widths(&LengthContext::default()), BorderWidths {
             top: 1.0,
             bottom: 1.0,
             left: 1.0,
             right: 1.0,
-        }, BorderDetails::Normal(NormalBorder {
+        });
+        assert_eq!(border.get_normal_border(), NormalBorder {
             left: BorderSide {
                 color: ColorF { r: 0.0, g: 0.0, b: 0.0, a: 1.0 },
                 style: BorderStyle::Double,
@@ -1950,7 +5039,52 @@ mod css_tests {
                 style: BorderStyle::Double,
             },
             radius: BorderRadius::zero(),
-        }))));
+        });
+    }
+
+    #[test]
+    fn test_parse_border_longhands() {
+        let parse = |key, value| match ParsedCssProperty::from_kv(key, value) {
+            Ok(ParsedCssProperty::Border(border)) => border,
+            other => panic!("{:?}", other),
+        };
+
+        let mut border = parse("border", "1px solid red");
+        border.merge(&parse("border-left", "#0000ff dashed 3px"));
+        border.merge(&parse("border-width", "2px 4px"));
+        border.merge(&parse("border-style", "none groove"));
+
+        // sides with the style `none` have no width
+        assert_eq!(border.get_widths(&LengthContext::default()), BorderWidths { top: 0.0, right: 4.0, bottom: 0.0, left: 4.0 });
+        let normal_border = border.get_normal_border();
+        assert_eq!(normal_border.right, BorderSide { color: ColorU { r: 255, g: 0, b: 0, a: 255 }.into(), style: BorderStyle::Groove });
+        assert_eq!(normal_border.left, BorderSide { color: ColorU { r: 0, g: 0, b: 255, a: 255 }.into(), style: BorderStyle::Groove });
+
+        assert_eq!(parse("border-top", "dotted").get_key(), "border-top");
+        assert_eq!(parse("border-color", "red blue").get_key(), "border-color");
+        assert_eq!(parse("border-width", "5px").get_widths(&LengthContext::default()), BorderWidths { top: 0.0, right: 0.0, bottom: 0.0, left: 0.0 });
+        assert_eq!(get_property_name("border-top"), "border");
+        assert_eq!(get_property_name("border-radius"), "border-radius");
+
+        assert!(parse_css_border("1px 2px solid").is_err());
+        assert!(parse_css_border("solid soild").is_err());
+        assert!(parse_css_border_width("1px 2px 3px 4px 5px").is_err());
+    }
+
+    #[test]
+    fn test_parse_border_image() {
+        assert_eq!(parse_css_border_image("image(\"frame\") 10 20 fill round"), Ok(Some(StyleBorderImage {
+            source: CssImageId(String::from("frame")),
+            slice: SideOffsets2D::new(10, 20, 10, 20),
+            fill: true,
+            repeat_horizontal: RepeatMode::Round,
+            repeat_vertical: RepeatMode::Round,
+        })));
+        assert_eq!(parse_css_border_image("image(\"frame\") 1 2 3 4 repeat space").unwrap().unwrap().slice, SideOffsets2D::new(1, 2, 3, 4));
+        assert_eq!(parse_css_border_image("none"), Ok(None));
+        assert_eq!(parse_css_border_image("image(\"frame\") fill"), Err(CssBorderImageParseError::InvalidSlice("image(\"frame\") fill")));
+        assert!(parse_css_border_image("linear-gradient(red, blue) 10").is_err());
+        assert!(parse_css_border_image("image(\"frame\") 10 round stretch space").is_err());
     }
 
     #[test]
@@ -1960,11 +5094,11 @@ mod css_tests {
                 direction: Direction::FromTo(DirectionCorner::Top, DirectionCorner::Bottom),
                 extend_mode: ExtendMode::Clamp,
                 stops: vec![GradientStopPre {
-                    offset: Some(0.0),
+                    offset: None,
                     color: ColorF { r: 1.0, g: 0.0, b: 0.0, a: 1.0 },
                 },
                 GradientStopPre {
-                    offset: Some(1.0),
+                    offset: None,
                     color: ColorF { r: 1.0, g: 1.0, b: 0.0, a: 1.0 },
                 }],
             })));
@@ -1977,19 +5111,19 @@ mod css_tests {
                 direction: Direction::FromTo(DirectionCorner::Top, DirectionCorner::Bottom),
                 extend_mode: ExtendMode::Clamp,
                 stops: vec![GradientStopPre {
-                    offset: Some(0.0),
+                    offset: None,
                     color: ColorF { r: 1.0, g: 0.0, b: 0.0, a: 1.0 },
                 },
                 GradientStopPre {
-                    offset: Some(0.33333334),
+                    offset: None,
                     color: ColorF { r: 0.0, g: 1.0, b: 0.0, a: 1.0 },
                 },
                 GradientStopPre {
-                    offset: Some(0.66666667),
+                    offset: None,
                     color: ColorF { r: 0.0, g: 0.0, b: 1.0, a: 1.0 },
                 },
                 GradientStopPre {
-                    offset: Some(1.0),
+                    offset: None,
                     color: ColorF { r: 1.0, g: 1.0, b: 0.0, a: 1.0 },
                 }],
         })));
@@ -2003,15 +5137,15 @@ mod css_tests {
                 extend_mode: ExtendMode::Repeat,
                 stops: vec![
                 GradientStopPre {
-                    offset: Some(0.0),
+                    offset: None,
                     color: ColorF { r: 0.0, g: 0.0, b: 1.0, a: 1.0 },
                 },
                 GradientStopPre {
-                    offset: Some(0.5),
+                    offset: None,
                     color: ColorF { r: 1.0, g: 1.0, b: 0.0, a: 1.0 },
                 },
                 GradientStopPre {
-                    offset: Some(1.0),
+                    offset: None,
                     color: ColorF { r: 0.0, g: 1.0, b: 0.0, a: 1.0 },
                 }],
         })));
@@ -2024,11 +5158,11 @@ mod css_tests {
                 direction: Direction::FromTo(DirectionCorner::TopLeft, DirectionCorner::BottomRight),
                 extend_mode: ExtendMode::Clamp,
                 stops: vec![GradientStopPre {
-                    offset: Some(0.0),
+                    offset: None,
                     color: ColorF { r: 1.0, g: 0.0, b: 0.0, a: 1.0 },
                 },
                 GradientStopPre {
-                    offset: Some(1.0),
+                    offset: None,
                     color: ColorF { r: 1.0, g: 1.0, b: 0.0, a: 1.0 },
                 }],
             })));
@@ -2039,51 +5173,104 @@ mod css_tests {
         assert_eq!(parse_css_background("radial-gradient(circle, lime, blue, yellow)"),
             Ok(Background::RadialGradient(RadialGradientPreInfo {
                 shape: Shape::Circle,
+                size: RadialGradientSize::FarthestCorner,
+                position: BackgroundPosition::center(),
                 extend_mode: ExtendMode::Clamp,
                 stops: vec![
                 GradientStopPre {
-                    offset: Some(0.0),
+                    offset: None,
                     color: ColorF { r: 0.0, g: 1.0, b: 0.0, a: 1.0 },
                 },
                 GradientStopPre {
-                    offset: Some(0.5),
+                    offset: None,
                     color: ColorF { r: 0.0, g: 0.0, b: 1.0, a: 1.0 },
                 },
                 GradientStopPre {
-                    offset: Some(1.0),
+                    offset: None,
                     color: ColorF { r: 1.0, g: 1.0, b: 0.0, a: 1.0 },
                 }],
         })));
     }
 
-    // This test currently fails, but it's not that important to fix right now
-    /*
     #[test]
     fn test_parse_radial_gradient_2() {
-        assert_eq!(parse_css_background("repeating-radial-gradient(circle, red 10%, blue 50%, lime, yellow)"),
-            Ok(ParsedGradient::RadialGradient(RadialGradientPreInfo {
-                shape: Shape::Circle,
-                extend_mode: ExtendMode::Repeat,
-                stops: vec![
-                GradientStopPre {
-                    offset: Some(0.1),
-                    color: ColorF { r: 1.0, g: 0.0, b: 0.0, a: 1.0 },
-                },
-                GradientStopPre {
-                    offset: Some(0.5),
-                    color: ColorF { r: 0.0, g: 0.0, b: 1.0, a: 1.0 },
-                },
-                GradientStopPre {
-                    offset: Some(0.75),
-                    color: ColorF { r: 0.0, g: 1.0, b: 0.0, a: 1.0 },
-                },
-                GradientStopPre {
-                    offset: Some(1.0),
-                    color: ColorF { r: 1.0, g: 1.0, b: 0.0, a: 1.0 },
-                }],
-        })));
+        let px = |value| PixelValue::from_metric(CssMetric::Px, value);
+        let percent = |value| PixelValue::from_metric(CssMetric::Percent, value);
+        let geometry = |input| parse_radial_gradient_geometry(input).unwrap();
+
+        assert_eq!(geometry("circle"), (Shape::Circle, RadialGradientSize::FarthestCorner, BackgroundPosition::center()));
+        assert_eq!(geometry("closest-side at 30% 20%"), (Shape::Ellipse, RadialGradientSize::ClosestSide,
+                   BackgroundPosition { horizontal: percent(30.0), vertical: percent(20.0) }));
+        assert_eq!(geometry("20px at top"), (Shape::Circle, RadialGradientSize::Exact(px(20.0), px(20.0)),
+                   BackgroundPosition { horizontal: percent(50.0), vertical: percent(0.0) }));
+        assert_eq!(geometry("ellipse 20px 50%").1, RadialGradientSize::Exact(px(20.0), percent(50.0)));
+        assert!(parse_radial_gradient_geometry("circle 20px 30px").is_err());
+        assert!(parse_radial_gradient_geometry("closest-side 20px").is_err());
+        assert!(parse_radial_gradient_geometry("circle at").is_err());
+
+        // the first argument is a stop if it isn't a shape, size or position
+        match parse_css_background("repeating-radial-gradient(circle at 30% 20%, red 10%, blue 50%, lime, yellow)") {
+            Ok(Background::RadialGradient(gradient)) => {
+                assert_eq!(gradient.extend_mode, ExtendMode::Repeat);
+                assert_eq!(gradient.stops.len(), 4);
+                assert_eq!(gradient.stops[0].offset, Some(percent(10.0)));
+            },
+            other => panic!("expected a radial gradient, got {:?}", other),
+        }
+        match parse_css_background("radial-gradient(red, blue)") {
+            Ok(Background::RadialGradient(gradient)) => assert_eq!(gradient.stops.len(), 2),
+            other => panic!("expected a radial gradient, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_radial_gradient_center_and_radii() {
+        let context = LengthContext::default();
+        let size = LayoutSize::new(200.0, 100.0);
+        let gradient = |input| match parse_css_background(input) {
+            Ok(Background::RadialGradient(gradient)) => gradient.get_center_and_radii(size, &context),
+            other => panic!("expected a radial gradient, got {:?}", other),
+        };
+
+        assert_eq!(gradient("radial-gradient(circle closest-side at 50px 50%, red, blue)"),
+                   (LayoutPoint::new(50.0, 50.0), LayoutSize::new(50.0, 50.0)));
+        assert_eq!(gradient("radial-gradient(ellipse farthest-side at 50px 50%, red, blue)"),
+                   (LayoutPoint::new(50.0, 50.0), LayoutSize::new(150.0, 50.0)));
+        assert_eq!(gradient("radial-gradient(circle farthest-corner at left top, red, blue)"),
+                   (LayoutPoint::new(0.0, 0.0), LayoutSize::new(200.0_f32.hypot(100.0), 200.0_f32.hypot(100.0))));
+        assert_eq!(gradient("radial-gradient(10px 50%, red, blue)"),
+                   (LayoutPoint::new(100.0, 50.0), LayoutSize::new(10.0, 50.0)));
+    }
+
+    #[test]
+    fn test_linear_gradient_angle_points() {
+        let rect = LayoutRect::new(LayoutPoint::zero(), LayoutSize::new(200.0, 100.0));
+        let points = |direction: &str| {
+            let (start, end) = parse_direction(direction).unwrap().to_points(&rect);
+            ((start.x.round(), start.y.round()), (end.x.round(), end.y.round()))
+        };
+
+        assert_eq!(points("90deg"), ((0.0, 50.0), (200.0, 50.0)));
+        assert_eq!(points("0.5turn"), ((100.0, 0.0), (100.0, 100.0)));
+        // the corners get the color of the first and the last stop
+        assert_eq!(points("45deg"), ((25.0, 125.0), (175.0, -25.0)));
+        assert!(parse_direction("45deg 10deg").is_err());
+    }
+
+    #[test]
+    fn test_resolve_gradient_stops() {
+        let context = LengthContext::default();
+        let offsets = |input, length| match parse_css_background(input) {
+            Ok(Background::LinearGradient(gradient)) => resolve_gradient_stops(&gradient.stops, length, &context)
+                .into_iter().map(|stop| stop.offset).collect::<Vec<f32>>(),
+            other => panic!("expected a linear gradient, got {:?}", other),
+        };
+
+        assert_eq!(offsets("linear-gradient(red, lime, blue, yellow)", 100.0), vec![0.0, 1.0 / 3.0, 2.0 / 3.0, 1.0]);
+        assert_eq!(offsets("linear-gradient(red 10%, blue 50%, lime, yellow)", 100.0), vec![0.1, 0.5, 0.75, 1.0]);
+        assert_eq!(offsets("linear-gradient(red 20px, blue 50%, lime 10px)", 200.0), vec![0.1, 0.5, 0.5]);
+        assert_eq!(offsets("linear-gradient(red, blue 150%)", 100.0), vec![0.0, 1.5]);
     }
-    */
 
     #[test]
     fn test_parse_css_color_1() {
@@ -2100,14 +5287,48 @@ mod css_tests {
         assert_eq!(parse_css_color("#EEE"), Ok(ColorU { r: 238, g: 238, b: 238, a: 255 }));
     }
 
+    #[test]
+    fn test_parse_css_color_functions() {
+        assert_eq!(parse_css_color("rgb(255, 128, 0)"), Ok(ColorU { r: 255, g: 128, b: 0, a: 255 }));
+        assert_eq!(parse_css_color("rgba(100%, 50%, 0%, 0.5)"), Ok(ColorU { r: 255, g: 128, b: 0, a: 128 }));
+        assert_eq!(parse_css_color("rgb(255 128 0 / 25%)"), Ok(ColorU { r: 255, g: 128, b: 0, a: 64 }));
+        // out of range values are clamped
+        assert_eq!(parse_css_color("rgba(300, -10, 0, 2)"), Ok(ColorU { r: 255, g: 0, b: 0, a: 255 }));
+        assert_eq!(parse_css_color("hsl(120, 100%, 50%)"), Ok(ColorU { r: 0, g: 255, b: 0, a: 255 }));
+        assert_eq!(parse_css_color("hsla(0.5turn, 100%, 25%, 0.5)"), Ok(ColorU { r: 0, g: 128, b: 128, a: 128 }));
+        assert_eq!(parse_css_color("hsl(-120deg 50% 50% / 1)"), Ok(ColorU { r: 64, g: 64, b: 191, a: 255 }));
+        assert_eq!(parse_css_color("hsl(0, 0%, 100%)"), Ok(ColorU { r: 255, g: 255, b: 255, a: 255 }));
+
+        assert_eq!(parse_css_color("#F0F8"), Ok(ColorU { r: 255, g: 0, b: 255, a: 136 }));
+        assert_eq!(parse_css_color("transparent"), Ok(ColorU { r: 0, g: 0, b: 0, a: 0 }));
+
+        assert_eq!(parse_css_color("#F0F8F"), Err(CssColorParseError::InvalidHexLength("F0F8F")));
+        assert_eq!(parse_css_color("#F0G"), Err(CssColorParseError::InvalidColorComponent(b'G')));
+        assert_eq!(parse_css_color("rgb(255, 0, 0"), Err(CssColorParseError::UnclosedColorFunction("rgb(255, 0, 0")));
+        assert_eq!(parse_css_color("rgb(255, 0)"), Err(CssColorParseError::WrongNumberOfComponents("rgb(255, 0)")));
+        assert_eq!(parse_css_color("rgb(red, 0, 0)"), Err(CssColorParseError::InvalidComponent("red")));
+        assert_eq!(parse_css_color("hsl(0, 1, 50%)"), Err(CssColorParseError::ExpectedPercentage("1")));
+        assert_eq!(parse_css_color("hsl(blue, 100%, 50%)"), Err(CssColorParseError::InvalidComponent("blue")));
+        assert_eq!(parse_css_color("lab(50% 0 0)"), Err(CssColorParseError::InvalidColor("lab(50% 0 0)")));
+        assert_eq!(parse_css_color("currentColor"), Err(CssColorParseError::UnresolvedCurrentColor("currentColor")));
+
+        // the functions can be used in any property that contains a color
+        assert_eq!(parse_css_border("2px dashed rgba(0, 0, 0, 0.5)").unwrap().color,
+                   parse_css_border("2px dashed #00000080").unwrap().color);
+        assert_eq!(parse_css_box_shadow("0px 1px 3px hsla(0, 0%, 0%, 0.5) inset"),
+                   parse_css_box_shadow("0px 1px 3px #00000080 inset"));
+        assert_eq!(parse_css_background("linear-gradient(rgb(255, 0, 0), hsl(240, 100%, 50%) 50%)"),
+                   parse_css_background("linear-gradient(red, blue 50%)"));
+    }
+
     #[test]
     fn test_parse_pixel_value_1() {
-        assert_eq!(parse_pixel_value("15px"), Ok(PixelValue { metric: CssMetric::Px, number: 15000 }));
+        assert_eq!(parse_pixel_value("15px"), Ok(PixelValue::from_metric(CssMetric::Px, 15.0)));
     }
 
     #[test]
     fn test_parse_pixel_value_2() {
-        assert_eq!(parse_pixel_value("1.2em"), Ok(PixelValue { metric: CssMetric::Em, number: 1200 }));
+        assert_eq!(parse_pixel_value("1.2em"), Ok(PixelValue::from_metric(CssMetric::Em, 1.2)));
     }
 
     #[test]
@@ -2115,6 +5336,29 @@ mod css_tests {
         assert_eq!(parse_pixel_value("aslkfdjasdflk"), Err(PixelParseError::InvalidComponent("aslkfdjasdflk")));
     }
 
+    #[test]
+    fn test_parse_relative_pixel_values() {
+        let context = LengthContext { font_size: 20.0, root_font_size: 10.0, viewport: LayoutSize::new(800.0, 600.0) };
+        let resolve = |input| parse_pixel_value(input).unwrap().resolve(&context, 200.0);
+
+        assert_eq!(resolve("50%"), 100.0);
+        assert_eq!(resolve("2em"), 40.0);
+        assert_eq!(resolve("2rem"), 20.0);
+        assert_eq!(resolve("10vw"), 80.0);
+        assert_eq!(resolve("10vh"), 60.0);
+        assert_eq!(resolve("calc(100% - 2rem)"), 180.0);
+        assert_eq!(resolve("calc(50% + 2 * (10px - 1em) / 2)"), 90.0);
+        assert_eq!(resolve("calc(-5px + calc(1em * 2))"), 35.0);
+
+        assert_eq!(parse_pixel_value("calc(5px * 5px)"), Err(PixelParseError::InvalidCalc("calc(5px * 5px)")));
+        assert_eq!(parse_pixel_value("calc(5px + 5)"), Err(PixelParseError::InvalidCalc("calc(5px + 5)")));
+        assert_eq!(parse_pixel_value("calc(5px +)"), Err(PixelParseError::InvalidCalc("calc(5px +)")));
+        assert_eq!(parse_pixel_value("calc(5px / 0)"), Err(PixelParseError::InvalidCalc("calc(5px / 0)")));
+        assert_eq!(parse_pixel_value("calc((5px)"), Err(PixelParseError::InvalidCalc("calc((5px)")));
+
+        assert_eq!(parse_pixel_value("calc(100% - 2rem)").unwrap().to_css_string(), "calc(-2rem + 100%)");
+    }
+
     #[test]
     fn test_parse_css_border_radius_1() {
         assert_eq!(parse_css_border_radius("15px"), Ok(BorderRadius::uniform(15.0)));
@@ -2175,4 +5419,419 @@ mod css_tests {
             CssImageId(String::from("Cat 01"))
         )));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_background_layers() {
+        let px = |value| PixelValue::from_metric(CssMetric::Px, value);
+        let percent = |value| PixelValue::from_metric(CssMetric::Percent, value);
+
+        assert_eq!(parse_css_backgrounds("image(\"Cat 01\"), linear-gradient(red, blue)").unwrap().0.len(), 2);
+        assert_eq!(parse_css_backgrounds("none"), Ok(StyleBackgrounds(Vec::new())));
+        assert!(parse_css_backgrounds("image(\"Cat 01\"),").is_err());
+
+        assert_eq!(parse_css_background_size("cover, 50%, auto 10px"), Ok(StyleBackgroundSizes(vec![
+            BackgroundSize::Cover,
+            BackgroundSize::Exact(Some(percent(50.0)), None),
+            BackgroundSize::Exact(None, Some(px(10.0))),
+        ])));
+        assert_eq!(parse_css_background_size("cover contain"), Err(CssBackgroundParseError::InvalidBackgroundSize("cover contain")));
+
+        assert_eq!(parse_css_background_position("center, top, right 10px, bottom left"), Ok(StyleBackgroundPositions(vec![
+            BackgroundPosition { horizontal: percent(50.0), vertical: percent(50.0) },
+            BackgroundPosition { horizontal: percent(50.0), vertical: percent(0.0) },
+            BackgroundPosition { horizontal: percent(100.0), vertical: px(10.0) },
+            BackgroundPosition { horizontal: percent(0.0), vertical: percent(100.0) },
+        ])));
+        assert_eq!(parse_css_background_position("left right"), Err(CssBackgroundParseError::InvalidBackgroundPosition("left right")));
+
+        assert_eq!(parse_css_background_repeat("no-repeat, repeat-x"), Ok(StyleBackgroundRepeats(vec![
+            BackgroundRepeat::NoRepeat,
+            BackgroundRepeat::RepeatX,
+        ])));
+        assert_eq!(parse_css_background_repeat("space"), Err(CssBackgroundParseError::InvalidBackgroundRepeat("space")));
+    }
+
+    #[test]
+    fn test_parse_padding_and_margin() {
+        let px = |value| Some(PixelValue::from_metric(CssMetric::Px, value));
+        let sides = |top, right, bottom, left| LayoutSideOffsets { top: px(top), right: px(right), bottom: px(bottom), left: px(left) };
+
+        assert_eq!(parse_layout_side_offsets("5px"), Ok(sides(5.0, 5.0, 5.0, 5.0)));
+        assert_eq!(parse_layout_side_offsets("5px 10px"), Ok(sides(5.0, 10.0, 5.0, 10.0)));
+        assert_eq!(parse_layout_side_offsets("5px 10px 15px"), Ok(sides(5.0, 10.0, 15.0, 10.0)));
+        assert_eq!(parse_layout_side_offsets("5px 10px 15px 20px"), Ok(sides(5.0, 10.0, 15.0, 20.0)));
+        assert_eq!(parse_layout_side_offsets("1px 2px 3px 4px 5px"), Err(CssSideOffsetsParseError::TooManyValues("1px 2px 3px 4px 5px")));
+
+        assert_eq!(ParsedCssProperty::from_kv("margin-left", "2em"), Ok(ParsedCssProperty::Margin(LayoutMargin(LayoutSideOffsets {
+            left: Some(PixelValue::from_metric(CssMetric::Em, 2.0)),
+            .. Default::default()
+        }))));
+
+        let mut padding = sides(5.0, 5.0, 5.0, 5.0);
+        padding.merge(&LayoutSideOffsets::top(PixelValue::from_metric(CssMetric::Px, 1.0)));
+        assert_eq!(padding, sides(1.0, 5.0, 5.0, 5.0));
+    }
+
+    #[test]
+    fn test_inherited_properties() {
+        assert_eq!(get_property_name("padding-top"), "padding");
+        assert_eq!(get_property_name("overflow"), "overflow");
+        assert_eq!(get_property_name("font-size"), "font-size");
+        assert!(ParsedCssProperty::from_kv("color", "red").unwrap().is_inherited());
+        assert!(ParsedCssProperty::from_kv("font-family", "serif").unwrap().is_inherited());
+        assert!(!ParsedCssProperty::from_kv("background-color", "red").unwrap().is_inherited());
+        assert!(!ParsedCssProperty::from_kv("padding-left", "5px").unwrap().is_inherited());
+    }
+
+    #[test]
+    fn test_to_css_string() {
+        let to_css_string = |key, value| ParsedCssProperty::from_kv(key, value).unwrap().to_css_string();
+        assert_eq!(to_css_string("width", "5.5px"), "5.5px");
+        assert_eq!(to_css_string("font-size", "12pt"), "12pt");
+        assert_eq!(to_css_string("color", "red"), "#ff0000");
+        assert_eq!(to_css_string("background-color", "#0000ff80"), "#0000ff80");
+        assert_eq!(to_css_string("border-radius", "5px"), "5px");
+        assert_eq!(to_css_string("border-radius", "1px 2px"), "1px 2px 1px 2px");
+        assert_eq!(to_css_string("border", "2px dashed #ff0000"), "2px dashed #ff0000");
+        assert_eq!(to_css_string("border-right", "red ridge"), "1px ridge #ff0000");
+        assert_eq!(to_css_string("border-width", "1px 2px"), "1px 2px 1px 2px");
+        assert_eq!(to_css_string("border-image", "image(\"frame\") 5 5 stretch"), "image(\"frame\") 5 stretch");
+        assert_eq!(to_css_string("box-shadow", "none"), "none");
+        assert_eq!(to_css_string("box-shadow", "1px 2px 3px #000000 inset"), "1px 2px 3px 0px #000000 inset");
+        assert_eq!(to_css_string("background", "linear-gradient(to right, red 0%, blue 100%)"), "linear-gradient(to right, #ff0000 0px, #0000ff 100%)");
+        assert_eq!(to_css_string("background", "radial-gradient(circle 10px, red, blue)"), "radial-gradient(circle 10px at 50% 50%, #ff0000, #0000ff)");
+        assert_eq!(to_css_string("background", "image(\"Cat 01\")"), "image(\"Cat 01\")");
+        assert_eq!(to_css_string("font-family", "\"Webly Sleeky UI\", monospace"), "\"Webly Sleeky UI\", monospace");
+        assert_eq!(to_css_string("overflow-y", "scroll"), "scroll");
+
+        let property = ParsedCssProperty::from_kv("justify-content", "space-between").unwrap();
+        assert_eq!(property.to_string(), "justify-content: space-between");
+        assert_eq!(ParsedCssProperty::from_kv("overflow-x", "hidden").unwrap().to_string(), "overflow-x: hidden");
+    }
+
+    #[test]
+    fn test_to_css_string_round_trip() {
+        let declarations = [
+            ("border-radius", "1px 2px 3px 4px"),
+            ("background-color", "#12345678"),
+            ("background-color", "transparent"),
+            ("color", "rgba(10, 20, 30, 0.5)"),
+            ("color", "hsl(210deg 50% 40%)"),
+            ("color", "alice-blue"),
+            ("border", "none"),
+            ("border", "1.5px solid #c5c5c5"),
+            ("border-top", "2px dashed red"),
+            ("border-left", "groove"),
+            ("border-width", "1px 2px"),
+            ("border-style", "solid none dotted"),
+            ("border-color", "red rgba(0, 0, 0, 0.5)"),
+            ("border-image", "image(\"frame\") 10 20 fill round stretch"),
+            ("border-image", "none"),
+            ("background", "linear-gradient(50deg, red, green 20%, blue)"),
+            ("background", "repeating-linear-gradient(to top left, #fcfcfc, #efefef)"),
+            ("background", "radial-gradient(circle, red, blue)"),
+            ("background", "repeating-radial-gradient(red 10%, blue 20%)"),
+            ("background", "radial-gradient(ellipse closest-corner at right 10px, red, blue 20px)"),
+            ("background", "repeating-radial-gradient(10px 50% at top, red, blue)"),
+            ("background", "image(\"logo\"), linear-gradient(red, blue)"),
+            ("background", "none"),
+            ("background-size", "cover, 50% auto"),
+            ("background-position", "center, right 10px"),
+            ("background-repeat", "no-repeat, repeat-y"),
+            ("font-size", "1.5em"),
+            ("font-family", "'Helvetica', sans-serif"),
+            ("text-align", "center"),
+            ("box-shadow", "0px 0px 3px #c5c5c5ad"),
+            ("box-shadow", "5px 10px 5px 10px #888888 inset"),
+            ("box-shadow", "0px 3px 1px -2px #00000033, 0px 2px 2px #00000024, inset 0px 1px 5px #0000001f"),
+            ("box-shadow", "none"),
+            ("line-height", "120%"),
+            ("width", "100px"),
+            ("width", "50%"),
+            ("height", "calc(100vh - 2.5rem + 1em)"),
+            ("max-width", "calc(100% / 3)"),
+            ("min-height", "2em"),
+            ("padding", "5px"),
+            ("padding", "1px 2px 3px 4px"),
+            ("padding-top", "5px"),
+            ("margin-right", "1.5em"),
+            ("margin", "0px 10pt"),
+            ("flex-wrap", "wrap"),
+            ("flex-direction", "row"),
+            ("align-items", "stretch"),
+            ("align-content", "space-around"),
+            ("overflow", "auto"),
+            ("overflow-x", "visible"),
+            ("font-weight", "bold"),
+            ("font-weight", "300"),
+            ("font-style", "italic"),
+            ("letter-spacing", "0.1em"),
+            ("word-spacing", "4px"),
+            ("text-decoration", "none"),
+            ("text-decoration", "underline line-through"),
+            ("text-transform", "capitalize"),
+            ("vertical-align", "middle"),
+            ("position", "absolute"),
+            ("top", "10px"),
+            ("right", "auto"),
+            ("bottom", "-5%"),
+            ("left", "calc(50% - 2em)"),
+            ("z-index", "-1"),
+            ("z-index", "auto"),
+            ("display", "none"),
+            ("visibility", "hidden"),
+            ("cursor", "pointer"),
+            ("cursor", "nwse-resize"),
+            ("opacity", "0.5"),
+            ("transform", "none"),
+            ("transform", "translate(10px, 50%) rotate(45deg) scale(1.5, 2) skew(10deg, 0deg)"),
+            ("filter", "none"),
+            ("filter", "blur(5px) grayscale(0.5) hue-rotate(90deg) brightness(1.5)"),
+            ("transition", "none"),
+            ("transition", "background-color 200ms ease-out, width 0.5s cubic-bezier(0.1, 0.7, 1, 0.1) 1s"),
+            ("animation", "spin 1s linear infinite"),
+            ("animation", "fade 200ms 1s 2.5 alternate-reverse forwards, pulse 1s"),
+        ];
+
+        for &(key, value) in declarations.iter() {
+            let parsed = ParsedCssProperty::from_kv(key, value).unwrap();
+            let css_string = parsed.to_css_string();
+            assert_eq!(ParsedCssProperty::from_kv(key, &css_string), Ok(parsed.clone()), "{}: {} -> {}", key, value, css_string);
+            assert_eq!(parsed.get_key(), key);
+        }
+    }
+
+    #[test]
+    fn test_parse_transition_and_animation() {
+        assert_eq!(parse_css_transition("background-color 200ms ease-out"), Ok(StyleTransitions(vec![Transition {
+            property: "background-color".to_string(),
+            duration: Duration::from_millis(200),
+            timing_function: TimingFunction::EaseOut,
+            delay: Duration::from_millis(0),
+        }])));
+        assert_eq!(parse_css_transition("0.5s cubic-bezier(0.1, 0.7, 1.0, 0.1) 1s, color 1s"), Ok(StyleTransitions(vec![
+            Transition {
+                property: "all".to_string(),
+                duration: Duration::from_millis(500),
+                timing_function: TimingFunction::CubicBezier(0.1, 0.7, 1.0, 0.1),
+                delay: Duration::from_millis(1000),
+            },
+            Transition {
+                property: "color".to_string(),
+                duration: Duration::from_millis(1000),
+                timing_function: TimingFunction::Ease,
+                delay: Duration::from_millis(0),
+            },
+        ])));
+        assert_eq!(parse_css_transition("none"), Ok(StyleTransitions(Vec::new())));
+        assert_eq!(parse_css_transition("width 200"), Err(CssAnimationParseError::InvalidTime("200")));
+        assert_eq!(parse_css_transition("width -1s"), Err(CssAnimationParseError::InvalidTime("-1s")));
+        assert_eq!(parse_css_transition("width height 1s"), Err(CssAnimationParseError::UnexpectedValue("height")));
+        assert_eq!(parse_css_transition("width 1s cubic-bezier(2, 0, 1, 1)"),
+                   Err(CssAnimationParseError::InvalidTimingFunction("cubic-bezier(2, 0, 1, 1)")));
+
+        assert_eq!(parse_css_animation("fade 200ms ease-in 1s 2.5 alternate-reverse forwards"), Ok(StyleAnimations(vec![Animation {
+            name: "fade".to_string(),
+            duration: Duration::from_millis(200),
+            timing_function: TimingFunction::EaseIn,
+            delay: Duration::from_millis(1000),
+            iteration_count: AnimationIterationCount::Count(2.5),
+            direction: AnimationDirection::AlternateReverse,
+            fill_mode: AnimationFillMode::Forwards,
+        }])));
+        assert_eq!(parse_css_animation("spin 1s linear infinite").unwrap().0[0].iteration_count, AnimationIterationCount::Infinite);
+        assert_eq!(parse_css_animation("1s linear"), Err(CssAnimationParseError::MissingAnimationName("1s linear")));
+        assert_eq!(parse_css_animation("spin -1"), Err(CssAnimationParseError::InvalidIterationCount("-1")));
+        assert_eq!(parse_css_animation("spin 5px"), Err(CssAnimationParseError::InvalidTime("5px")));
+        assert_eq!(parse_css_animation("spin fade"), Err(CssAnimationParseError::UnexpectedValue("fade")));
+    }
+
+    #[test]
+    fn test_parse_text_properties() {
+        assert_eq!(parse_font_weight("normal"), Ok(FontWeight(400)));
+        assert_eq!(parse_font_weight("bold"), Ok(FontWeight(700)));
+        assert_eq!(parse_font_weight("600"), Ok(FontWeight(600)));
+        assert!(FontWeight(600).is_bold());
+        assert!(!FontWeight(500).is_bold());
+        assert_eq!(parse_font_weight("0"), Err(InvalidValueErr("0")));
+        assert_eq!(parse_font_weight("heavy"), Err(InvalidValueErr("heavy")));
+
+        assert_eq!(parse_font_style("oblique"), Ok(FontStyle::Oblique));
+        assert_eq!(parse_text_spacing("normal"), Ok(PixelValue::zero()));
+        assert_eq!(parse_text_spacing("-1px"), Ok(PixelValue::from_metric(CssMetric::Px, -1.0)));
+
+        assert_eq!(parse_text_decoration("line-through  underline"), Ok(TextDecoration { underline: true, overline: false, line_through: true }));
+        assert_eq!(parse_text_decoration("none"), Ok(TextDecoration::default()));
+        assert_eq!(parse_text_decoration("underline underline"), Err(InvalidValueErr("underline")));
+        assert_eq!(parse_text_decoration("blink"), Err(InvalidValueErr("blink")));
+
+        assert_eq!(parse_layout_vertical_align("middle"), Ok(TextAlignmentVert::Center));
+        assert_eq!(parse_layout_vertical_align("center"), Err(InvalidValueErr("center")));
+
+        assert_eq!(TextTransform::Uppercase.apply("straße 1"), "STRASSE 1");
+        assert_eq!(TextTransform::Lowercase.apply("Hello World"), "hello world");
+        assert_eq!(TextTransform::Capitalize.apply("hello  wide\tworld"), "Hello  Wide\tWorld");
+        assert_eq!(TextTransform::None.apply("hello"), "hello");
+
+        assert!(ParsedCssProperty::from_kv("font-weight", "bold").unwrap().is_inherited());
+        assert!(ParsedCssProperty::from_kv("letter-spacing", "1px").unwrap().is_inherited());
+        assert!(!ParsedCssProperty::from_kv("text-decoration", "underline").unwrap().is_inherited());
+        assert!(!ParsedCssProperty::from_kv("vertical-align", "top").unwrap().is_inherited());
+    }
+
+    #[test]
+    fn test_parse_positioning() {
+        assert_eq!(parse_layout_position("relative"), Ok(LayoutPosition::Relative));
+        assert_eq!(parse_layout_position("fixed"), Err(InvalidValueErr("fixed")));
+        assert_eq!(parse_layout_offset("auto"), Ok(None));
+        assert_eq!(parse_layout_offset("-10px"), Ok(Some(PixelValue::from_metric(CssMetric::Px, -10.0))));
+        assert!(parse_layout_offset("left").is_err());
+
+        assert_eq!(parse_layout_z_index("auto"), Ok(LayoutZIndex(None)));
+        assert_eq!(parse_layout_z_index("-2"), Ok(LayoutZIndex(Some(-2))));
+        assert_eq!(parse_layout_z_index("1.5"), Err(InvalidValueErr("1.5")));
+
+        assert_eq!(parse_layout_display("none"), Ok(LayoutDisplay::None));
+        assert_eq!(parse_style_visibility("hidden"), Ok(StyleVisibility::Hidden));
+
+        // only `visibility` is inherited, `display: none` already hides the children
+        assert!(ParsedCssProperty::from_kv("visibility", "hidden").unwrap().is_inherited());
+        assert!(!ParsedCssProperty::from_kv("display", "none").unwrap().is_inherited());
+        assert!(!ParsedCssProperty::from_kv("z-index", "1").unwrap().is_inherited());
+    }
+
+    #[test]
+    fn test_parse_cursor() {
+        assert_eq!(parse_style_cursor("pointer"), Ok(StyleCursor::Pointer));
+        assert_eq!(parse_style_cursor("ew-resize"), Ok(StyleCursor::EwResize));
+        assert_eq!(parse_style_cursor("hand"), Err(InvalidValueErr("hand")));
+        assert!(ParsedCssProperty::from_kv("cursor", "text").unwrap().is_inherited());
+    }
+
+    #[test]
+    fn test_parse_opacity_transform_and_filter() {
+        assert_eq!(parse_style_opacity("0.5"), Ok(StyleOpacity(0.5)));
+        assert_eq!(parse_style_opacity("25%"), Ok(StyleOpacity(0.25)));
+        assert_eq!(parse_style_opacity("2"), Ok(StyleOpacity(1.0)));
+        assert!(parse_style_opacity("half").is_err());
+
+        assert_eq!(parse_css_transform("translate(10px, 50%) rotate(0.25turn) scaleY(2) skewX(10deg)"), Ok(StyleTransforms(vec![
+            TransformFunction::Translate(PixelValue::from_metric(CssMetric::Px, 10.0), PixelValue::from_metric(CssMetric::Percent, 50.0)),
+            TransformFunction::Rotate(90.0),
+            TransformFunction::Scale(1.0, 2.0),
+            TransformFunction::Skew(10.0, 0.0),
+        ])));
+        assert_eq!(parse_css_transform("translateY(5px) scale(0.5)"), Ok(StyleTransforms(vec![
+            TransformFunction::Translate(PixelValue::zero(), PixelValue::from_metric(CssMetric::Px, 5.0)),
+            TransformFunction::Scale(0.5, 0.5),
+        ])));
+        assert_eq!(parse_css_transform("none"), Ok(StyleTransforms(Vec::new())));
+        assert_eq!(parse_css_transform("rotate(45)"), Err(CssTransformParseError::InvalidAngle("45")));
+        assert_eq!(parse_css_transform("scale(5px)"), Err(CssTransformParseError::InvalidNumber("5px")));
+        assert_eq!(parse_css_transform("rotate(1deg, 2deg)"), Err(CssTransformParseError::WrongNumberOfArguments("rotate(1deg, 2deg)")));
+        assert_eq!(parse_css_transform("perspective(5px)"), Err(CssTransformParseError::UnknownFunction("perspective(5px)")));
+        assert_eq!(parse_css_transform("rotate 45deg"), Err(CssTransformParseError::UnknownFunction("rotate")));
+
+        assert_eq!(parse_css_filter("blur(2px) grayscale(50%) hue-rotate(90deg) invert() opacity(2)"), Ok(StyleFilters(vec![
+            FilterFunction::Blur(PixelValue::from_metric(CssMetric::Px, 2.0)),
+            FilterFunction::Grayscale(0.5),
+            FilterFunction::HueRotate(90.0),
+            FilterFunction::Invert(1.0),
+            FilterFunction::Opacity(1.0),
+        ])));
+        assert_eq!(parse_css_filter("brightness(150%)"), Ok(StyleFilters(vec![FilterFunction::Brightness(1.5)])));
+        assert_eq!(parse_css_filter("none"), Ok(StyleFilters(Vec::new())));
+        assert_eq!(parse_css_filter("grayscale(-1)"), Err(CssFilterParseError::InvalidAmount("-1")));
+        assert_eq!(parse_css_filter("hue-rotate(90)"), Err(CssFilterParseError::InvalidAngle("90")));
+        assert_eq!(parse_css_filter("blur(1px, 2px)"), Err(CssFilterParseError::WrongNumberOfArguments("blur(1px, 2px)")));
+        assert_eq!(parse_css_filter("drop-shadow(1px 1px red)"), Err(CssFilterParseError::UnknownFunction("drop-shadow(1px 1px red)")));
+    }
+
+    #[test]
+    fn test_transform_origin_is_center() {
+        let bounds = LayoutRect::new(LayoutPoint::new(100.0, 100.0), LayoutSize::new(100.0, 50.0));
+        let context = LengthContext::default();
+        let transform = |input: &str, point: LayoutPoint| {
+            let point = parse_css_transform(input).unwrap().to_layout_transform(&bounds, &context).transform_point2d(&point).unwrap();
+            ((point.x * 100.0).round() / 100.0, (point.y * 100.0).round() / 100.0)
+        };
+
+        // the center doesn't move when rotating or scaling
+        assert_eq!(transform("rotate(90deg) scale(2)", LayoutPoint::new(150.0, 125.0)), (150.0, 125.0));
+        assert_eq!(transform("scale(2)", LayoutPoint::new(100.0, 100.0)), (50.0, 75.0));
+        // rotations are clockwise, since the y axis points down
+        assert_eq!(transform("rotate(90deg)", LayoutPoint::new(200.0, 125.0)), (150.0, 175.0));
+        // `%` is relative to the size of the node
+        assert_eq!(transform("translate(50%, 10px)", LayoutPoint::new(100.0, 100.0)), (150.0, 110.0));
+        // the node is rotated first and then translated
+        assert_eq!(transform("translateX(10px) rotate(90deg)", LayoutPoint::new(200.0, 125.0)), (160.0, 175.0));
+    }
+
+    #[test]
+    fn test_timing_function() {
+        use self::TimingFunction::*;
+
+        for function in [Linear, Ease, EaseIn, EaseOut, EaseInOut, CubicBezier(0.5, -1.0, 0.5, 2.0)].iter() {
+            assert_eq!(function.evaluate(0.0), 0.0);
+            assert_eq!(function.evaluate(1.0), 1.0);
+        }
+
+        assert_eq!(Linear.evaluate(0.25), 0.25);
+        assert!((CubicBezier(0.0, 0.0, 1.0, 1.0).evaluate(0.3) - 0.3).abs() < 0.001);
+        assert!((EaseInOut.evaluate(0.5) - 0.5).abs() < 0.001);
+        assert!(EaseIn.evaluate(0.25) < 0.25);
+        assert!(EaseOut.evaluate(0.25) > 0.25);
+        // the curve can overshoot
+        assert!(CubicBezier(0.5, -1.0, 0.5, 2.0).evaluate(0.1) < 0.0);
+    }
+
+    #[test]
+    fn test_interpolate_properties() {
+        fn interpolate(key: &str, from: &str, to: &str, t: f32) -> Option<String> {
+            let from = ParsedCssProperty::from_kv(key, from).unwrap();
+            let to = ParsedCssProperty::from_kv(key, to).unwrap();
+            from.interpolate(&to, t).and_then(|property| Some(property.to_css_string()))
+        }
+
+        assert_eq!(interpolate("background-color", "#000000", "#ff0000", 0.5), Some("#800000".to_string()));
+        assert_eq!(interpolate("width", "10px", "20px", 0.25), Some("12.5px".to_string()));
+        assert_eq!(interpolate("width", "10px", "50%", 0.5), Some("calc(5px + 25%)".to_string()));
+        assert_eq!(interpolate("padding", "0px", "10px 20px", 0.5), Some("5px 10px 5px 10px".to_string()));
+        assert_eq!(interpolate("border-radius", "0px", "10px 20px", 0.5), Some("5px 10px 5px 10px".to_string()));
+        assert_eq!(interpolate("border", "1px solid #000000", "3px solid #ffffff", 0.5), Some("2px solid #808080".to_string()));
+        assert_eq!(interpolate("border-width", "0px", "4px 2px", 0.5), Some("2px 1px 2px 1px".to_string()));
+        assert_eq!(interpolate("border-top", "1px solid red", "1px dashed red", 0.5), None);
+        assert_eq!(interpolate("box-shadow", "none", "10px 10px 4px #000000", 0.5), Some("5px 5px 2px 0px #00000080".to_string()));
+        assert_eq!(interpolate("background", "linear-gradient(90deg, red, blue 50%)", "linear-gradient(180deg, blue, red 100%)", 0.5),
+                   Some("linear-gradient(135deg, #800080, #800080 75%)".to_string()));
+        assert_eq!(interpolate("background", "radial-gradient(circle 10px at left, red, blue)", "radial-gradient(circle 20px at right, red, blue)", 0.5),
+                   Some("radial-gradient(circle 15px at 50% 50%, #ff0000, #0000ff)".to_string()));
+        assert_eq!(interpolate("background-size", "10px auto, cover", "20px auto, cover", 0.5), Some("15px auto, cover".to_string()));
+        assert_eq!(interpolate("background-position", "left top", "right bottom", 0.5), Some("50% 50%".to_string()));
+        assert_eq!(interpolate("opacity", "0", "1", 0.25), Some("0.25".to_string()));
+        assert_eq!(interpolate("left", "0px", "100%", 0.5), Some("50%".to_string()));
+        assert_eq!(interpolate("z-index", "1", "10", 0.5), Some("6".to_string()));
+        assert_eq!(interpolate("top", "auto", "10px", 0.5), None);
+        assert_eq!(interpolate("transform", "rotate(0deg) scale(1)", "rotate(90deg) scale(2)", 0.5), Some("rotate(45deg) scale(1.5, 1.5)".to_string()));
+        assert_eq!(interpolate("transform", "none", "translateX(10px)", 0.5), Some("translate(5px, 0px)".to_string()));
+        assert_eq!(interpolate("filter", "blur(4px) grayscale(1)", "none", 0.5), Some("blur(2px) grayscale(0.5)".to_string()));
+        assert_eq!(interpolate("transform", "rotate(45deg)", "scale(2)", 0.5), None);
+        assert_eq!(interpolate("filter", "blur(1px)", "blur(1px) sepia(1)", 0.5), None);
+
+        // gradients with a different number of stops, longhands of different
+        // sides and values that aren't numeric can't be interpolated
+        assert_eq!(interpolate("background", "linear-gradient(red, blue)", "linear-gradient(red, blue, green)", 0.5), None);
+        assert_eq!(interpolate("background-size", "cover", "cover, contain", 0.5), None);
+        assert_eq!(interpolate("background-size", "auto", "10px", 0.5), None);
+        assert_eq!(interpolate("box-shadow", "1px 1px", "1px 1px inset", 0.5), None);
+        assert_eq!(interpolate("box-shadow", "2px 2px #000000", "4px 4px #000000, 2px 2px #000000 inset", 0.5),
+                   Some("3px 3px 0px 0px #000000, 1px 1px 0px 0px #00000080 inset".to_string()));
+        assert_eq!(interpolate("flex-direction", "row", "column", 0.5), None);
+        let padding_top = ParsedCssProperty::from_kv("padding-top", "1px").unwrap();
+        let padding_left = ParsedCssProperty::from_kv("padding-left", "1px").unwrap();
+        assert_eq!(padding_top.interpolate(&padding_left, 0.5), None);
+        let width = ParsedCssProperty::from_kv("width", "1px").unwrap();
+        let height = ParsedCssProperty::from_kv("height", "1px").unwrap();
+        assert_eq!(width.interpolate(&height, 0.5), None);
+    }
+}
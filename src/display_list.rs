@@ -7,23 +7,20 @@ use std::{
     fmt::Debug,
 };
 use webrender::api::*;
-use app_units::{AU_PER_PX, MIN_AU, MAX_AU, Au};
+use app_units::{MIN_AU, MAX_AU, Au};
 use euclid::{TypedRect, TypedSize2D};
-use cassowary::{Constraint, Solver, Variable};
 
 use {
     FastHashMap,
     resources::AppResources,
     traits::Layout,
-    constraints::{DisplayRect, CssConstraint},
     ui_description::{UiDescription, StyledNode},
     window::UiSolver,
+    ui_solver::{self, PaintNode},
     window_state::WindowSize,
     id_tree::{Arena, NodeId},
     css_parser::{self, *},
     dom::{NodeData, NodeType::{self, *}},
-    css::Css,
-    cache::DomChangeSet,
     ui_description::CssConstraintList,
     text_layout::{TextOverflowPass2, ScrollbarInfo},
     images::ImageId,
@@ -53,14 +50,28 @@ pub(crate) struct DisplayRectangle<'a> {
     pub(crate) layout: RectLayout,
 }
 
-/// It is not very efficient to re-create constraints on every call, the difference
-/// in performance can be huge. Without re-creating constraints, solving can take 0.3 ms,
-/// with re-creation it can take up to 9 ms. So the goal is to not re-create constraints
-/// if their contents haven't changed.
-#[derive(Default)]
-pub(crate) struct SolvedLayout<T: Layout> {
-    // List of previously solved constraints
-    pub(crate) solved_constraints: FastHashMap<NodeId, NodeData<T>>,
+/// The solved layout of the last frame, i.e. the final position and size of every node.
+/// Nodes can be looked up by their `NodeId`, their CSS id or their hit-testing tag.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SolvedLayout {
+    /// The bounds of every node
+    pub(crate) rects: BTreeMap<NodeId, LayoutRect>,
+    /// The content box of every node, i.e. its bounds minus its padding
+    pub(crate) content_boxes: BTreeMap<NodeId, LayoutRect>,
+    /// The length context of every node, i.e. its computed font size (see `ui_solver::SolvedRects`)
+    pub(crate) length_contexts: BTreeMap<NodeId, LengthContext>,
+    /// Maps the CSS ids (`#id`) of the nodes to their `NodeId`
+    pub(crate) css_ids: FastHashMap<String, NodeId>,
+    /// Maps the hit-testing tags (see `DisplayRectangle::tag`) to their `NodeId`
+    pub(crate) tags: FastHashMap<u64, NodeId>,
+    /// The visible nodes in the order in which they are painted (see `ui_solver::get_paint_order`),
+    /// the last node is the top-most one
+    pub(crate) paint_order: Vec<NodeId>,
+    /// The `cursor` of every node that has one (inherited from its parent or set directly)
+    pub(crate) cursors: BTreeMap<NodeId, StyleCursor>,
+    /// The transform of every node that is transformed, by its own `transform` or by the
+    /// `transform` of a node that it is painted with (see `collect_transforms`)
+    pub(crate) transforms: BTreeMap<NodeId, LayoutTransform>,
 }
 
 /// This is used for caching large strings (in the `push_text` function)
@@ -97,11 +108,50 @@ impl TextInfo {
     }
 }
 
-impl<T: Layout> SolvedLayout<T> {
+impl SolvedLayout {
     pub fn empty() -> Self {
-        Self {
-            solved_constraints: FastHashMap::default(),
-        }
+        Self::default()
+    }
+
+    /// Returns the bounds of the node with the given `NodeId`
+    pub fn get_bounds(&self, node_id: NodeId) -> Option<LayoutRect> {
+        self.rects.get(&node_id).cloned()
+    }
+
+    /// Returns the bounds of the node with the given CSS id
+    pub fn get_bounds_by_id(&self, css_id: &str) -> Option<LayoutRect> {
+        self.css_ids.get(css_id).and_then(|node_id| self.get_bounds(*node_id))
+    }
+
+    /// Returns the bounds of the node with the given hit-testing tag
+    pub fn get_bounds_by_tag(&self, tag: u64) -> Option<LayoutRect> {
+        self.tags.get(&tag).and_then(|node_id| self.get_bounds(*node_id))
+    }
+
+    /// Returns the top-most node at the given point, i.e. the node that is
+    /// painted last. Unlike the webrender hit test, this also finds nodes
+    /// that don't have a callback attached to them.
+    ///
+    /// Nodes with `visibility: hidden` or `display: none` are never hit. The `transform`
+    /// of the nodes is taken into account, i.e. a translated node is hit where it is painted.
+    pub fn hit_test(&self, point: LayoutPoint) -> Option<NodeId> {
+        self.paint_order.iter().rev().find(|node_id| {
+            // Transform the point back into the untransformed bounds of the node
+            let point = match self.transforms.get(*node_id) {
+                Some(transform) => match transform.inverse().and_then(|inverse| inverse.transform_point2d(&point)) {
+                    Some(point) => point,
+                    None => return false,
+                },
+                None => point,
+            };
+            self.rects.get(*node_id).map(|bounds| bounds.contains(&point)).unwrap_or(false)
+        }).cloned()
+    }
+
+    /// Returns the `cursor` of the top-most node at the given point or `None` if
+    /// the node has no `cursor` property (in which case the default cursor is shown)
+    pub fn get_cursor(&self, point: LayoutPoint) -> Option<StyleCursor> {
+        self.hit_test(point).and_then(|node_id| self.cursors.get(&node_id).cloned())
     }
 }
 
@@ -129,7 +179,8 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
         let display_rect_arena = arena.transform(|node, node_id| {
             let style = ui_description.styled_nodes.get(&node_id).unwrap_or(&ui_description.default_style_of_node);
             let mut rect = DisplayRectangle::new(node.tag, style);
-            populate_css_properties(&mut rect, &ui_description.dynamic_css_overrides);
+            let animated_properties = ui_description.animated_properties.get(&node_id).map(|p| &p[..]).unwrap_or(&[]);
+            populate_css_properties(&mut rect, &ui_description.dynamic_css_overrides, animated_properties);
             rect
         });
 
@@ -207,13 +258,13 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
         use font::FontState;
         use css_parser::FontId;
 
-        let mut updated_fonts = Vec::<(FontId, Vec<u8>)>::new();
+        let mut updated_fonts = Vec::<(FontId, Vec<u8>, u32)>::new();
         let mut to_delete_fonts = Vec::<(FontId, Option<(FontKey, Vec<FontInstanceKey>)>)>::new();
 
         for (key, value) in app_resources.font_data.iter() {
             match value.2 {
-                FontState::ReadyForUpload(ref bytes) => {
-                    updated_fonts.push((key.clone(), bytes.clone()));
+                FontState::ReadyForUpload(ref bytes, index) => {
+                    updated_fonts.push((key.clone(), bytes.clone(), index));
                 },
                 FontState::Uploaded(_) => { },
                 FontState::AboutToBeDeleted(ref font_key) => {
@@ -240,93 +291,88 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
         }
 
         // Upload all remaining fonts to the GPU only if the haven't been uploaded yet
-        for (resource_key, data) in updated_fonts.into_iter() {
+        for (resource_key, data, index) in updated_fonts.into_iter() {
             let key = api.generate_font_key();
-            resource_updates.push(ResourceUpdate::AddFont(AddFont::Raw(key, data, 0))); // TODO: use the index better?
+            resource_updates.push(ResourceUpdate::AddFont(AddFont::Raw(key, data, index)));
             app_resources.font_data.get_mut(&resource_key).unwrap().2 = FontState::Uploaded(key);
         }
     }
 
-    pub fn into_display_list_builder(
-        &self,
-        pipeline_id: PipelineId,
-        ui_solver: &mut UiSolver<T>,
-        css: &mut Css,
-        app_resources: &mut AppResources,
-        render_api: &RenderApi,
-        mut has_window_size_changed: bool,
-        window_size: &WindowSize)
-    -> Option<DisplayListBuilder>
-    {
-        use euclid::TypedScale;
+    /// Calculates the bounds of every rectangle, given the bounds of the window,
+    /// and the order in which the rectangles have to be painted
+    fn solve_layout(&self, full_screen_rect: LayoutRect) -> (SolvedLayout, Vec<PaintNode>) {
 
-        let mut changeset = None;
+        let root = match self.ui_descr.ui_descr_root {
+            Some(root) => root,
+            None => return (SolvedLayout::empty(), Vec::new()),
+        };
 
-        if let Some(root) = self.ui_descr.ui_descr_root {
-            let local_changeset = ui_solver.dom_tree_cache.update(root, &*(self.ui_descr.ui_descr_arena.borrow()));
-            ui_solver.edit_variable_cache.initialize_new_rectangles(&mut ui_solver.solver, &local_changeset);
-            ui_solver.edit_variable_cache.remove_unused_variables(&mut ui_solver.solver);
-            changeset = Some(local_changeset);
-        }
+        let layout_arena = self.rectangles.transform(|rect, _| rect.layout);
+        let ui_solver::SolvedRects { bounds: rects, content_boxes, length_contexts } = ui_solver::solve_layout(&layout_arena, root, full_screen_rect);
+        let paint_nodes = ui_solver::get_paint_order(&layout_arena, root, &|node_id| self.rectangles[node_id].data.style.has_stacking_context());
 
-        if css.needs_relayout {
-
-            // constraints were added or removed during the last frame
-            for rect_idx in self.rectangles.linear_iter() {
-                let rect = &self.rectangles[rect_idx].data;
-                let arena = &*self.ui_descr.ui_descr_arena.borrow();
-                let dom_hash = &ui_solver.dom_tree_cache.previous_layout.arena[rect_idx];
-                let display_rect = ui_solver.edit_variable_cache.map[&dom_hash.data];
-                let layout_contraints = create_layout_constraints(rect, rect_idx, &self.rectangles, window_size);
-                let cassowary_constraints = css_constraints_to_cassowary_constraints(&display_rect.1, &layout_contraints);
-                ui_solver.solver.add_constraints(&cassowary_constraints).unwrap();
-            }
+        let mut paint_order = Vec::new();
+        flatten_paint_order(&paint_nodes, &self.rectangles, &mut paint_order);
 
-            // if we push or pop constraints that means we also need to re-layout the window
-            has_window_size_changed = true;
-        }
+        let mut transforms = BTreeMap::new();
+        collect_transforms(&paint_nodes, &self.rectangles, &rects, &length_contexts, None, &mut transforms);
 
-        let changeset_is_useless = match changeset {
-            None => true,
-            Some(c) => c.is_empty()
-        };
+        let arena = self.ui_descr.ui_descr_arena.borrow();
+        let mut css_ids = FastHashMap::default();
+        let mut tags = FastHashMap::default();
+        let mut cursors = BTreeMap::new();
 
-        // recalculate the actual layout
-        if css.needs_relayout || has_window_size_changed {
-            /*
-            for change in solver.fetch_changes() {
-                println!("change: - {:?}", change);
+        for node_id in arena.linear_iter() {
+            let node = &arena[node_id].data;
+            if let Some(ref css_id) = node.id {
+                css_ids.insert(css_id.clone(), node_id);
+            }
+            if let Some(tag) = node.tag {
+                tags.insert(tag, node_id);
+            }
+            if let Some(cursor) = self.rectangles[node_id].data.style.cursor {
+                cursors.insert(node_id, cursor);
             }
-            */
         }
 
-        css.needs_relayout = false;
+        (SolvedLayout { rects, content_boxes, length_contexts, css_ids, tags, paint_order, cursors, transforms }, paint_nodes)
+    }
 
+    pub fn into_display_list_builder(
+        &self,
+        pipeline_id: PipelineId,
+        ui_solver: &mut UiSolver,
+        app_resources: &mut AppResources,
+        render_api: &RenderApi,
+        window_size: &WindowSize)
+    -> Option<DisplayListBuilder>
+    {
         use glium::glutin::dpi::LogicalSize;
 
+        if let Some(root) = self.ui_descr.ui_descr_root {
+            ui_solver.dom_tree_cache.update(root, &*(self.ui_descr.ui_descr_arena.borrow()));
+        }
+
         let LogicalSize { width, height } = window_size.dimensions;
         let mut builder = DisplayListBuilder::with_capacity(pipeline_id, TypedSize2D::new(width as f32, height as f32), self.rectangles.nodes_len());
         let mut resource_updates = Vec::<ResourceUpdate>::new();
-        let full_screen_rect = LayoutRect::new(LayoutPoint::zero(), builder.content_size());;
+        let full_screen_rect = LayoutRect::new(LayoutPoint::zero(), builder.content_size());
+
+        // Solve the layout, i.e. calculate the bounds of every rectangle
+        let (solved_layout, paint_nodes) = self.solve_layout(full_screen_rect);
+        ui_solver.solved_layout = solved_layout;
 
         // Upload image and font resources
         Self::update_resources(render_api, app_resources, &mut resource_updates);
 
-        for rect_idx in self.rectangles.linear_iter() {
-
-            let arena = self.ui_descr.ui_descr_arena.borrow();
-            let node_type = &arena[rect_idx].data.node_type;
-
-            // ask the solver what the bounds of the current rectangle is
-            // let bounds = ui_solver.query_bounds_of_rect(*rect_idx);
-
-            // temporary: fill the whole window with each rectangle
-            displaylist_handle_rect(
+        let arena = self.ui_descr.ui_descr_arena.borrow();
+        for paint_node in &paint_nodes {
+            push_rectangles_into_displaylist(
                 &mut builder,
-                rect_idx,
+                paint_node,
                 &self.rectangles,
-                node_type,
-                full_screen_rect, /* replace this with the real bounds */
+                &*arena,
+                &ui_solver.solved_layout,
                 full_screen_rect,
                 app_resources,
                 render_api,
@@ -339,12 +385,184 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
     }
 }
 
+/// Flattens the `paint_nodes` into the `paint_order` of the `SolvedLayout`, without the invisible nodes
+fn flatten_paint_order<'a>(paint_nodes: &[PaintNode], arena: &Arena<DisplayRectangle<'a>>, paint_order: &mut Vec<NodeId>) {
+    for paint_node in paint_nodes {
+        if arena[paint_node.node_id].data.style.is_visible() {
+            paint_order.push(paint_node.node_id);
+        }
+        flatten_paint_order(&paint_node.children, arena, paint_order);
+    }
+}
+
+/// Collects the transforms of the `paint_nodes` and their children into the `transforms` of the
+/// `SolvedLayout`. Like the stacking contexts of `push_stacking_context`, the `transform` of a node
+/// also applies to the children that are painted with it, on top of their own `transform`.
+fn collect_transforms<'a>(
+    paint_nodes: &[PaintNode],
+    arena: &Arena<DisplayRectangle<'a>>,
+    rects: &BTreeMap<NodeId, LayoutRect>,
+    length_contexts: &BTreeMap<NodeId, LengthContext>,
+    parent_transform: Option<LayoutTransform>,
+    transforms: &mut BTreeMap<NodeId, LayoutTransform>)
+{
+    for paint_node in paint_nodes {
+        let node_id = paint_node.node_id;
+        let style = &arena[node_id].data.style;
+
+        let own_transform = match (style.transform.as_ref(), rects.get(&node_id), length_contexts.get(&node_id)) {
+            (Some(transform), Some(bounds), Some(context)) if !transform.0.is_empty() => Some(transform.to_layout_transform(bounds, context)),
+            _ => None,
+        };
+
+        // The own transform is applied first, then the one of the parent
+        let transform = match (own_transform, parent_transform) {
+            (Some(own_transform), Some(parent_transform)) => Some(own_transform.post_mul(&parent_transform)),
+            (own_transform, parent_transform) => own_transform.or(parent_transform),
+        };
+
+        if let Some(transform) = transform {
+            transforms.insert(node_id, transform);
+        }
+
+        collect_transforms(&paint_node.children, arena, rects, length_contexts, transform, transforms);
+    }
+}
+
+/// Pushes the rectangle and the children of the `paint_node` (in paint order). If the rectangle
+/// has an `opacity`, a `transform` or a `filter`, the rectangle and its children are wrapped in a
+/// stacking context, so that they are faded / transformed / filtered as one group.
+///
+/// Rectangles with `visibility: hidden` are skipped, but their children can still be visible.
+fn push_rectangles_into_displaylist<'a, T: Layout>(
+    builder: &mut DisplayListBuilder,
+    paint_node: &PaintNode,
+    arena: &Arena<DisplayRectangle<'a>>,
+    node_arena: &Arena<NodeData<T>>,
+    solved_layout: &SolvedLayout,
+    full_screen_rect: TypedRect<f32, LayoutPixel>,
+    app_resources: &mut AppResources,
+    render_api: &RenderApi,
+    resource_updates: &mut Vec<ResourceUpdate>)
+{
+    let rect_idx = paint_node.node_id;
+    let bounds = solved_layout.rects.get(&rect_idx).cloned();
+    // the relative lengths are resolved against the same font size as in the layout
+    let context = solved_layout.length_contexts.get(&rect_idx).cloned()
+        .unwrap_or(LengthContext { viewport: full_screen_rect.size, .. Default::default() });
+
+    let has_stacking_context = match bounds {
+        Some(bounds) => push_stacking_context(builder, &arena[rect_idx].data.style, bounds, &context, full_screen_rect),
+        None => false,
+    };
+
+    let is_visible = arena[rect_idx].data.style.is_visible();
+
+    if let (Some(bounds), true) = (bounds, is_visible) {
+        let content_bounds = solved_layout.content_boxes.get(&rect_idx).cloned().unwrap_or(bounds);
+        displaylist_handle_rect(
+            builder,
+            rect_idx,
+            arena,
+            &node_arena[rect_idx].data.node_type,
+            bounds,
+            content_bounds,
+            &context,
+            full_screen_rect,
+            app_resources,
+            render_api,
+            resource_updates);
+    }
+
+    for child in &paint_node.children {
+        push_rectangles_into_displaylist(
+            builder,
+            child,
+            arena,
+            node_arena,
+            solved_layout,
+            full_screen_rect,
+            app_resources,
+            render_api,
+            resource_updates);
+    }
+
+    if has_stacking_context {
+        builder.pop_stacking_context();
+    }
+}
+
+/// Pushes a stacking context for the `opacity`, `transform` and `filter` of a rectangle.
+/// Returns `false` (and doesn't push anything) if the rectangle has none of these properties.
+fn push_stacking_context(
+    builder: &mut DisplayListBuilder,
+    style: &RectStyle,
+    bounds: TypedRect<f32, LayoutPixel>,
+    context: &LengthContext,
+    full_screen_rect: TypedRect<f32, LayoutPixel>)
+-> bool
+{
+    let transform = style.transform.as_ref().and_then(|transform| {
+        if transform.0.is_empty() {
+            None
+        } else {
+            Some(PropertyBinding::Value(transform.to_layout_transform(&bounds, context)))
+        }
+    });
+
+    // The filters are applied first, then the opacity
+    let mut filters = style.filter.as_ref().and_then(|filter| {
+        Some(filter.0.iter().map(|function| get_filter_op(function, context)).collect::<Vec<FilterOp>>())
+    }).unwrap_or_default();
+
+    if let Some(StyleOpacity(opacity)) = style.opacity {
+        if opacity < 1.0 {
+            filters.push(FilterOp::Opacity(PropertyBinding::Value(opacity), opacity));
+        }
+    }
+
+    if transform.is_none() && filters.is_empty() {
+        return false;
+    }
+
+    // The bounds of the children are relative to the window, not to the stacking
+    // context, so the origin of the stacking context has to stay at (0, 0)
+    builder.push_stacking_context(
+        &LayoutPrimitiveInfo::new(full_screen_rect),
+        None,
+        transform,
+        TransformStyle::Flat,
+        None,
+        MixBlendMode::Normal,
+        filters,
+        GlyphRasterSpace::Screen);
+
+    true
+}
+
+fn get_filter_op(function: &FilterFunction, context: &LengthContext) -> FilterOp {
+    use css_parser::FilterFunction::*;
+    match *function {
+        Blur(radius) => FilterOp::Blur(radius.resolve(context, 0.0)),
+        Brightness(amount) => FilterOp::Brightness(amount),
+        Contrast(amount) => FilterOp::Contrast(amount),
+        Grayscale(amount) => FilterOp::Grayscale(amount),
+        HueRotate(deg) => FilterOp::HueRotate(deg),
+        Invert(amount) => FilterOp::Invert(amount),
+        Opacity(amount) => FilterOp::Opacity(PropertyBinding::Value(amount), amount),
+        Saturate(amount) => FilterOp::Saturate(amount),
+        Sepia(amount) => FilterOp::Sepia(amount),
+    }
+}
+
 fn displaylist_handle_rect<'a>(
     builder: &mut DisplayListBuilder,
     rect_idx: NodeId,
     arena: &Arena<DisplayRectangle<'a>>,
     html_node: &NodeType,
     bounds: TypedRect<f32, LayoutPixel>,
+    content_bounds: TypedRect<f32, LayoutPixel>,
+    context: &LengthContext,
     full_screen_rect: TypedRect<f32, LayoutPixel>,
     app_resources: &mut AppResources,
     render_api: &RenderApi,
@@ -384,12 +602,14 @@ fn displaylist_handle_rect<'a>(
         push_rect(&info, builder, bg_col);
     }
 
-    if let Some(ref bg) = rect.style.background {
-        push_background(
+    if let Some(ref backgrounds) = rect.style.background {
+        push_backgrounds(
             &info,
             &bounds,
             builder,
-            bg,
+            backgrounds,
+            &rect.style,
+            context,
             &app_resources);
     };
 
@@ -403,7 +623,9 @@ fn displaylist_handle_rect<'a>(
     push_border(
         &info,
         builder,
-        &rect.style);
+        &rect.style,
+        context,
+        &app_resources);
 
     let (horz_alignment, vert_alignment) = determine_text_alignment(rect_idx, arena);
 
@@ -416,9 +638,12 @@ fn displaylist_handle_rect<'a>(
                 &TextInfo::Uncached(text.clone()),
                 builder,
                 &rect.style,
+                context,
                 app_resources,
                 &render_api,
                 &bounds,
+                // the text is placed inside of the padding
+                &content_bounds,
                 resource_updates,
                 horz_alignment,
                 vert_alignment);
@@ -429,9 +654,12 @@ fn displaylist_handle_rect<'a>(
                 &TextInfo::Cached(*text_id),
                 builder,
                 &rect.style,
+                context,
                 app_resources,
                 &render_api,
                 &bounds,
+                // the text is placed inside of the padding
+                &content_bounds,
                 resource_updates,
                 horz_alignment,
                 vert_alignment);
@@ -514,6 +742,10 @@ fn determine_text_alignment<'a>(rect_idx: NodeId, arena: &Arena<DisplayRectangle
         horz_alignment = text_align;
     }
 
+    if let Some(vertical_align) = rect.data.style.vertical_align {
+        vert_alignment = vertical_align;
+    }
+
     (horz_alignment, vert_alignment)
 }
 
@@ -532,9 +764,11 @@ fn push_text(
     text: &TextInfo,
     builder: &mut DisplayListBuilder,
     style: &RectStyle,
+    context: &LengthContext,
     app_resources: &mut AppResources,
     render_api: &RenderApi,
     bounds: &TypedRect<f32, LayoutPixel>,
+    content_bounds: &TypedRect<f32, LayoutPixel>,
     resource_updates: &mut Vec<ResourceUpdate>,
     horz_alignment: TextAlignmentHorz,
     vert_alignment: TextAlignmentVert)
@@ -553,10 +787,12 @@ fn push_text(
         None => return,
     };
 
-    let font_size = style.font_size.unwrap_or(DEFAULT_FONT_SIZE);
-    let font_size_app_units = Au((font_size.0.to_pixels() as i32) * AU_PER_PX as i32);
+    // the computed font size of the node, `em`, `%`, `vw` etc. are resolved by the layout
+    let font_size = FontSize::px(context.font_size);
+    let font_size_app_units = Au::from_f32_px(context.font_size);
     let font_id = font_family.fonts.get(0).unwrap_or(&DEFAULT_BUILTIN_FONT_SANS_SERIF);
-    let font_result = push_font(font_id, font_size_app_units, resource_updates, app_resources, render_api);
+    let font_id = app_resources.get_font_face(font_id, style.font_weight.unwrap_or_default(), style.font_style.unwrap_or_default());
+    let font_result = push_font(&font_id, font_size_app_units, resource_updates, app_resources, render_api);
 
     let font_instance_key = match font_result {
         Some(f) => f,
@@ -565,6 +801,10 @@ fn push_text(
 
     let line_height = style.line_height;
 
+    // `em` is relative to the font size of the text
+    let letter_spacing = style.letter_spacing.and_then(|s| Some(s.0.resolve(context, 0.0))).unwrap_or(0.0);
+    let word_spacing = style.word_spacing.and_then(|s| Some(s.0.resolve(context, 0.0))).unwrap_or(0.0);
+
     let overflow_behaviour = style.overflow.unwrap_or(LayoutOverflow::default());
 
     let scrollbar_style = ScrollbarInfo {
@@ -577,12 +817,15 @@ fn push_text(
 
     let (positioned_glyphs, scrollbar_info) = text_layout::get_glyphs(
         app_resources,
-        bounds,
+        content_bounds,
         horz_alignment,
         vert_alignment,
         &font_id,
         &font_size,
         line_height,
+        letter_spacing,
+        word_spacing,
+        style.text_transform.unwrap_or_default(),
         text,
         &overflow_behaviour,
         &scrollbar_style
@@ -602,15 +845,30 @@ fn push_text(
 
     builder.push_text(&info, &positioned_glyphs, font_instance_key, font_color, Some(options));
 
+    // Underline, overline and line-through are drawn in the color of the text
+    if let Some(text_decoration) = style.text_decoration {
+        let font = &app_resources.font_data[&font_id].0;
+        for line in text_layout::get_text_decoration_rects(&positioned_glyphs, font, &font_size, text_decoration) {
+            let line_info = PrimitiveInfo {
+                rect: line,
+                clip_rect: info.clip_rect,
+                is_backface_visible: false,
+                tag: info.tag,
+            };
+            builder.push_rect(&line_info, font_color);
+        }
+    }
+
     use text_layout::TextOverflow;
 
     // If the rectangle should have a scrollbar, push a scrollbar onto the display list
     // TODO !!!
+    let border_widths = style.border.and_then(|border| Some(border.get_widths(context)));
     if let TextOverflow::IsOverflowing(amount_vert) = scrollbar_info.vertical {
-        push_scrollbar(builder, &overflow_behaviour, &scrollbar_info, &scrollbar_style, bounds, &style.border)
+        push_scrollbar(builder, &overflow_behaviour, &scrollbar_info, &scrollbar_style, bounds, border_widths)
     }
     if let TextOverflow::IsOverflowing(amount_horz) = scrollbar_info.horizontal {
-        push_scrollbar(builder, &overflow_behaviour, &scrollbar_info, &scrollbar_style, bounds, &style.border)
+        push_scrollbar(builder, &overflow_behaviour, &scrollbar_info, &scrollbar_style, bounds, border_widths)
     }
 }
 
@@ -622,14 +880,14 @@ fn push_scrollbar(
     scrollbar_info: &TextOverflowPass2,
     scrollbar_style: &ScrollbarInfo,
     bounds: &TypedRect<f32, LayoutPixel>,
-    border: &Option<(BorderWidths, BorderDetails)>)
+    border_widths: Option<BorderWidths>)
 {
     use euclid::TypedPoint2D;
 
     // The border is inside the rectangle - subtract the border width on the left and bottom side,
     // so that the scrollbar is laid out correctly
     let mut bounds = *bounds;
-    if let Some((border_widths, _)) = border {
+    if let Some(border_widths) = border_widths {
         bounds.size.width -= border_widths.left;
         bounds.size.height -= border_widths.bottom;
     }
@@ -752,6 +1010,9 @@ fn push_triangle(
     builder.push_border(&triangle_rect_info, border_widths, border_details);
 }
 
+/// Pushes the shadows of the `box-shadow` that have the given clip mode, starting
+/// with the last one, so that the first shadow is painted on top.
+///
 /// WARNING: For "inset" shadows, you must push a clip ID first, otherwise the
 /// shadow will not show up.
 ///
@@ -765,7 +1026,7 @@ fn push_box_shadow(
     full_screen_rect: &TypedRect<f32, LayoutPixel>,
     shadow_type: BoxShadowClipMode)
 {
-    let pre_shadow = match style.box_shadow {
+    let pre_shadows = match style.box_shadow {
         Some(ref ps) => ps,
         None => return,
     };
@@ -773,75 +1034,237 @@ fn push_box_shadow(
     // The pre_shadow is missing the BorderRadius & LayoutRect
     let border_radius = style.border_radius.unwrap_or(BorderRadius::zero());
 
-    if pre_shadow.clip_mode != shadow_type {
-        return;
-    }
+    for pre_shadow in pre_shadows.0.iter().rev().filter(|pre_shadow| pre_shadow.clip_mode == shadow_type) {
+        let clip_rect = if pre_shadow.clip_mode == BoxShadowClipMode::Inset {
+            // inset shadows do not work like outset shadows
+            // for inset shadows, you have to push a clip ID first, so that they are
+            // clipped to the bounds -we trust that the calling function knows to do this
+            *bounds
+        } else {
+            let clip_rect = get_outset_shadow_clip_rect(bounds, pre_shadow);
+            // prevent shadows that are larger than the full screen
+            clip_rect.intersection(full_screen_rect).unwrap_or(clip_rect)
+        };
 
-    let clip_rect = if pre_shadow.clip_mode == BoxShadowClipMode::Inset {
-        // inset shadows do not work like outset shadows
-        // for inset shadows, you have to push a clip ID first, so that they are
-        // clipped to the bounds -we trust that the calling function knows to do this
-        *bounds
-    } else {
-        // calculate the maximum extent of the outset shadow
-        let mut clip_rect = *bounds;
+        let info = LayoutPrimitiveInfo::with_clip_rect(LayoutRect::zero(), clip_rect);
+        builder.push_box_shadow(&info, *bounds, pre_shadow.offset, pre_shadow.color,
+                                 pre_shadow.blur_radius, pre_shadow.spread_radius,
+                                 border_radius, pre_shadow.clip_mode);
+    }
+}
 
-        let origin_displace = pre_shadow.spread_radius - pre_shadow.blur_radius;
-        clip_rect.origin.x = clip_rect.origin.x + pre_shadow.offset.x - origin_displace;
-        clip_rect.origin.y = clip_rect.origin.y + pre_shadow.offset.y - origin_displace;
+/// Returns the maximum extent of an outset shadow: the bounds, moved by the offset of the
+/// shadow and grown by its spread radius (or shrunk, for a negative spread) and its blur
+fn get_outset_shadow_clip_rect(bounds: &TypedRect<f32, LayoutPixel>, pre_shadow: &BoxShadowPreDisplayItem)
+-> TypedRect<f32, LayoutPixel>
+{
+    // the blur fades out well within three blur radii
+    let extent = pre_shadow.spread_radius + pre_shadow.blur_radius * 3.0;
+    bounds.translate(&pre_shadow.offset).inflate(extent, extent)
+}
 
-        let spread = (pre_shadow.spread_radius * 2.0) + (pre_shadow.blur_radius * 2.0);
-        clip_rect.size.height = clip_rect.size.height + spread;
-        clip_rect.size.width = clip_rect.size.width + spread;
+#[inline]
+/// Pushes the layers of the `background`, starting with the last one, so that the first layer
+/// is painted on top. If there are more layers than values of `background-size`, `-position`
+/// or `-repeat`, the values are repeated.
+fn push_backgrounds(
+    info: &PrimitiveInfo<LayoutPixel>,
+    bounds: &TypedRect<f32, LayoutPixel>,
+    builder: &mut DisplayListBuilder,
+    backgrounds: &StyleBackgrounds,
+    style: &RectStyle,
+    context: &LengthContext,
+    app_resources: &AppResources)
+{
+    let sizes = style.background_size.as_ref().map(|s| &s.0[..]).unwrap_or(&[]);
+    let positions = style.background_position.as_ref().map(|p| &p.0[..]).unwrap_or(&[]);
+    let repeats = style.background_repeat.as_ref().map(|r| &r.0[..]).unwrap_or(&[]);
 
-        // prevent shadows that are larger than the full screen
-        clip_rect.intersection(full_screen_rect).unwrap_or(clip_rect)
-    };
+    for (layer_idx, background) in backgrounds.0.iter().enumerate().rev() {
+        push_background(
+            info,
+            bounds,
+            builder,
+            background,
+            get_layer_value(sizes, layer_idx),
+            get_layer_value(positions, layer_idx),
+            get_layer_value(repeats, layer_idx),
+            context,
+            app_resources);
+    }
+}
 
-    let info = LayoutPrimitiveInfo::with_clip_rect(LayoutRect::zero(), clip_rect);
-    builder.push_box_shadow(&info, *bounds, pre_shadow.offset, pre_shadow.color,
-                             pre_shadow.blur_radius, pre_shadow.spread_radius,
-                             border_radius, pre_shadow.clip_mode);
+/// Returns the value of a `background-size`, `-position` or `-repeat` for the given layer
+fn get_layer_value<T: Copy + Default>(values: &[T], layer_idx: usize) -> T {
+    if values.is_empty() {
+        T::default()
+    } else {
+        values[layer_idx % values.len()]
+    }
 }
 
-#[inline]
 fn push_background(
     info: &PrimitiveInfo<LayoutPixel>,
     bounds: &TypedRect<f32, LayoutPixel>,
     builder: &mut DisplayListBuilder,
     background: &Background,
+    size: BackgroundSize,
+    position: BackgroundPosition,
+    repeat: BackgroundRepeat,
+    context: &LengthContext,
     app_resources: &AppResources)
 {
+    use images::ImageState;
+
+    // Images have an intrinsic size, gradients don't
+    let image_info = match background {
+        Background::Image(css_image_id) => {
+            let image_info = app_resources.css_ids_to_image_ids.get(&css_image_id.0)
+                .and_then(|image_id| app_resources.images.get(image_id))
+                .and_then(|image_state| match image_state {
+                    ImageState::Uploaded(image_info) => Some(image_info),
+                    _ => None,
+                });
+            match image_info {
+                Some(image_info) => Some(image_info),
+                None => return,
+            }
+        },
+        _ => None,
+    };
+
+    let intrinsic_size = image_info.and_then(|image_info| {
+        let size = image_info.descriptor.size;
+        Some(LayoutSize::new(size.width as f32, size.height as f32))
+    });
+
+    let tile_size = get_background_tile_size(size, intrinsic_size, bounds.size, context);
+    if tile_size.width <= 0.0 || tile_size.height <= 0.0 {
+        return;
+    }
+
+    let tile_origin = get_background_tile_origin(position, tile_size, bounds, context);
+
+    // webrender repeats the tile over the whole rect, the tiles outside of the bounds are clipped
+    let info = LayoutPrimitiveInfo {
+        rect: get_background_tiled_rect(repeat, tile_origin, tile_size, bounds),
+        .. *info
+    };
+
     match background {
+        // The points of the gradients are relative to the top left corner of the tile
         Background::RadialGradient(gradient) => {
-            let mut stops: Vec<GradientStop> = gradient.stops.iter().map(|gradient_pre|
-                GradientStop {
-                    offset: gradient_pre.offset.unwrap(),
-                    color: gradient_pre.color,
-                }).collect();
-            let center = bounds.bottom_left(); // TODO - expose in CSS
-            let radius = TypedSize2D::new(40.0, 40.0); // TODO - expose in CSS
-            let gradient = builder.create_radial_gradient(center, radius, stops, gradient.extend_mode);
-            builder.push_radial_gradient(&info, gradient, bounds.size, LayoutSize::zero());
+            let (center, radii) = gradient.get_center_and_radii(tile_size, context);
+            // the stops are placed along the horizontal radius
+            let stops = css_parser::resolve_gradient_stops(&gradient.stops, radii.width, context);
+            let gradient = builder.create_radial_gradient(center, radii, stops, gradient.extend_mode);
+            builder.push_radial_gradient(&info, gradient, tile_size, LayoutSize::zero());
         },
         Background::LinearGradient(gradient) => {
-            let mut stops: Vec<GradientStop> = gradient.stops.iter().map(|gradient_pre|
-                GradientStop {
-                    offset: gradient_pre.offset.unwrap(),
-                    color: gradient_pre.color,
-                }).collect();
-            let (begin_pt, end_pt) = gradient.direction.to_points(&bounds);
+            let (begin_pt, end_pt) = gradient.direction.to_points(&LayoutRect::new(LayoutPoint::zero(), tile_size));
+            let stops = css_parser::resolve_gradient_stops(&gradient.stops, (end_pt - begin_pt).length(), context);
             let gradient = builder.create_gradient(begin_pt, end_pt, stops, gradient.extend_mode);
-            builder.push_gradient(&info, gradient, bounds.size, LayoutSize::zero());
+            builder.push_gradient(&info, gradient, tile_size, LayoutSize::zero());
         },
-        Background::Image(css_image_id) => {
-            if let Some(image_id) = app_resources.css_ids_to_image_ids.get(&css_image_id.0) {
-                push_image(info, builder, bounds, app_resources, image_id);
+        Background::Image(_) => {
+            if let Some(image_info) = image_info {
+                builder.push_image(
+                        &info,
+                        tile_size,
+                        LayoutSize::zero(),
+                        ImageRendering::Auto,
+                        AlphaType::Alpha,
+                        image_info.key);
             }
         }
     }
 }
 
+/// Returns the size of one tile of a background layer. An `auto` size keeps the
+/// `intrinsic_size` of an image, gradients are as large as the node.
+fn get_background_tile_size(
+    size: BackgroundSize,
+    intrinsic_size: Option<LayoutSize>,
+    node_size: LayoutSize,
+    context: &LengthContext)
+-> LayoutSize
+{
+    let intrinsic = intrinsic_size.unwrap_or(node_size);
+    if intrinsic.width <= 0.0 || intrinsic.height <= 0.0 {
+        return LayoutSize::zero();
+    }
+
+    let aspect_ratio = intrinsic.width / intrinsic.height;
+
+    match size {
+        BackgroundSize::Cover | BackgroundSize::Contain => {
+            let scale_x = node_size.width / intrinsic.width;
+            let scale_y = node_size.height / intrinsic.height;
+            let scale = if size == BackgroundSize::Cover { scale_x.max(scale_y) } else { scale_x.min(scale_y) };
+            LayoutSize::new(intrinsic.width * scale, intrinsic.height * scale)
+        },
+        BackgroundSize::Exact(width, height) => {
+            let width = width.and_then(|w| Some(w.resolve(context, node_size.width)));
+            let height = height.and_then(|h| Some(h.resolve(context, node_size.height)));
+            match (width, height, intrinsic_size) {
+                (Some(w), Some(h), _) => LayoutSize::new(w, h),
+                (Some(w), None, Some(_)) => LayoutSize::new(w, w / aspect_ratio),
+                (None, Some(h), Some(_)) => LayoutSize::new(h * aspect_ratio, h),
+                (Some(w), None, None) => LayoutSize::new(w, node_size.height),
+                (None, Some(h), None) => LayoutSize::new(node_size.width, h),
+                (None, None, _) => intrinsic,
+            }
+        },
+    }
+}
+
+/// Returns the top left corner of the tile that is placed at the `background-position`,
+/// `%` is relative to the free space, i.e. the size of the node minus the size of the tile
+fn get_background_tile_origin(
+    position: BackgroundPosition,
+    tile_size: LayoutSize,
+    bounds: &LayoutRect,
+    context: &LengthContext)
+-> LayoutPoint
+{
+    LayoutPoint::new(
+        bounds.origin.x + position.horizontal.resolve(context, bounds.size.width - tile_size.width),
+        bounds.origin.y + position.vertical.resolve(context, bounds.size.height - tile_size.height))
+}
+
+/// Returns the rect that webrender fills with tiles, starting at its top left corner. On a
+/// repeated axis, the rect starts at the first tile that is (partially) inside of the bounds
+/// and ends at the end of the bounds. Otherwise, the rect only contains the one tile.
+fn get_background_tiled_rect(
+    repeat: BackgroundRepeat,
+    tile_origin: LayoutPoint,
+    tile_size: LayoutSize,
+    bounds: &LayoutRect)
+-> LayoutRect
+{
+    fn repeat_axis(tile_start: f32, tile_length: f32, bounds_start: f32, bounds_end: f32) -> (f32, f32) {
+        let first_tile_start = tile_start - ((tile_start - bounds_start) / tile_length).ceil() * tile_length;
+        (first_tile_start, bounds_end - first_tile_start)
+    }
+
+    let repeat_x = repeat == BackgroundRepeat::Repeat || repeat == BackgroundRepeat::RepeatX;
+    let repeat_y = repeat == BackgroundRepeat::Repeat || repeat == BackgroundRepeat::RepeatY;
+
+    let (x, width) = if repeat_x {
+        repeat_axis(tile_origin.x, tile_size.width, bounds.min_x(), bounds.max_x())
+    } else {
+        (tile_origin.x, tile_size.width)
+    };
+
+    let (y, height) = if repeat_y {
+        repeat_axis(tile_origin.y, tile_size.height, bounds.min_y(), bounds.max_y())
+    } else {
+        (tile_origin.y, tile_size.height)
+    };
+
+    LayoutRect::new(LayoutPoint::new(x, y), LayoutSize::new(width, height))
+}
+
 fn push_image(
     info: &PrimitiveInfo<LayoutPixel>,
     builder: &mut DisplayListBuilder,
@@ -867,19 +1290,54 @@ fn push_image(
 }
 
 #[inline]
+/// Pushes the border, as a nine-patch border if a `border-image` is set and its
+/// image is uploaded, otherwise as a normal border with the `border-style` of each side
 fn push_border(
     info: &PrimitiveInfo<LayoutPixel>,
     builder: &mut DisplayListBuilder,
-    style: &RectStyle)
+    style: &RectStyle,
+    context: &LengthContext,
+    app_resources: &AppResources)
 {
-    if let Some((border_widths, mut border_details)) = style.border {
-        if let Some(border_radius) = style.border_radius {
-            if let BorderDetails::Normal(ref mut n) = border_details {
-                n.radius = border_radius;
+    use images::ImageState;
+
+    let border = match style.border {
+        Some(border) => border,
+        None => return,
+    };
+
+    let border_widths = border.get_widths(context);
+
+    let border_image_info = style.border_image.as_ref().and_then(|border_image| {
+        app_resources.css_ids_to_image_ids.get(&border_image.source.0)
+            .and_then(|image_id| app_resources.images.get(image_id))
+            .and_then(|image_state| match image_state {
+                ImageState::Uploaded(image_info) => Some((border_image, image_info)),
+                _ => None,
+            })
+    });
+
+    let border_details = match border_image_info {
+        Some((border_image, image_info)) => BorderDetails::NinePatch(NinePatchBorder {
+            source: NinePatchBorderSource::Image(image_info.key),
+            width: image_info.descriptor.size.width,
+            height: image_info.descriptor.size.height,
+            slice: border_image.slice,
+            fill: border_image.fill,
+            repeat_horizontal: border_image.repeat_horizontal,
+            repeat_vertical: border_image.repeat_vertical,
+            outset: SideOffsets2D::zero(),
+        }),
+        None => {
+            let mut normal_border = border.get_normal_border();
+            if let Some(border_radius) = style.border_radius {
+                normal_border.radius = border_radius;
             }
-        }
-        builder.push_border(info, border_widths, border_details);
-    }
+            BorderDetails::Normal(normal_border)
+        },
+    };
+
+    builder.push_border(info, border_widths, border_details);
 }
 
 #[inline]
@@ -898,11 +1356,24 @@ fn push_font(
         return None;
     }
 
-    let &(ref font, _, ref font_state) = match app_resources.font_data.get(font_id) {
-        Some(f) => f,
+    let font_state = match app_resources.font_data.get_mut(font_id) {
+        Some(&mut (_, _, ref mut font_state)) => font_state,
         None => return None,
     };
 
+    // Font faces are loaded while the display list is built (see `AppResources::get_font_face`),
+    // after the fonts have been uploaded - so the font may have to be uploaded right here
+    let bytes_to_upload = match *font_state {
+        FontState::ReadyForUpload(ref bytes, index) => Some((bytes.clone(), index)),
+        _ => None,
+    };
+
+    if let Some((bytes, index)) = bytes_to_upload {
+        let key = render_api.generate_font_key();
+        resource_updates.push(ResourceUpdate::AddFont(AddFont::Raw(key, bytes, index)));
+        *font_state = FontState::Uploaded(key);
+    }
+
     match *font_state {
         FontState::Uploaded(font_key) => {
             let font_sizes_hashmap = app_resources.fonts.entry(font_key)
@@ -934,7 +1405,12 @@ fn push_font(
 }
 
 /// Populate and parse the CSS style properties
-fn populate_css_properties(rect: &mut DisplayRectangle, css_overrides: &FastHashMap<String, ParsedCssProperty>)
+/// Applies the cascaded declarations of the node, followed by the
+/// values of its running transitions and animations
+fn populate_css_properties(
+    rect: &mut DisplayRectangle,
+    css_overrides: &FastHashMap<String, ParsedCssProperty>,
+    animated_properties: &[ParsedCssProperty])
 {
     use css_parser::ParsedCssProperty::{self, *};
 
@@ -943,9 +1419,19 @@ fn populate_css_properties(rect: &mut DisplayRectangle, css_overrides: &FastHash
             BorderRadius(b)             => { rect.style.border_radius = Some(*b);                   },
             BackgroundColor(c)          => { rect.style.background_color = Some(*c);                },
             TextColor(t)                => { rect.style.font_color = Some(*t);                      },
-            Border(widths, details)     => { rect.style.border = Some((*widths, *details));         },
+            Border(b)                   => {
+                if let Some(ref mut existing_border) = rect.style.border {
+                    existing_border.merge(b);
+                } else {
+                    rect.style.border = Some(*b)
+                }
+            },
+            BorderImage(i)              => { rect.style.border_image = i.clone();                   },
             Background(b)               => { rect.style.background = Some(b.clone());               },
-            FontSize(f)                 => { rect.style.font_size = Some(*f);                       },
+            BackgroundSize(s)           => { rect.style.background_size = Some(s.clone());          },
+            BackgroundPosition(p)       => { rect.style.background_position = Some(p.clone());      },
+            BackgroundRepeat(r)         => { rect.style.background_repeat = Some(r.clone());        },
+            FontSize(f)                 => { rect.style.font_size = Some(*f); rect.layout.font_size = Some(*f); },
             FontFamily(f)               => { rect.style.font_family = Some(f.clone());              },
             Overflow(o)                 => {
                 if let Some(ref mut existing_overflow) = rect.style.overflow {
@@ -955,8 +1441,15 @@ fn populate_css_properties(rect: &mut DisplayRectangle, css_overrides: &FastHash
                 }
             },
             TextAlign(ta)               => { rect.style.text_align = Some(*ta);                     },
-            BoxShadow(opt_box_shadow)   => { rect.style.box_shadow = *opt_box_shadow;               },
+            BoxShadow(s)                => { rect.style.box_shadow = Some(s.clone());               },
             LineHeight(lh)              => { rect.style.line_height = Some(*lh);                     },
+            FontWeight(w)               => { rect.style.font_weight = Some(*w);                     },
+            FontStyle(s)                => { rect.style.font_style = Some(*s);                      },
+            LetterSpacing(s)            => { rect.style.letter_spacing = Some(*s);                  },
+            WordSpacing(s)              => { rect.style.word_spacing = Some(*s);                    },
+            TextDecoration(d)           => { rect.style.text_decoration = Some(*d);                 },
+            TextTransform(t)            => { rect.style.text_transform = Some(*t);                  },
+            VerticalAlign(a)            => { rect.style.vertical_align = Some(*a);                  },
 
             Width(w)                    => { rect.layout.width = Some(*w);                          },
             Height(h)                   => { rect.layout.height = Some(*h);                         },
@@ -964,12 +1457,43 @@ fn populate_css_properties(rect: &mut DisplayRectangle, css_overrides: &FastHash
             MinHeight(mh)               => { rect.layout.min_height = Some(*mh);                    },
             MaxWidth(mw)                => { rect.layout.max_width = Some(*mw);                     },
             MaxHeight(mh)               => { rect.layout.max_height = Some(*mh);                    },
+            Padding(p)                  => {
+                if let Some(ref mut existing_padding) = rect.layout.padding {
+                    existing_padding.0.merge(&p.0);
+                } else {
+                    rect.layout.padding = Some(*p)
+                }
+            },
+            Margin(m)                   => {
+                if let Some(ref mut existing_margin) = rect.layout.margin {
+                    existing_margin.0.merge(&m.0);
+                } else {
+                    rect.layout.margin = Some(*m)
+                }
+            },
 
             FlexWrap(w)                 => { rect.layout.wrap = Some(*w);                           },
             FlexDirection(d)            => { rect.layout.direction = Some(*d);                      },
             JustifyContent(j)           => { rect.layout.justify_content = Some(*j);                },
             AlignItems(a)               => { rect.layout.align_items = Some(*a);                    },
             AlignContent(a)             => { rect.layout.align_content = Some(*a);                  },
+
+            Position(p)                 => { rect.layout.position = Some(*p);                       },
+            Top(t)                      => { rect.layout.top = Some(*t);                            },
+            Right(r)                    => { rect.layout.right = Some(*r);                          },
+            Bottom(b)                   => { rect.layout.bottom = Some(*b);                         },
+            Left(l)                     => { rect.layout.left = Some(*l);                           },
+            ZIndex(z)                   => { rect.layout.z_index = Some(*z);                        },
+            Display(d)                  => { rect.layout.display = Some(*d);                        },
+            Visibility(v)               => { rect.style.visibility = Some(*v);                      },
+            Cursor(cursor)              => { rect.style.cursor = Some(*cursor);                     },
+
+            Opacity(o)                  => { rect.style.opacity = Some(*o);                         },
+            Transform(t)                => { rect.style.transform = Some(t.clone());                },
+            Filter(f)                   => { rect.style.filter = Some(f.clone());                   },
+
+            // transitions and animations are run by the `AnimationState`, see `UiDescription::update_animations`
+            Transition(_) | Animation(_) => { },
         }
     }
 
@@ -992,142 +1516,141 @@ fn populate_css_properties(rect: &mut DisplayRectangle, css_overrides: &FastHash
                 } else {
                     apply_parsed_css_property(rect, &dynamic_property.default);
                 }
-            }
+            },
+            // var() references and keywords are resolved during the cascade
+            CustomProperty(_) | Unresolved(_) | Keyword(_) => { },
         }
     }
-}
 
-// Returns the constraints for one rectangle
-fn create_layout_constraints<'a>(
-    rect: &DisplayRectangle,
-    rect_id: NodeId,
-    arena: &Arena<DisplayRectangle<'a>>,
-    window_size: &WindowSize)
--> Vec<CssConstraint>
-{
-    use css_parser;
-    use cassowary::strength::*;
-    use constraints::{SizeConstraint, Strength};
-
-    let mut layout_constraints = Vec::<CssConstraint>::new();
-    /*
-    let max_width = arena.get_wh_for_rectangle(rect_id, WidthOrHeight::Width)
-                         .unwrap_or(window_size.width as f32);
-    */
-    layout_constraints.push(CssConstraint::Size((SizeConstraint::Width(200.0), Strength(STRONG))));
-    layout_constraints.push(CssConstraint::Size((SizeConstraint::Height(200.0), Strength(STRONG))));
-
-    layout_constraints
+    for animated_property in animated_properties {
+        apply_parsed_css_property(rect, animated_property);
+    }
 }
 
-fn css_constraints_to_cassowary_constraints(rect: &DisplayRect, css: &Vec<CssConstraint>)
--> Vec<Constraint>
-{
-    use self::CssConstraint::*;
+// Empty test, for some reason codecov doesn't detect any files (and therefore
+// doesn't report codecov % correctly) except if they have at least one test in
+// the file. This is an empty test, which should be updated later on
+#[test]
+fn __codecov_test_display_list_file() {
 
-    css.iter().flat_map(|constraint|
-        match *constraint {
-            Size((constraint, strength)) => {
-                constraint.build(&rect, strength.0)
-            }
-            Padding((constraint, strength, padding)) => {
-                constraint.build(&rect, strength.0, padding.0)
-            }
-        }
-    ).collect()
 }
 
-// Layout / tracing-related functions
+#[test]
+fn test_solved_layout_lookup() {
+    let node_id = NodeId::new(0);
+    let bounds = LayoutRect::new(LayoutPoint::new(10.0, 20.0), LayoutSize::new(30.0, 40.0));
+
+    let mut solved_layout = SolvedLayout::empty();
+    solved_layout.rects.insert(node_id, bounds);
+    solved_layout.css_ids.insert(String::from("main"), node_id);
+    solved_layout.tags.insert(5, node_id);
+
+    assert_eq!(solved_layout.get_bounds(node_id), Some(bounds));
+    assert_eq!(solved_layout.get_bounds_by_id("main"), Some(bounds));
+    assert_eq!(solved_layout.get_bounds_by_tag(5), Some(bounds));
+    assert_eq!(solved_layout.get_bounds_by_id("other"), None);
+}
 
-// What constraint (width or height) to search for when looking for a fitting width / height constraint
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum WidthOrHeight {
-    Width,
-    Height,
+#[test]
+fn test_solved_layout_hit_test() {
+    let parent = NodeId::new(0);
+    let child = NodeId::new(1);
+    let overlay = NodeId::new(2);
+
+    let mut solved_layout = SolvedLayout::empty();
+    solved_layout.rects.insert(parent, LayoutRect::new(LayoutPoint::new(0.0, 0.0), LayoutSize::new(100.0, 100.0)));
+    solved_layout.rects.insert(child, LayoutRect::new(LayoutPoint::new(10.0, 10.0), LayoutSize::new(20.0, 20.0)));
+    solved_layout.paint_order = vec![parent, child];
+
+    assert_eq!(solved_layout.hit_test(LayoutPoint::new(15.0, 15.0)), Some(child));
+    assert_eq!(solved_layout.hit_test(LayoutPoint::new(50.0, 50.0)), Some(parent));
+    assert_eq!(solved_layout.hit_test(LayoutPoint::new(150.0, 50.0)), None);
+
+    // the paint order decides which node is on top, not the order of the nodes in the DOM
+    solved_layout.rects.insert(overlay, LayoutRect::new(LayoutPoint::new(0.0, 0.0), LayoutSize::new(50.0, 50.0)));
+    solved_layout.paint_order = vec![overlay, parent, child];
+    assert_eq!(solved_layout.hit_test(LayoutPoint::new(40.0, 40.0)), Some(parent));
+    assert_eq!(solved_layout.hit_test(LayoutPoint::new(15.0, 15.0)), Some(child));
 }
 
-impl<'a> Arena<DisplayRectangle<'a>> {
+#[test]
+fn test_solved_layout_hit_test_transformed() {
+    let node = NodeId::new(0);
 
-    /// Recursive algorithm for getting the dimensions of a rectangle
-    ///
-    /// This function can be used on any rectangle to get the maximum allowed width
-    /// (for inserting the width / height constraint into the layout solver).
-    /// It simply traverses upwards through the nodes, until it finds a matching min-width / width
-    /// constraint, returns None, if the root node is reached (with no constraints)
-    ///
-    /// Usually, you'd use it like:
-    ///
-    /// ```no_run,ignore
-    /// let max_width = arena.get_wh_for_rectangle(id, WidthOrHeight::Width)
-    ///                      .unwrap_or(window_dimensions.width);
-    /// ```
-    fn get_wh_for_rectangle(&self, id: NodeId, field: WidthOrHeight) -> Option<f32> {
-
-        use self::WidthOrHeight::*;
-
-        let node = &self[id];
-
-        macro_rules! get_wh {
-            ($field_name:ident, $min_field:ident) => ({
-                let mut $field_name: Option<f32> = None;
-
-                match node.data.layout.$min_field {
-                    Some(m_w) => {
-                        let m_w_px = m_w.0.to_pixels();
-                        match node.data.layout.$field_name {
-                            Some(w) => {
-                                // width + min_width
-                                let w_px = w.0.to_pixels();
-                                $field_name = Some(m_w_px.max(w_px));
-                            },
-                            None => {
-                                // min_width
-                                $field_name = Some(m_w_px);
-                            }
-                        }
-                    },
-                    None => {
-                        match node.data.layout.$field_name {
-                            Some(w) => {
-                                // width
-                                let w_px = w.0.to_pixels();
-                                $field_name = Some(w_px);
-                            },
-                            None => {
-                                // neither width nor min_width
-                            }
-                        }
-                    }
-                };
+    let mut solved_layout = SolvedLayout::empty();
+    solved_layout.rects.insert(node, LayoutRect::new(LayoutPoint::new(0.0, 0.0), LayoutSize::new(20.0, 20.0)));
+    solved_layout.paint_order = vec![node];
+    solved_layout.transforms.insert(node, LayoutTransform::create_translation(100.0, 0.0, 0.0));
 
-                if $field_name.is_none() {
-                    match node.parent() {
-                        Some(p) => $field_name = self.get_wh_for_rectangle(p, field),
-                        None => { },
-                    }
-                }
+    // the node is hit where it is painted, not at its untransformed bounds
+    assert_eq!(solved_layout.hit_test(LayoutPoint::new(110.0, 10.0)), Some(node));
+    assert_eq!(solved_layout.hit_test(LayoutPoint::new(10.0, 10.0)), None);
+}
 
-                $field_name
-            })
-        }
+#[test]
+fn test_solved_layout_get_cursor() {
+    let parent = NodeId::new(0);
+    let child = NodeId::new(1);
+
+    let mut solved_layout = SolvedLayout::empty();
+    solved_layout.rects.insert(parent, LayoutRect::new(LayoutPoint::new(0.0, 0.0), LayoutSize::new(100.0, 100.0)));
+    solved_layout.rects.insert(child, LayoutRect::new(LayoutPoint::new(10.0, 10.0), LayoutSize::new(20.0, 20.0)));
+    solved_layout.paint_order = vec![parent, child];
+    solved_layout.cursors.insert(child, StyleCursor::Pointer);
+
+    assert_eq!(solved_layout.get_cursor(LayoutPoint::new(15.0, 15.0)), Some(StyleCursor::Pointer));
+    // only the top-most node decides the cursor
+    assert_eq!(solved_layout.get_cursor(LayoutPoint::new(50.0, 50.0)), None);
+    assert_eq!(solved_layout.get_cursor(LayoutPoint::new(150.0, 50.0)), None);
+}
 
-        match field {
-            Width => {
-                let w = get_wh!(width, min_width);
-                w
-            },
-            Height => {
-                let h = get_wh!(height, min_height);
-                h
-            }
-        }
-    }
+#[test]
+fn test_background_tile_geometry() {
+    let context = LengthContext::default();
+    let px = |value| PixelValue::from_metric(CssMetric::Px, value);
+    let percent = |value| PixelValue::from_metric(CssMetric::Percent, value);
+    let node_size = LayoutSize::new(200.0, 100.0);
+    let image_size = Some(LayoutSize::new(50.0, 25.0));
+
+    assert_eq!(get_background_tile_size(BackgroundSize::default(), image_size, node_size, &context), LayoutSize::new(50.0, 25.0));
+    assert_eq!(get_background_tile_size(BackgroundSize::default(), None, node_size, &context), node_size);
+    assert_eq!(get_background_tile_size(BackgroundSize::Cover, image_size, node_size, &context), LayoutSize::new(200.0, 100.0));
+    assert_eq!(get_background_tile_size(BackgroundSize::Contain, Some(LayoutSize::new(50.0, 50.0)), node_size, &context), LayoutSize::new(100.0, 100.0));
+    // `auto` keeps the aspect ratio of the image
+    assert_eq!(get_background_tile_size(BackgroundSize::Exact(Some(percent(50.0)), None), image_size, node_size, &context), LayoutSize::new(100.0, 50.0));
+    assert_eq!(get_background_tile_size(BackgroundSize::Exact(None, Some(px(10.0))), None, node_size, &context), LayoutSize::new(200.0, 10.0));
+
+    let bounds = LayoutRect::new(LayoutPoint::new(10.0, 10.0), node_size);
+    let tile_size = LayoutSize::new(50.0, 25.0);
+    let bottom_right = BackgroundPosition { horizontal: percent(100.0), vertical: percent(100.0) };
+    let offset = BackgroundPosition { horizontal: px(20.0), vertical: percent(50.0) };
+    assert_eq!(get_background_tile_origin(bottom_right, tile_size, &bounds, &context), LayoutPoint::new(160.0, 85.0));
+    assert_eq!(get_background_tile_origin(offset, tile_size, &bounds, &context), LayoutPoint::new(30.0, 47.5));
+
+    // the repeated tiles start before the bounds, so that the tile at the origin is aligned
+    let tile_origin = LayoutPoint::new(30.0, 47.5);
+    assert_eq!(get_background_tiled_rect(BackgroundRepeat::NoRepeat, tile_origin, tile_size, &bounds),
+               LayoutRect::new(tile_origin, tile_size));
+    assert_eq!(get_background_tiled_rect(BackgroundRepeat::RepeatX, tile_origin, tile_size, &bounds),
+               LayoutRect::new(LayoutPoint::new(-20.0, 47.5), LayoutSize::new(230.0, 25.0)));
+    assert_eq!(get_background_tiled_rect(BackgroundRepeat::Repeat, tile_origin, tile_size, &bounds),
+               LayoutRect::new(LayoutPoint::new(-20.0, -2.5), LayoutSize::new(230.0, 112.5)));
 }
 
-// Empty test, for some reason codecov doesn't detect any files (and therefore
-// doesn't report codecov % correctly) except if they have at least one test in
-// the file. This is an empty test, which should be updated later on
 #[test]
-fn __codecov_test_display_list_file() {
+fn test_outset_shadow_clip_rect() {
+    let bounds = LayoutRect::new(LayoutPoint::new(10.0, 10.0), LayoutSize::new(100.0, 50.0));
+    let shadow = BoxShadowPreDisplayItem {
+        offset: LayoutVector2D::new(0.0, 3.0),
+        color: ColorF { r: 0.0, g: 0.0, b: 0.0, a: 0.2 },
+        blur_radius: 1.0,
+        spread_radius: -2.0,
+        clip_mode: BoxShadowClipMode::Outset,
+    };
+    assert_eq!(get_outset_shadow_clip_rect(&bounds, &shadow),
+               LayoutRect::new(LayoutPoint::new(9.0, 12.0), LayoutSize::new(102.0, 52.0)));
 
+    let shadow = BoxShadowPreDisplayItem { blur_radius: 0.0, spread_radius: 5.0, .. shadow };
+    assert_eq!(get_outset_shadow_clip_rect(&bounds, &shadow),
+               LayoutRect::new(LayoutPoint::new(5.0, 8.0), LayoutSize::new(110.0, 60.0)));
 }
@@ -7,9 +7,9 @@ use rusttype::Error as RusttypeError;
 pub(crate) enum FontState {
     // Font is available for the renderer
     Uploaded(FontKey),
-    // Raw bytes for the font, to be uploaded in the next
-    // draw call (for webrenders add_raw_font function)
-    ReadyForUpload(Vec<u8>),
+    // Raw bytes for the font and the index of the font in the font collection,
+    // to be uploaded in the next draw call (for webrenders add_raw_font function)
+    ReadyForUpload(Vec<u8>, u32),
     /// Font that is about to be deleted
     /// We need both the ID (to delete the bytes of the font)
     /// as well as the FontKey to delete all the font instances
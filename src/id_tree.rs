@@ -259,6 +259,11 @@ impl<T> Arena<T> {
         self.nodes_len() == 0
     }
 
+    /// Returns whether the `NodeId` points to a node in this arena
+    pub(crate) fn contains(&self, node_id: NodeId) -> bool {
+        node_id.index.get() < self.nodes_len()
+    }
+
     /// Appends another arena to the end of the current arena.
     /// Highly unsafe if you don't know what you're doing
     pub(crate) fn append(&mut self, other: &mut Arena<T>) {
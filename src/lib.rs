@@ -43,7 +43,6 @@ extern crate euclid;
 extern crate lyon;
 extern crate resvg;
 extern crate webrender;
-extern crate cassowary;
 extern crate twox_hash;
 extern crate simplecss;
 extern crate rusttype;
@@ -53,7 +52,7 @@ extern crate harfbuzz_rs;
 extern crate tinyfiledialogs;
 extern crate clipboard2;
 extern crate font_loader;
-#[macro_use(error, debug, info, log)]
+#[macro_use(error, warn, debug, info, log)]
 extern crate log;
 #[cfg(feature = "logging")]
 extern crate fern;
@@ -85,12 +84,14 @@ mod app;
 mod app_state;
 /// Styling & CSS parsing
 mod css;
+/// CSS transitions and `@keyframes` animations
+mod animation;
 /// Font & image resource handling, lookup and caching
 mod resources;
 /// UI Description & display list handling (webrender)
 mod ui_description;
-/// Constraint handling
-mod constraints;
+/// Flexbox layout solver, calculates the position and size of each rectangle
+mod ui_solver;
 /// Converts the UI description (the styled HTML nodes)
 /// to an actual display list (+ layout)
 mod display_list;
@@ -127,28 +128,40 @@ type FastHashSet<T> = ::std::collections::HashSet<T, ::std::hash::BuildHasherDef
 pub mod prelude {
     pub use app::{App, AppConfig};
     pub use app_state::AppState;
-    pub use css::{Css, FakeCss};
+    pub use css::{Css, FakeCss, CssLoader, CssFileLoader};
     pub use dom::{Dom, NodeType, NodeData, Callback, On, UpdateScreen};
-    pub use traits::{Layout, ModifyAppState};
+    pub use traits::{Layout, ModifyAppState, DefaultStyle};
     pub use window::{MonitorIter, Window, WindowCreateOptions, WindowId,
                      MouseMode, UpdateBehaviour, UpdateMode,
                      WindowMonitorTarget, RendererType, WindowEvent, WindowInfo, ReadOnlyWindow};
-    pub use window_state::WindowState;
+    pub use window_state::{WindowState, ColorScheme};
     pub use images::ImageType;
     pub use text_cache::{TextCache, TextId};
+    pub use id_tree::NodeId;
     pub use css_parser::{
         ParsedCssProperty, BorderRadius, BackgroundColor, TextColor,
         BorderWidths, BorderDetails, Background, FontSize,
+        StyleBorder, StyleBorderSide, StyleBorderImage, BorderStyle, RepeatMode,
+        StyleBackgrounds, BackgroundSize, StyleBackgroundSizes, BackgroundPosition,
+        StyleBackgroundPositions, BackgroundRepeat, StyleBackgroundRepeats,
         FontFamily, TextOverflowBehaviour, TextOverflowBehaviourInner, TextAlignmentHorz,
-        BoxShadowPreDisplayItem, LayoutWidth, LayoutHeight,
+        TextAlignmentVert, FontWeight, FontStyle, LetterSpacing, WordSpacing,
+        TextDecoration, TextTransform,
+        BoxShadowPreDisplayItem, StyleBoxShadows, LayoutWidth, LayoutHeight,
         LayoutMinWidth, LayoutMinHeight, LayoutMaxWidth,
-        LayoutMaxHeight, LayoutWrap, LayoutDirection,
+        LayoutMaxHeight, LayoutPadding, LayoutMargin, LayoutSideOffsets,
+        LayoutWrap, LayoutDirection,
         LayoutJustifyContent, LayoutAlignItems, LayoutAlignContent,
-        LinearGradientPreInfo, RadialGradientPreInfo, CssImageId, FontId,
+        LayoutPosition, LayoutTop, LayoutRight, LayoutBottom, LayoutLeft,
+        LayoutZIndex, LayoutDisplay, StyleVisibility, StyleCursor,
+        LinearGradientPreInfo, RadialGradientPreInfo, RadialGradientSize, CssImageId, FontId,
 
         LayoutPixel, TypedSize2D, BoxShadowClipMode, ColorU, ColorF, LayoutVector2D,
         Gradient, SideOffsets2D, RadialGradient, LayoutPoint, LayoutSize,
-        ExtendMode, PixelValue, PercentageValue,
+        ExtendMode, PixelValue, PercentageValue, LayoutRect, ToCssString,
+        StyleTransitions, Transition, StyleAnimations, Animation, TimingFunction,
+        AnimationIterationCount, AnimationDirection, AnimationFillMode,
+        StyleOpacity, StyleTransforms, TransformFunction, StyleFilters, FilterFunction,
     };
     pub use glium::glutin::dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize};
     pub use rusttype::Font;
@@ -160,14 +173,16 @@ pub mod prelude {
 /// Re-exports of errors
 pub mod errors {
     pub use css_parser::{
-        CssParsingError, CssBorderParseError, CssShadowParseError, InvalidValueErr,
+        CssParsingError, CssBorderParseError, CssBorderImageParseError, CssShadowParseError, InvalidValueErr,
         PixelParseError, CssImageParseError, CssFontFamilyParseError, CssMetric,
         PercentageParseError,
         CssBackgroundParseError, CssColorParseError, CssBorderRadiusParseError,
         CssDirectionParseError, CssGradientStopParseError, CssShapeParseError,
+        CssSideOffsetsParseError, CssAnimationParseError, CssTransformParseError,
+        CssFilterParseError,
     };
     pub use simplecss::Error as CssSyntaxError;
-    pub use css::{CssParseError, DynamicCssParseError};
+    pub use css::{CssParseError, CssParseErrorWithLocation, DynamicCssParseError, CssImportError};
     pub use font::FontError;
     pub use image::ImageError;
 
@@ -18,7 +18,8 @@ use webrender::api::{ImageData, ImageDescriptor, ImageFormat};
 use std::collections::hash_map::Entry::*;
 use app_units::Au;
 use css_parser;
-use css_parser::FontId::{self, ExternalFont};
+use css_parser::{FontWeight, FontStyle};
+use css_parser::FontId::{self, BuiltinFont, ExternalFont, SystemFontFace};
 use text_cache::TextId;
 use clipboard2::{Clipboard, ClipboardError, SystemClipboard};
 use rusttype::Font;
@@ -52,6 +53,10 @@ pub(crate) struct AppResources<'a> {
     // the font instance key (if there is any). If there is no font instance key,
     // we first need to create one.
     pub(crate) fonts: FastHashMap<FontKey, FastHashMap<Au, FontInstanceKey>>,
+    /// Maps the requested bold / italic faces (see `get_font_face`) to the font that is
+    /// actually used: either the loaded face or, if the system has no such face, the
+    /// regular font. Prevents querying the system fonts again on every frame.
+    pub(crate) font_faces: FastHashMap<FontId, FontId>,
     /// Stores long texts across frames
     pub(crate) text_cache: TextCache,
     /// Keyboard clipboard storage and retrieval functionality
@@ -66,6 +71,7 @@ impl<'a> Default for AppResources<'a> {
         Self {
             css_ids_to_image_ids: FastHashMap::default(),
             fonts: FastHashMap::default(),
+            font_faces: FastHashMap::default(),
             font_data: default_font_data,
             images: FastHashMap::default(),
             text_cache: TextCache::default(),
@@ -77,13 +83,12 @@ impl<'a> Default for AppResources<'a> {
 fn load_system_fonts<'a>(fonts: &mut FastHashMap<FontId, (::rusttype::Font<'a>, Vec<u8>, FontState)>) {
 
     use font_loader::system_fonts::{self, FontPropertyBuilder};
-    use css_parser::FontId::BuiltinFont;
     use font::rusttype_load_font;
 
     fn insert_font<'b>(fonts: &mut FastHashMap<FontId, (::rusttype::Font<'b>, Vec<u8>, FontState)>, target: &'static str) {
         if let Some((font_bytes, idx)) = system_fonts::get(&FontPropertyBuilder::new().family(target).build()) {
             match rusttype_load_font(font_bytes.clone(), Some(idx)) {
-                Ok((f, b)) =>  { fonts.insert(BuiltinFont(target), (f, b, FontState::ReadyForUpload(font_bytes))); },
+                Ok((f, b)) =>  { fonts.insert(BuiltinFont(target), (f, b, FontState::ReadyForUpload(font_bytes, idx as u32))); },
                 Err(e) => error!("Error loading {} font: {:?}", target, e),
             }
         }
@@ -172,12 +177,65 @@ impl<'a> AppResources<'a> {
                 let mut font_data = Vec::<u8>::new();
                 data.read_to_end(&mut font_data).map_err(|e| FontError::IoError(e))?;
                 let (parsed_font, fd) = font::rusttype_load_font(font_data.clone(), None)?;
-                v.insert((parsed_font, fd, FontState::ReadyForUpload(font_data)));
+                v.insert((parsed_font, fd, FontState::ReadyForUpload(font_data, 0)));
                 Ok(Some(()))
             },
         }
     }
 
+    /// Returns the face of the font that matches the `font-weight` and `font-style`,
+    /// i.e. the bold face of `sans-serif`. Faces are loaded from the system fonts
+    /// (via `font-loader`) on first use. If the system has no matching face, the
+    /// regular font is used instead.
+    pub(crate) fn get_font_face(&mut self, font_id: &FontId, weight: FontWeight, style: FontStyle) -> FontId {
+        use font_loader::system_fonts::{self, FontPropertyBuilder};
+        use font::rusttype_load_font;
+
+        if !weight.is_bold() && style == FontStyle::Normal {
+            return font_id.clone();
+        }
+
+        let family = match *font_id {
+            BuiltinFont(family) => family.to_string(),
+            ExternalFont(ref family) => family.clone(),
+            SystemFontFace { ref family, .. } => family.clone(),
+        };
+
+        let face_id = SystemFontFace { family: family.clone(), bold: weight.is_bold(), style: style };
+
+        if let Some(face) = self.font_faces.get(&face_id) {
+            return face.clone();
+        }
+
+        let mut property = FontPropertyBuilder::new().family(&family);
+        if weight.is_bold() {
+            property = property.bold();
+        }
+        property = match style {
+            FontStyle::Normal => property,
+            FontStyle::Italic => property.italic(),
+            FontStyle::Oblique => property.oblique(),
+        };
+
+        let loaded_face = system_fonts::get(&property.build()).and_then(|(font_bytes, idx)| {
+            match rusttype_load_font(font_bytes.clone(), Some(idx)) {
+                Ok((f, b)) => Some((f, b, FontState::ReadyForUpload(font_bytes, idx as u32))),
+                Err(e) => { error!("Error loading {:?} font face: {:?}", face_id, e); None },
+            }
+        });
+
+        let face = match loaded_face {
+            Some(font) => {
+                self.font_data.insert(face_id.clone(), font);
+                face_id.clone()
+            },
+            None => font_id.clone(),
+        };
+
+        self.font_faces.insert(face_id, face.clone());
+        face
+    }
+
     pub fn get_font<'b>(&'b self, id: &FontId) -> Option<(&'b Font<'a>, &'b Vec<u8>)> {
         self.font_data.get(id).and_then(|(font, bytes, _)| Some((font, bytes)))
     }
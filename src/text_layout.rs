@@ -8,7 +8,8 @@ use {
     display_list::TextInfo,
     css_parser::{
         TextAlignmentHorz, FontSize, BackgroundColor,
-        FontId, TextAlignmentVert, LineHeight, LayoutOverflow
+        FontId, TextAlignmentVert, LineHeight, LayoutOverflow,
+        TextTransform, TextDecoration,
     },
     text_cache::{TextId, TextCache},
 };
@@ -160,6 +161,9 @@ pub struct FontMetrics {
 /// - `font`: The font to use for layouting (only the ID)
 /// - `font_size`: The font size (without line height)
 /// - `line_height`: The line height (100% = 1.0). I.e. `line-height = 1.2;` scales the text vertically by 1.2x
+/// - `letter_spacing`: Space (in pixels) that is added after every character, parsed from `letter-spacing`
+/// - `word_spacing`: Space (in pixels) that is added to the space between two words, parsed from `word-spacing`
+/// - `text_transform`: Case conversion of the text before the layout, parsed from `text-transform`
 /// - `text`: The actual text to layout. Will be unicode-normalized after the Unicode Normalization Form C
 ///   (canonical decomposition followed by canonical composition).
 /// - `overflow`: If the scrollbars should be show, parsed from the `overflow-{x / y}` fields
@@ -180,6 +184,9 @@ pub(crate) fn get_glyphs(
     target_font_id: &FontId,
     target_font_size: &FontSize,
     line_height: Option<LineHeight>,
+    letter_spacing: f32,
+    word_spacing: f32,
+    text_transform: TextTransform,
     text: &TextInfo,
     overflow: &LayoutOverflow,
     scrollbar_info: &ScrollbarInfo)
@@ -189,7 +196,9 @@ pub(crate) fn get_glyphs(
 
     let target_font = app_resources.font_data.get(target_font_id).expect("Drawing with invalid font!");
 
-    let font_metrics = calculate_font_metrics(&target_font.0, target_font_size, line_height);
+    let mut font_metrics = calculate_font_metrics(&target_font.0, target_font_size, line_height);
+    // The space between two words is a character, too, so it also gets the letter spacing
+    font_metrics.space_width += word_spacing + letter_spacing;
 
     // (1) Split the text into semantic items (word, tab or newline) OR get the cached
     // text and scale it accordingly.
@@ -197,17 +206,33 @@ pub(crate) fn get_glyphs(
     // This function also normalizes the unicode characters and calculates kerning.
     //
     // NOTE: This should be revisited, the caching does unnecessary cloning.
+    //
+    // The cache only stores the words of the original text, so transformed text can't be cached.
     let words_owned;
     let words = match text {
-        TextInfo::Cached(text_id) => {
+        TextInfo::Cached(text_id) if text_transform == TextTransform::None => {
             get_words_cached(text_id, &target_font.0, target_font_id, target_font_size, font_metrics.font_size_no_line_height, &mut app_resources.text_cache)
         },
+        TextInfo::Cached(text_id) => {
+            let transformed_text = text_transform.apply(&app_resources.text_cache.string_cache[text_id]);
+            words_owned = split_text_into_words(&transformed_text, &target_font.0, font_metrics.font_size_no_line_height);
+            &words_owned
+        },
         TextInfo::Uncached(s) => {
-            words_owned = split_text_into_words(s, &target_font.0, font_metrics.font_size_no_line_height);
+            words_owned = split_text_into_words(&text_transform.apply(s), &target_font.0, font_metrics.font_size_no_line_height);
             &words_owned
         },
     };
 
+    // (1.5) Add the letter spacing to the words (on a copy, so that the cached words stay untouched)
+    let words_with_spacing;
+    let words = if letter_spacing != 0.0 {
+        words_with_spacing = add_letter_spacing(words, letter_spacing);
+        &words_with_spacing
+    } else {
+        words
+    };
+
     // (2) Calculate the additions / subtractions that have to be take into account
     // let harfbuzz_adjustments = calculate_harfbuzz_adjustments(&text, &target_font.0);
 
@@ -333,6 +358,19 @@ fn get_words_cached<'a>(
     text_cache.cached_strings.get(&text_id).unwrap().get(&font_id).unwrap().get(&font_size).unwrap()
 }
 
+/// Moves every glyph of a word by the letter spacing of the glyphs in front of it,
+/// i.e. the third glyph of a word is moved by `2 * letter_spacing`.
+fn add_letter_spacing(words: &Words, letter_spacing: f32) -> Words {
+    let mut words = words.clone();
+    for word in words.0.iter_mut() {
+        if let SemanticWordItem::Word(ref mut w) = word {
+            w.glyphs.iter_mut().enumerate().for_each(|(idx, g)| g.point.x += idx as f32 * letter_spacing);
+            w.total_width += w.glyphs.len() as f32 * letter_spacing;
+        }
+    }
+    words
+}
+
 fn scale_words(words: &mut Words, scale_factor: f32) {
     // Scale the horizontal width of the words to match the new font size
     // Since each word has a local origin (i.e. the first character of each word
@@ -875,6 +913,58 @@ fn add_origin(positioned_glyphs: &mut [GlyphInstance], x: f32, y: f32)
     }
 }
 
+/// Returns the rectangles of the lines of a `text-decoration` (underline, overline and
+/// line-through), for every line of the (already positioned) glyphs.
+pub(crate) fn get_text_decoration_rects<'a>(
+    glyphs: &[GlyphInstance],
+    font: &Font<'a>,
+    font_size: &FontSize,
+    text_decoration: TextDecoration)
+-> Vec<TypedRect<f32, LayoutPixel>>
+{
+    let scale = Scale::uniform(font_size.0.to_pixels() * RUSTTYPE_SIZE_HACK * PX_TO_PT);
+    let v_metrics = font.v_metrics(scale);
+    let thickness = ((v_metrics.ascent - v_metrics.descent) / 16.0).max(1.0);
+
+    // Offsets of the lines from the baseline, the descent is negative
+    let mut line_offsets = Vec::new();
+    if text_decoration.underline {
+        line_offsets.push(-v_metrics.descent / 2.0);
+    }
+    if text_decoration.overline {
+        line_offsets.push(-v_metrics.ascent);
+    }
+    if text_decoration.line_through {
+        line_offsets.push(-v_metrics.ascent * 0.3);
+    }
+
+    let mut rects = Vec::new();
+    let mut line_start = 0;
+
+    // All glyphs of one line of text have the same baseline
+    for idx in 0..glyphs.len() {
+        let is_last_glyph_in_line = idx + 1 == glyphs.len() || glyphs[idx + 1].point.y != glyphs[idx].point.y;
+        if !is_last_glyph_in_line {
+            continue;
+        }
+
+        let first_glyph = &glyphs[line_start];
+        let last_glyph = &glyphs[idx];
+        let last_glyph_width = font.glyph(GlyphId(last_glyph.index)).scaled(scale).h_metrics().advance_width;
+        let line_width = last_glyph.point.x + last_glyph_width - first_glyph.point.x;
+
+        for offset in &line_offsets {
+            rects.push(TypedRect::new(
+                TypedPoint2D::new(first_glyph.point.x, first_glyph.point.y + offset - thickness / 2.0),
+                TypedSize2D::new(line_width, thickness)));
+        }
+
+        line_start = idx + 1;
+    }
+
+    rects
+}
+
 // -------------------------- PUBLIC API -------------------------- //
 
 pub type IndexOfLineBreak = usize;
@@ -1,17 +1,18 @@
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     rc::Rc,
     cell::RefCell,
     hash::Hash,
     sync::{Arc, Mutex},
 };
 use {
+    FastHashMap,
     dom::{NodeData, Dom},
-    ui_description::{StyledNode, CssConstraintList, UiDescription},
-    css::{Css, CssRule},
+    ui_description::{StyledNode, UiDescription},
+    css::{self, Css, CssRule, CssDeclaration},
     window::WindowInfo,
     id_tree::{NodeId, Arena},
-    css_parser::{ParsedCssProperty, CssParsingError},
+    css_parser::{ParsedCssProperty, CssParsingError, ColorU, FontSize, EM_HEIGHT, get_property_name, get_initial_longhand_value},
 };
 
 /// The core trait that has to be implemented for the app model to provide a
@@ -33,17 +34,10 @@ pub trait Layout {
     /// function and calculates the final display list that is submitted to the
     /// renderer.
     fn style_dom(dom: &Dom<Self>, css: &Css) -> UiDescription<Self> where Self: Sized {
-        match_dom_css_selectors(dom.root, &dom.arena, &ParsedCss::from_css(css), css, 0)
+        match_dom_css_selectors(dom.root, &dom.arena, css, 0)
     }
 }
 
-pub(crate) struct ParsedCss<'a> {
-    pub(crate) pure_global_rules: Vec<&'a CssRule>,
-    pub(crate) pure_div_rules: Vec<&'a CssRule>,
-    pub(crate) pure_class_rules: Vec<&'a CssRule>,
-    pub(crate) pure_id_rules: Vec<&'a CssRule>,
-}
-
 /// Convenience trait for the `css.set_dynamic_property()` function.
 /// 
 /// This trait exists because `TryFrom` / `TryInto` are not yet stabilized.
@@ -60,6 +54,19 @@ pub trait IntoParsedCssProperty<'a> {
     fn into_parsed_css_property(self) -> Result<ParsedCssProperty, CssParsingError<'a>>;
 }
 
+/// Widgets that come with their own default stylesheet, i.e. the `Button`.
+///
+/// The stylesheet is added to the user-agent layer via `css.with_default_style::<MyWidget>()`,
+/// so the rules of the app always win over the defaults of the widget.
+pub trait DefaultStyle {
+    /// The class of the root node of the widget. The class is reserved for the widget
+    /// (prefixed with `__azul-`) and the default stylesheet only targets this class,
+    /// so it doesn't style anything but the widget.
+    const STYLE_CLASS: &'static str;
+    /// Returns the default stylesheet of the widget
+    fn default_style() -> Css;
+}
+
 /// Convenience trait that allows the `app_state.modify()` - only implemented for
 /// `Arc<Mutex<T: Layout>` - shortly locks the app state mutex, modifies it and unlocks 
 /// it again.
@@ -97,79 +104,14 @@ impl<'a> IntoParsedCssProperty<'a> for (&'a str, &'a str) {
     }
 }
 
-impl<'a> ParsedCss<'a> {
-    pub(crate) fn from_css(css: &'a Css) -> Self {
-
-        // Parse the CSS nodes cascading by their importance
-        // 1. global rules
-        // 2. div-type ("html { }") specific rules
-        // 3. class-based rules
-        // 4. ID-based rules
-
-        /*
-            CssRule { html_type: "div", id: Some("main"), classes: [], declaration: ("direction", "row") }
-            CssRule { html_type: "div", id: Some("main"), classes: [], declaration: ("justify-content", "center") }
-            CssRule { html_type: "div", id: Some("main"), classes: [], declaration: ("align-items", "center") }
-            CssRule { html_type: "div", id: Some("main"), classes: [], declaration: ("align-content", "center") }
-        */
-
-        // note: the following passes can be done in parallel ...
-
-        // Global rules
-        // * {
-        //    background-color: blue;
-        // }
-        let pure_global_rules: Vec<&CssRule> = css.rules.iter().filter(|rule|
-            rule.html_type == "*" && rule.id.is_none() && rule.classes.is_empty()
-        ).collect();
-
-        // Pure-div-type specific rules
-        // button {
-        //    justify-content: center;
-        // }
-        let pure_div_rules: Vec<&CssRule> = css.rules.iter().filter(|rule|
-            rule.html_type != "*" && rule.id.is_none() && rule.classes.is_empty()
-        ).collect();
-
-        // Pure-class rules
-        // NOTE: These classes are sorted alphabetically and are not duplicated
-        //
-        // .something .otherclass {
-        //    text-color: red;
-        // }
-        let pure_class_rules: Vec<&CssRule> = css.rules.iter().filter(|rule|
-            rule.id.is_none() && !rule.classes.is_empty()
-        ).collect();
-
-        // Pure-id rules
-        // #something {
-        //    background-color: red;
-        // }
-        let pure_id_rules: Vec<&CssRule> = css.rules.iter().filter(|rule|
-            rule.id.is_some() && rule.classes.is_empty()
-        ).collect();
-
-        Self {
-            pure_global_rules: pure_global_rules,
-            pure_div_rules: pure_div_rules,
-            pure_class_rules: pure_class_rules,
-            pure_id_rules: pure_id_rules,
-        }
-    }
-}
-
-fn match_dom_css_selectors<'a, T: Layout>(
+fn match_dom_css_selectors<T: Layout>(
     root: NodeId,
     arena: &Rc<RefCell<Arena<NodeData<T>>>>,
-    parsed_css: &ParsedCss<'a>,
     css: &Css,
     parent_z_level: u32)
 -> UiDescription<T>
 {
-    let mut root_constraints = CssConstraintList::default();
-    for global_rule in &parsed_css.pure_global_rules {
-        push_rule(&mut root_constraints, global_rule);
-    }
+    let root_style = StyledNode::default();
 
     let arena_borrow = &*(*arena).borrow();
     let mut styled_nodes = BTreeMap::<NodeId, StyledNode>::new();
@@ -178,7 +120,7 @@ fn match_dom_css_selectors<'a, T: Layout>(
     // sibling_iterator.next().unwrap();
 
     for sibling in sibling_iterator {
-        styled_nodes.append(&mut match_dom_css_selectors_inner(sibling, arena_borrow, parsed_css, css, &root_constraints, parent_z_level));
+        styled_nodes.append(&mut match_dom_css_selectors_inner(sibling, arena_borrow, css, &root_style, parent_z_level));
     }
 
     UiDescription {
@@ -189,95 +131,181 @@ fn match_dom_css_selectors<'a, T: Layout>(
         styled_nodes: styled_nodes,
         default_style_of_node: StyledNode::default(),
         dynamic_css_overrides: css.dynamic_css_overrides.clone(),
+        animated_properties: BTreeMap::new(),
     }
 }
 
-fn match_dom_css_selectors_inner<'a, T: Layout>(
+fn match_dom_css_selectors_inner<T: Layout>(
     root: NodeId,
     arena: &Arena<NodeData<T>>,
-    parsed_css: &ParsedCss<'a>,
     css: &Css,
-    parent_constraints: &CssConstraintList,
+    parent_style: &StyledNode,
     parent_z_level: u32)
 -> BTreeMap<NodeId, StyledNode>
 {
     let mut styled_nodes = BTreeMap::<NodeId, StyledNode>::new();
 
-    let mut current_constraints = parent_constraints.clone();
-    cascade_constraints(&arena[root].data, &mut current_constraints, parsed_css, css);
-
-    let current_node = StyledNode {
+    let mut current_node = StyledNode {
         z_level: parent_z_level,
-        css_constraints: current_constraints,
+        // only the inherited properties (i.e. `color`) are passed on to the children
+        css_constraints: parent_style.css_constraints.get_inherited(),
+        custom_properties: parent_style.custom_properties.clone(),
     };
 
+    cascade_constraints(root, arena, &mut current_node, parent_style, css);
+
     // DFS tree
     for child in root.children(arena) {
-        styled_nodes.append(&mut match_dom_css_selectors_inner(child, arena, parsed_css, css, &current_node.css_constraints, parent_z_level + 1));
+        styled_nodes.append(&mut match_dom_css_selectors_inner(child, arena, css, &current_node, parent_z_level + 1));
     }
 
     styled_nodes.insert(root, current_node);
     styled_nodes
 }
 
-/// Cascade the rules, put them into the list
-#[allow(unused_variables)]
-fn cascade_constraints<'a, T: Layout>(
-    node: &NodeData<T>,
-    list: &mut CssConstraintList,
-    parsed_css: &ParsedCss<'a>,
-    css: &Css)
+/// Re-runs the cascade for the given nodes and their descendants, for example
+/// after the `:hover` state of the nodes has changed. The DOM stays the same,
+/// so `Layout::layout()` doesn't have to be called again.
+pub(crate) fn restyle_nodes<T: Layout>(
+    ui_description: &mut UiDescription<T>,
+    css: &Css,
+    nodes: &BTreeSet<NodeId>)
 {
-    for div_rule in &parsed_css.pure_div_rules {
-        if *node.node_type.get_css_id() == div_rule.html_type {
-            push_rule(list, div_rule);
-        }
-    }
+    let arena_rc = ui_description.ui_descr_arena.clone();
+    let arena = &*arena_rc.borrow();
 
-    let mut node_classes: Vec<&String> = node.classes.iter().map(|x| x).collect();
-    node_classes.sort();
-    node_classes.dedup_by(|a, b| *a == *b);
-
-    // for all classes that this node has
-    for class_rule in &parsed_css.pure_class_rules {
-        // NOTE: class_rule is sorted and de-duplicated
-        // If the selector matches, the node classes must be identical
-        let mut should_insert_rule = true;
-        if class_rule.classes.len() != node_classes.len() {
-            should_insert_rule = false;
-        } else {
-            for i in 0..class_rule.classes.len() {
-                // we verified that the length of the two classes is the same
-                if *node_classes[i] != class_rule.classes[i] {
-                    should_insert_rule = false;
-                    break;
-                }
-            }
+    for node_id in nodes {
+
+        // The nodes may be left over from an older DOM
+        if !arena.contains(*node_id) {
+            continue;
         }
 
-        if should_insert_rule {
-            push_rule(list, class_rule);
+        // If an ancestor gets restyled, the node gets restyled as well
+        if node_id.ancestors(arena).skip(1).any(|ancestor| nodes.contains(&ancestor)) {
+            continue;
         }
-    }
 
-    // first attribute for "id = something"
-    let node_id = &node.id;
+        let (parent_style, z_level) = match arena[*node_id].parent() {
+            Some(parent) => match ui_description.styled_nodes.get(&parent) {
+                Some(parent_style) => (parent_style.clone(), parent_style.z_level + 1),
+                None => continue,
+            },
+            None => (StyledNode::default(), 0),
+        };
 
-    if let Some(ref node_id) = *node_id {
-        // if the node has an ID
-        for id_rule in &parsed_css.pure_id_rules {
-            if *id_rule.id.as_ref().unwrap() == *node_id {
-                push_rule(list, id_rule);
-            }
+        let mut restyled_nodes = match_dom_css_selectors_inner(*node_id, arena, css, &parent_style, z_level);
+        ui_description.styled_nodes.append(&mut restyled_nodes);
+    }
+}
+
+/// Cascade the rules, put them into the list
+///
+/// The rules whose selector matches the node get pushed in cascade order, so
+/// that the last declaration of a property wins: `!important` declarations win
+/// over normal ones, then the app wins over the user agent (reversed for
+/// `!important` declarations), then the higher specificity wins, then the later
+/// rule in the stylesheet.
+fn cascade_constraints<T: Layout>(
+    node_id: NodeId,
+    arena: &Arena<NodeData<T>>,
+    style: &mut StyledNode,
+    parent_style: &StyledNode,
+    css: &Css)
+{
+    let mut matching_rules = css.rules.iter().enumerate()
+        .filter(|(_, rule)| rule.media_queries.iter().all(|query| query.matches(&css.media_query_state)))
+        .filter(|(_, rule)| rule.path.matches_html_element(node_id, arena, &css.pseudo_class_state))
+        .collect::<Vec<(usize, &CssRule)>>();
+
+    matching_rules.sort_by_key(|(source_order, rule)| (
+        rule.important,
+        rule.origin.get_precedence(rule.important),
+        rule.path.get_specificity(),
+        *source_order,
+    ));
+
+    // `currentColor` refers to the final `color` of the node, so the `color` is cascaded first.
+    // The order of the other properties doesn't matter, since they don't depend on each other.
+    matching_rules.sort_by_key(|(_, rule)| rule.declaration.0 != "color");
+
+    // The custom properties of the node have to be known before
+    // the var() references of the node can be resolved
+    for (_, rule) in &matching_rules {
+        if let CssDeclaration::CustomProperty(ref value) = rule.declaration.1 {
+            style.custom_properties.insert(rule.declaration.0.clone(), value.clone());
         }
     }
 
-    // TODO: all the mixed rules
+    for (_, rule) in matching_rules {
+        push_rule(style, parent_style, rule, css);
+    }
 }
 
 #[inline]
-fn push_rule(list: &mut CssConstraintList, rule: &CssRule) {
-    list.list.push(rule.declaration.1.clone());
+fn push_rule(style: &mut StyledNode, parent_style: &StyledNode, rule: &CssRule, css: &Css) {
+    let (ref key, ref declaration) = rule.declaration;
+    match declaration {
+        CssDeclaration::CustomProperty(_) => { },
+        CssDeclaration::Keyword(keyword) if get_property_name(key) != key => {
+            // a longhand (`padding-top: inherit`) only replaces its own side of the merged property:
+            // the initial value of the side, overwritten by the value of the parent if it inherits
+            let current_color = get_current_color(style, &css.dynamic_css_overrides);
+            let resolved_property = get_initial_longhand_value(key)
+                .and_then(|initial_value| ParsedCssProperty::from_kv(key, &css::resolve_current_color(initial_value, &current_color)).ok())
+                .and_then(|mut property| {
+                    if keyword.inherits(key) {
+                        let parent_values = parent_style.css_constraints.list.iter()
+                            .filter(|declaration| declaration.get_property_name() == Some(get_property_name(key)))
+                            .filter_map(|declaration| declaration.get_value(&css.dynamic_css_overrides));
+                        for parent_value in parent_values {
+                            property.replace_set_parts(parent_value);
+                        }
+                    }
+                    Some(property)
+                });
+            match resolved_property {
+                Some(property) => style.css_constraints.list.push(CssDeclaration::Static(property)),
+                None => warn!("could not resolve the CSS declaration \"{}: {}\"", key, keyword),
+            }
+        },
+        CssDeclaration::Keyword(keyword) => {
+            // replace the earlier (and inherited) declarations of the property with the ones of the parent
+            let property = Some(get_property_name(key));
+            style.css_constraints.list.retain(|declaration| declaration.get_property_name() != property);
+            if key == "font-size" {
+                // without a declaration, the layout uses the computed font size of the parent
+                if !keyword.inherits(key) {
+                    style.css_constraints.list.push(CssDeclaration::Static(FontSize::px(EM_HEIGHT).into()));
+                }
+            } else if keyword.inherits(key) {
+                let parent_declarations = parent_style.css_constraints.list.iter()
+                    .filter(|declaration| declaration.get_property_name() == property);
+                style.css_constraints.list.extend(parent_declarations.cloned());
+            }
+        },
+        CssDeclaration::Unresolved(value) => {
+            // `color: currentColor` is the same as `color: inherit`
+            let current_color = get_current_color(if key == "color" { parent_style } else { style }, &css.dynamic_css_overrides);
+            let resolved_property = css::resolve_var_references(value, &style.custom_properties, &css.custom_property_overrides)
+                .and_then(|resolved_value| Some(css::resolve_current_color(&resolved_value, &current_color)))
+                .and_then(|resolved_value| ParsedCssProperty::from_kv(key, &resolved_value).ok());
+            match resolved_property {
+                Some(property) => style.css_constraints.list.push(CssDeclaration::Static(property)),
+                None => warn!("could not resolve the CSS declaration \"{}: {}\"", key, value),
+            }
+        },
+        _ => style.css_constraints.list.push(declaration.clone()),
+    }
+}
+
+/// Returns the `color` of the node (as far as it is cascaded yet), black by default.
+/// This is the color that `currentColor` refers to.
+fn get_current_color(style: &StyledNode, dynamic_css_overrides: &FastHashMap<String, ParsedCssProperty>) -> ColorU {
+    style.css_constraints.list.iter().rev().filter_map(|declaration| match declaration.get_value(dynamic_css_overrides) {
+        Some(ParsedCssProperty::TextColor(color)) => Some(color.0),
+        _ => None,
+    }).next().unwrap_or(ColorU { r: 0, g: 0, b: 0, a: 255 })
 }
 
 // Empty test, for some reason codecov doesn't detect any files (and therefore
@@ -286,4 +314,210 @@ fn push_rule(list: &mut CssConstraintList, rule: &CssRule) {
 #[test]
 fn __codecov_test_traits_file() {
 
+}
+
+/// The `Layout` of the tests: the tests build their DOM themselves and style it with `style_test_dom`
+#[cfg(test)]
+pub(crate) struct TestLayout { }
+
+#[cfg(test)]
+impl Layout for TestLayout {
+    fn layout(&self) -> Dom<Self> {
+        Dom::new(::dom::NodeType::Div)
+    }
+}
+
+/// Styles the DOM of a test with the given CSS
+#[cfg(test)]
+pub(crate) fn style_test_dom(dom: &Dom<TestLayout>, css: &Css) -> UiDescription<TestLayout> {
+    TestLayout::style_dom(dom, css)
+}
+
+/// Returns the properties of the node after the cascade
+#[cfg(test)]
+pub(crate) fn test_node_declarations(ui_description: &UiDescription<TestLayout>, node_id: NodeId) -> Vec<ParsedCssProperty> {
+    ui_description.styled_nodes[&node_id].css_constraints.list.iter().map(|declaration| match declaration {
+        CssDeclaration::Static(property) => property.clone(),
+        other => panic!("unexpected declaration: {:?}", other),
+    }).collect()
+}
+
+#[test]
+fn test_cascade_order() {
+    use dom::NodeType;
+    use css_parser::{LayoutWidth, PixelValue, CssMetric};
+
+    // Returns the width that wins the cascade for the root node
+    fn cascaded_width(css: &str) -> ParsedCssProperty {
+        let dom = Dom::new(NodeType::Div).with_id("main").with_class("content");
+        let ui_description = style_test_dom(&dom, &Css::new_from_string(css).unwrap());
+        test_node_declarations(&ui_description, dom.root).pop().unwrap()
+    }
+
+    fn width(px: f32) -> ParsedCssProperty {
+        ParsedCssProperty::Width(LayoutWidth(PixelValue::from_metric(CssMetric::Px, px)))
+    }
+
+    // specificity wins over source order
+    assert_eq!(cascaded_width("#main { width: 1px; } .content { width: 2px; } div { width: 3px; }"), width(1.0));
+    // source order wins for equal specificity
+    assert_eq!(cascaded_width(".content { width: 1px; } div.content { width: 2px; } .content { width: 3px; }"), width(2.0));
+    assert_eq!(cascaded_width(".content { width: 1px; } .content { width: 2px; }"), width(2.0));
+    // !important wins over specificity
+    assert_eq!(cascaded_width("div { width: 1px !important; } #main { width: 2px; }"), width(1.0));
+}
+
+#[test]
+fn test_cascade_layers() {
+    use dom::NodeType;
+    use css_parser::{LayoutWidth, PixelValue, CssMetric};
+
+    // Returns the width that wins the cascade for the root node
+    fn cascaded_width(user_agent: &str, app: &str) -> ParsedCssProperty {
+        let css = Css::layered(Css::new_from_string(user_agent).unwrap(), Css::new_from_string(app).unwrap());
+        let dom = Dom::new(NodeType::Div).with_id("main");
+        let ui_description = style_test_dom(&dom, &css);
+        test_node_declarations(&ui_description, dom.root).pop().unwrap()
+    }
+
+    fn width(px: f32) -> ParsedCssProperty {
+        ParsedCssProperty::Width(LayoutWidth(PixelValue::from_metric(CssMetric::Px, px)))
+    }
+
+    // the app wins over the user agent, regardless of the specificity
+    assert_eq!(cascaded_width("#main { width: 1px; }", "div { width: 2px; }"), width(2.0));
+    // !important app rules win over normal user-agent rules
+    assert_eq!(cascaded_width("#main { width: 1px; }", "div { width: 2px !important; }"), width(2.0));
+    // !important user-agent rules win over everything
+    assert_eq!(cascaded_width("div { width: 1px !important; }", "#main { width: 2px !important; }"), width(1.0));
+    // within a layer, the specificity still wins
+    assert_eq!(cascaded_width("#main { width: 1px; } div { width: 2px; }", ""), width(1.0));
+}
+
+/// Returns the properties of the `.child` in a `.parent > .child` DOM
+#[cfg(test)]
+fn child_declarations(css: &str) -> Vec<ParsedCssProperty> {
+    use dom::NodeType;
+    let dom = Dom::new(NodeType::Div).with_class("parent")
+        .with_child(Dom::new(NodeType::Div).with_class("child"));
+    let ui_description = style_test_dom(&dom, &Css::new_from_string(css).unwrap());
+    let child = dom.arena.borrow()[dom.root].first_child().unwrap();
+    test_node_declarations(&ui_description, child)
+}
+
+/// Parses the expected `(key, value)` properties of a test
+#[cfg(test)]
+fn test_properties(properties: &[(&'static str, &'static str)]) -> Vec<ParsedCssProperty> {
+    properties.iter().map(|&(key, value)| ParsedCssProperty::from_kv(key, value).unwrap()).collect()
+}
+
+#[test]
+fn test_inheritance() {
+    let parent = ".parent { color: red; font-family: serif; background-color: blue; width: 5px; padding: 1px; }";
+
+    // only the inherited properties are passed on to the children
+    assert_eq!(child_declarations(parent), test_properties(&[("color", "red"), ("font-family", "serif")]));
+    // initial removes the inherited value
+    assert_eq!(child_declarations(&format!("{} .child {{ color: initial; }}", parent)), test_properties(&[("font-family", "serif")]));
+    // inherit uses the value of the parent, even for non-inherited properties
+    assert_eq!(child_declarations(&format!("{} .child {{ width: 1px; width: inherit; padding-top: inherit; }}", parent)),
+        test_properties(&[("color", "red"), ("font-family", "serif"), ("width", "5px"), ("padding-top", "1px")]));
+    // a keyword on a longhand only applies to its side
+    assert_eq!(child_declarations(&format!("{} .child {{ padding: 3px; padding-left: initial; margin-bottom: inherit; }}", parent)),
+        test_properties(&[("color", "red"), ("font-family", "serif"), ("padding", "3px"), ("padding-left", "0px"), ("margin-bottom", "0px")]));
+    // unset is inherit for inherited properties and initial for all others
+    assert_eq!(child_declarations(&format!("{} .child {{ color: blue; color: unset; background-color: green; background-color: unset; }}", parent)),
+        test_properties(&[("font-family", "serif"), ("color", "red")]));
+    // the font size is inherited by the layout as the computed size, not by the cascade
+    assert_eq!(child_declarations(".parent { font-size: 1.5em; }"), test_properties(&[]));
+    assert_eq!(child_declarations(".parent { font-size: 1.5em; } .child { font-size: 2em; font-size: inherit; }"), test_properties(&[]));
+    assert_eq!(child_declarations(".parent { font-size: 1.5em; } .child { font-size: initial; }"), test_properties(&[("font-size", "16px")]));
+}
+
+#[test]
+fn test_restyle_hovered_nodes() {
+    use dom::NodeType;
+    use css::PseudoClassState;
+
+    let mut css = Css::new_from_string(".button { width: 1px; } .button:hover { width: 2px; }").unwrap();
+    let dom = Dom::new(NodeType::Div).with_class("button")
+        .with_child(Dom::new(NodeType::Div));
+    let mut ui_description = style_test_dom(&dom, &css);
+    let child = dom.arena.borrow()[dom.root].first_child().unwrap();
+
+    let not_hovered = ui_description.styled_nodes.clone();
+    assert_eq!(not_hovered[&dom.root].css_constraints.list.len(), 1);
+
+    let new_state = PseudoClassState::new(Some(child), false, None, &*dom.arena.borrow());
+    let changed_nodes = css.pseudo_class_state.get_changed_nodes(&new_state);
+    css.pseudo_class_state = new_state;
+    restyle_nodes(&mut ui_description, &css, &changed_nodes);
+
+    assert_eq!(ui_description.styled_nodes[&dom.root].css_constraints.list.len(), 2);
+    assert_eq!(ui_description.styled_nodes, style_test_dom(&dom, &css).styled_nodes);
+}
+
+#[test]
+fn test_custom_properties() {
+    use dom::NodeType;
+    use css_parser::{LayoutWidth, PixelValue, CssMetric};
+
+    // Returns the last property of the two `.themed` nodes
+    fn cascaded_widths(css: &Css) -> Vec<ParsedCssProperty> {
+        let dom = Dom::new(NodeType::Div).with_class("root")
+            .with_child(Dom::new(NodeType::Div).with_class("themed")
+                .with_child(Dom::new(NodeType::Div).with_class("dark")
+                    .with_child(Dom::new(NodeType::Div).with_class("themed"))));
+        let ui_description = style_test_dom(&dom, css);
+        let arena = dom.arena.borrow();
+        let outer = arena[dom.root].first_child().unwrap();
+        let dark = arena[outer].first_child().unwrap();
+        let inner = arena[dark].first_child().unwrap();
+        vec![outer, inner].into_iter().map(|node_id| test_node_declarations(&ui_description, node_id).pop().unwrap()).collect()
+    }
+
+    fn width(px: f32) -> ParsedCssProperty {
+        ParsedCssProperty::Width(LayoutWidth(PixelValue::from_metric(CssMetric::Px, px)))
+    }
+
+    let mut css = Css::new_from_string("
+        .root { --size: 10px; }
+        .dark { --size: 20px; }
+        .themed { width: var(--size, 5px); }
+    ").unwrap();
+
+    // the custom property is inherited, the closest declaration wins
+    assert_eq!(cascaded_widths(&css), vec![width(10.0), width(20.0)]);
+
+    // the fallback is used if the property isn't declared
+    let fallback_css = Css::new_from_string(".themed { width: var(--size, 5px); }").unwrap();
+    assert_eq!(cascaded_widths(&fallback_css), vec![width(5.0), width(5.0)]);
+
+    // setting the property from the FakeCss re-themes every node
+    css.custom_property_overrides.insert(String::from("--size"), String::from("30px"));
+    assert_eq!(cascaded_widths(&css), vec![width(30.0), width(30.0)]);
+}
+
+#[test]
+fn test_current_color() {
+    // currentColor is the final color of the node, even if the color is declared later
+    assert_eq!(child_declarations(".child { border: 1px solid currentColor; color: blue; }"),
+        test_properties(&[("color", "blue"), ("border", "1px solid blue")]));
+    // the inherited color is used if the node has no color
+    assert_eq!(child_declarations(".parent { color: red; } .child { background-color: CurrentColor; }"),
+        test_properties(&[("color", "red"), ("background-color", "red")]));
+    // `color: currentColor` is the color of the parent
+    assert_eq!(child_declarations(".parent { color: red; } .child { color: currentColor; }"),
+        test_properties(&[("color", "red"), ("color", "red")]));
+    // currentColor in a custom property
+    assert_eq!(child_declarations(".child { --accent: currentColor; color: #00ff00; background-color: var(--accent); }"),
+        test_properties(&[("color", "#00ff00"), ("background-color", "#00ff00")]));
+
+    // the override of a dynamic color is used
+    let mut css = Css::new_from_string(".child { color: [[ theme | #000000 ]]; border-color: currentColor; }").unwrap();
+    css.dynamic_css_overrides.insert(String::from("theme"), ParsedCssProperty::from_kv("color", "blue").unwrap());
+    let dom = Dom::new(::dom::NodeType::Div).with_class("child");
+    let ui_description = style_test_dom(&dom, &css);
+    assert_eq!(ui_description.styled_nodes[&dom.root].css_constraints.list.last(),
+        Some(&CssDeclaration::Static(ParsedCssProperty::from_kv("border-color", "blue").unwrap())));
 }
\ No newline at end of file
@@ -1,7 +1,8 @@
 use std::{
     cell::RefCell,
     rc::Rc,
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
+    time::Instant,
 };
 use {
     FastHashMap,
@@ -27,6 +28,9 @@ pub struct UiDescription<T: Layout> {
     pub(crate) default_style_of_node: StyledNode,
     /// The CSS properties that should be overridden for this frame, cloned from the `Css`
     pub(crate) dynamic_css_overrides: FastHashMap<String, ParsedCssProperty>,
+    /// The current values of the running transitions and animations, by node.
+    /// They override the values of the cascade, see `update_animations`
+    pub(crate) animated_properties: BTreeMap<NodeId, Vec<ParsedCssProperty>>,
 }
 
 impl<T: Layout> Clone for UiDescription<T> {
@@ -37,6 +41,7 @@ impl<T: Layout> Clone for UiDescription<T> {
             styled_nodes: self.styled_nodes.clone(),
             default_style_of_node: self.default_style_of_node.clone(),
             dynamic_css_overrides: self.dynamic_css_overrides.clone(),
+            animated_properties: self.animated_properties.clone(),
         }
     }
 }
@@ -49,6 +54,7 @@ impl<T: Layout> Default for UiDescription<T> {
             styled_nodes: BTreeMap::new(),
             default_style_of_node: StyledNode::default(),
             dynamic_css_overrides: FastHashMap::default(),
+            animated_properties: BTreeMap::new(),
         }
     }
 }
@@ -58,6 +64,19 @@ impl<T: Layout> UiDescription<T> {
     {
         T::style_dom(&ui_state.dom, style)
     }
+
+    /// Restyles the given nodes (and their children) without re-creating the DOM,
+    /// i.e. when only the `:hover` or `:active` state of a node has changed
+    pub(crate) fn restyle_nodes(&mut self, style: &Css, nodes: &BTreeSet<NodeId>) {
+        ::traits::restyle_nodes(self, style, nodes);
+    }
+
+    /// Starts the transitions of the properties that have changed since the last frame
+    /// and samples the running transitions and animations, see `AnimationState::update`
+    pub(crate) fn update_animations(&mut self, style: &mut Css) {
+        let animated_properties = style.animation_state.update(self, &style.keyframes, &style.media_query_state, Instant::now());
+        self.animated_properties = animated_properties;
+    }
 }
 
 #[derive(Debug, Default, Clone, PartialEq)]
@@ -65,7 +84,10 @@ pub(crate) struct StyledNode {
     /// The z-index level that we are currently on, 0 by default
     pub(crate) z_level: u32,
     /// The CSS constraints, after the cascading step
-    pub(crate) css_constraints: CssConstraintList
+    pub(crate) css_constraints: CssConstraintList,
+    /// The custom properties (`--accent: #3070ff`) of this node, including the
+    /// ones inherited from the parent. The values are stored unparsed.
+    pub(crate) custom_properties: FastHashMap<String, String>,
 }
 
 #[derive(Debug, Default, Clone, PartialEq)]
@@ -73,6 +95,16 @@ pub(crate) struct CssConstraintList {
     pub(crate) list: Vec<CssDeclaration>
 }
 
+impl CssConstraintList {
+    /// Returns the declarations that the children of the node inherit, i.e. the `color`,
+    /// but not the `background` of the node
+    pub(crate) fn get_inherited(&self) -> Self {
+        Self {
+            list: self.list.iter().filter(|declaration| declaration.is_inherited()).cloned().collect(),
+        }
+    }
+}
+
 // Empty test, for some reason codecov doesn't detect any files (and therefore
 // doesn't report codecov % correctly) except if they have at least one test in
 // the file. This is an empty test, which should be updated later on
@@ -0,0 +1,904 @@
+//! Flexbox layout solver, calculates the final position and size of every rectangle
+//!
+//! The layout is solved top-down: the root nodes fill the window, then every node
+//! distributes its own rectangle among its children, according to its `flex-direction`,
+//! `flex-wrap`, `justify-content`, `align-items` and `align-content` properties.
+//!
+//! Since there is no content-based sizing yet, a node without an explicit `width` /
+//! `height` is flexible: on the main axis, flexible nodes share the space that is left
+//! over by the fixed-size nodes, on the cross axis they fill the whole line.
+//!
+//! The `width` / `height` of a node include its `padding` (like `box-sizing: border-box`),
+//! the children of a node are laid out inside of its content box. The `margin` of a
+//! node is added around its size, the space of the margins isn't shared with other nodes.
+//!
+//! Relative lengths are resolved here: percentages of `width`, `padding` and `margin`
+//! are relative to the width of the content box of the parent, percentages of `height`
+//! to its height. `em` is relative to the (inherited) `font-size` of the node, `rem`
+//! to the `font-size` of the root node and `vw` / `vh` to the size of the window. The font size
+//! of every node is computed in pixels, a node without a `font-size` inherits the computed
+//! font size of its parent (so a `font-size: 1.5em` doesn't grow on every level of the tree).
+//!
+//! Nodes with `display: none` are skipped, together with their children. Nodes with
+//! `position: relative` are moved by their `top` / `left` / etc. after the layout of their
+//! parent, `position: absolute` nodes don't take part in the layout of their parent at all,
+//! they are placed inside of the bounds of their nearest positioned ancestor (or the window).
+//! `get_paint_order` then sorts the nodes into stacking contexts by their `z-index`.
+
+use std::collections::BTreeMap;
+use webrender::api::{LayoutRect, LayoutPoint, LayoutSize, LayoutVector2D};
+use euclid::SideOffsets2D;
+use {
+    id_tree::{Arena, NodeId},
+    css_parser::{
+        RectLayout, LayoutDirection, LayoutWrap, LayoutJustifyContent,
+        LayoutAlignItems, LayoutAlignContent, LengthContext,
+        LayoutPosition, LayoutDisplay, PixelValue,
+    },
+};
+
+/// The solved layout of all nodes
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct SolvedRects {
+    /// The bounds of every node, including its padding
+    pub(crate) bounds: BTreeMap<NodeId, LayoutRect>,
+    /// The content box of every node, i.e. its bounds minus its padding.
+    /// The children and the text of the node are placed inside of the content box.
+    pub(crate) content_boxes: BTreeMap<NodeId, LayoutRect>,
+    /// The lengths of every node are resolved against this context, i.e. its computed font size
+    pub(crate) length_contexts: BTreeMap<NodeId, LengthContext>,
+}
+
+/// Solves the layout of all nodes in the `arena`. The `root` node and its following
+/// siblings are laid out as if they were the children of a (vertical) container that
+/// spans the `bounds`, i.e. the whole window.
+pub(crate) fn solve_layout(arena: &Arena<RectLayout>, root: NodeId, bounds: LayoutRect)
+-> SolvedRects
+{
+    let mut solved = SolvedRects::default();
+    let root_nodes = root.following_siblings(arena).collect::<Vec<NodeId>>();
+
+    let window_context = LengthContext { viewport: bounds.size, .. Default::default() };
+    let root_font_size = window_context.with_font_size(arena[root].data.font_size).font_size;
+    let context = LengthContext { root_font_size, .. window_context };
+
+    layout_children(arena, &RectLayout::default(), &root_nodes, bounds, bounds, &context, &mut solved);
+    solved
+}
+
+/// Returns the content box of a node, i.e. its `bounds` minus its `padding`
+fn get_content_box(bounds: LayoutRect, padding: SideOffsets2D<f32>) -> LayoutRect {
+    LayoutRect::new(
+        LayoutPoint::new(bounds.origin.x + padding.left, bounds.origin.y + padding.top),
+        LayoutSize::new(
+            (bounds.size.width - padding.horizontal()).max(0.0),
+            (bounds.size.height - padding.vertical()).max(0.0)))
+}
+
+/// One child of a flex container, with its sizes resolved to pixels and
+/// translated to the main / cross axis of the container
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct FlexItem {
+    node_id: NodeId,
+    /// The `width` (for horizontal containers) or `height` (for vertical containers)
+    main_size: Option<f32>,
+    min_main: f32,
+    max_main: f32,
+    /// The `height` (for horizontal containers) or `width` (for vertical containers)
+    cross_size: Option<f32>,
+    min_cross: f32,
+    max_cross: f32,
+    /// The margins before and after the item on the main axis
+    main_margin: (f32, f32),
+    /// The margins before and after the item on the cross axis
+    cross_margin: (f32, f32),
+}
+
+impl FlexItem {
+    /// Resolves the sizes of the item, `container` is the size of the content box of the parent
+    fn new(node_id: NodeId, layout: &RectLayout, direction: LayoutDirection, container: LayoutSize, context: &LengthContext) -> Self {
+        let width = layout.width.and_then(|w| Some(w.0.resolve(context, container.width)));
+        let height = layout.height.and_then(|h| Some(h.0.resolve(context, container.height)));
+        let min_width = layout.min_width.and_then(|w| Some(w.0.resolve(context, container.width))).unwrap_or(0.0);
+        let min_height = layout.min_height.and_then(|h| Some(h.0.resolve(context, container.height))).unwrap_or(0.0);
+        let max_width = layout.max_width.and_then(|w| Some(w.0.resolve(context, container.width))).unwrap_or(::std::f32::INFINITY);
+        let max_height = layout.max_height.and_then(|h| Some(h.0.resolve(context, container.height))).unwrap_or(::std::f32::INFINITY);
+        let margin = layout.margin.and_then(|m| Some(m.0)).unwrap_or_default().resolve(context, container.width);
+
+        match direction {
+            LayoutDirection::Horizontal => Self {
+                node_id,
+                main_size: width, min_main: min_width, max_main: max_width,
+                cross_size: height, min_cross: min_height, max_cross: max_height,
+                main_margin: (margin.left, margin.right),
+                cross_margin: (margin.top, margin.bottom),
+            },
+            LayoutDirection::Vertical => Self {
+                node_id,
+                main_size: height, min_main: min_height, max_main: max_height,
+                cross_size: width, min_cross: min_width, max_cross: max_width,
+                main_margin: (margin.top, margin.bottom),
+                cross_margin: (margin.left, margin.right),
+            },
+        }
+    }
+
+    /// The size of the item on the main axis (including its margins), before the
+    /// remaining space is distributed, used for breaking the items into lines
+    fn hypothetical_main_size(&self) -> f32 {
+        let size = match self.main_size {
+            Some(s) => clamp(s, self.min_main, self.max_main),
+            None => self.min_main,
+        };
+        size + self.main_margin.0 + self.main_margin.1
+    }
+
+    /// The size that the item needs on the cross axis (including its margins), at minimum
+    fn hypothetical_cross_size(&self) -> f32 {
+        let size = match self.cross_size {
+            Some(s) => clamp(s, self.min_cross, self.max_cross),
+            None => self.min_cross,
+        };
+        size + self.cross_margin.0 + self.cross_margin.1
+    }
+}
+
+/// Clamps the `value` between `min` and `max` - like in CSS, `min` wins over `max`
+#[inline]
+fn clamp(value: f32, min: f32, max: f32) -> f32 {
+    value.min(max).max(min)
+}
+
+/// Lays out the `children` of a container (which has the `layout`, the length `context`
+/// and whose content box spans the `bounds`), then recursively lays out the children of each child.
+///
+/// `positioned_box` are the bounds of the nearest positioned ancestor, which
+/// the `position: absolute` children are placed in.
+fn layout_children(
+    arena: &Arena<RectLayout>,
+    layout: &RectLayout,
+    children: &[NodeId],
+    bounds: LayoutRect,
+    positioned_box: LayoutRect,
+    context: &LengthContext,
+    solved: &mut SolvedRects)
+{
+    let children = children.iter().cloned()
+        .filter(|child| arena[*child].data.display.unwrap_or_default() != LayoutDisplay::None)
+        .collect::<Vec<NodeId>>();
+
+    if children.is_empty() {
+        return;
+    }
+
+    let direction = layout.direction.unwrap_or_default();
+    let wrap = layout.wrap.unwrap_or_default();
+    let justify_content = layout.justify_content.unwrap_or_default();
+    let align_items = layout.align_items.unwrap_or_default();
+    let align_content = layout.align_content.unwrap_or_default();
+
+    let (main_available, cross_available) = match direction {
+        LayoutDirection::Horizontal => (bounds.size.width, bounds.size.height),
+        LayoutDirection::Vertical => (bounds.size.height, bounds.size.width),
+    };
+
+    let contexts = children.iter().map(|child| context.with_font_size(arena[*child].data.font_size)).collect::<Vec<LengthContext>>();
+    let items = children.iter().zip(contexts.iter())
+        .filter(|&(child, _)| arena[*child].data.position.unwrap_or_default() != LayoutPosition::Absolute)
+        .map(|(child, context)| FlexItem::new(*child, &arena[*child].data, direction, bounds.size, context))
+        .collect::<Vec<_>>();
+
+    let lines = match wrap {
+        LayoutWrap::NoWrap => vec![items],
+        LayoutWrap::Wrap => break_into_lines(items, main_available),
+    };
+
+    // Resolve the cross size of each line
+    let line_cross_sizes = if lines.len() == 1 {
+        vec![cross_available]
+    } else {
+        let mut sizes = lines.iter().map(|line| {
+            line.iter().map(|item| item.hypothetical_cross_size()).fold(0.0, f32::max)
+        }).collect::<Vec<f32>>();
+        let free_space = cross_available - sizes.iter().sum::<f32>();
+        if align_content == LayoutAlignContent::Stretch && free_space > 0.0 {
+            let extra = free_space / sizes.len() as f32;
+            for size in sizes.iter_mut() {
+                *size += extra;
+            }
+        }
+        sizes
+    };
+
+    let cross_free_space = cross_available - line_cross_sizes.iter().sum::<f32>();
+    let (mut cross_cursor, cross_gap) = distribute_free_space(cross_free_space, lines.len(), align_content_to_justify(align_content));
+
+    for (line, line_cross_size) in lines.iter().zip(line_cross_sizes.iter()) {
+
+        let main_sizes = resolve_main_sizes(line, main_available);
+        let main_margins = line.iter().map(|item| item.main_margin.0 + item.main_margin.1).sum::<f32>();
+        let main_free_space = main_available - main_sizes.iter().sum::<f32>() - main_margins;
+        let (mut main_cursor, main_gap) = distribute_free_space(main_free_space, line.len(), justify_content);
+
+        for (item, main_size) in line.iter().zip(main_sizes.iter()) {
+
+            let (cross_margin_start, cross_margin_end) = item.cross_margin;
+            let cross_space = line_cross_size - cross_margin_start - cross_margin_end;
+            let cross_size = clamp(item.cross_size.unwrap_or(cross_space.max(0.0)), item.min_cross, item.max_cross);
+            let cross_offset = cross_margin_start + match align_items {
+                LayoutAlignItems::Stretch | LayoutAlignItems::Start => 0.0,
+                LayoutAlignItems::Center => (cross_space - cross_size) / 2.0,
+                LayoutAlignItems::End => cross_space - cross_size,
+            };
+
+            main_cursor += item.main_margin.0;
+
+            let rect = match direction {
+                LayoutDirection::Horizontal => LayoutRect::new(
+                    LayoutPoint::new(bounds.origin.x + main_cursor, bounds.origin.y + cross_cursor + cross_offset),
+                    LayoutSize::new(*main_size, cross_size)),
+                LayoutDirection::Vertical => LayoutRect::new(
+                    LayoutPoint::new(bounds.origin.x + cross_cursor + cross_offset, bounds.origin.y + main_cursor),
+                    LayoutSize::new(cross_size, *main_size)),
+            };
+
+            solved.bounds.insert(item.node_id, rect);
+            main_cursor += main_size + item.main_margin.1 + main_gap;
+        }
+
+        cross_cursor += line_cross_size + cross_gap;
+    }
+
+    for (child, context) in children.iter().zip(contexts.iter()) {
+        let child_layout = &arena[*child].data;
+        let position = child_layout.position.unwrap_or_default();
+        let child_bounds = match position {
+            LayoutPosition::Static => solved.bounds[child],
+            LayoutPosition::Relative => get_relative_bounds(child_layout, solved.bounds[child], bounds.size, context),
+            LayoutPosition::Absolute => get_absolute_bounds(*child, child_layout, bounds.origin, positioned_box, context),
+        };
+        solved.bounds.insert(*child, child_bounds);
+        solved.length_contexts.insert(*child, *context);
+
+        let padding = child_layout.padding.and_then(|p| Some(p.0)).unwrap_or_default().resolve(context, bounds.size.width);
+        let content_box = get_content_box(child_bounds, padding);
+        solved.content_boxes.insert(*child, content_box);
+
+        // Positioned nodes contain their absolutely positioned descendants
+        let child_positioned_box = match position {
+            LayoutPosition::Static => positioned_box,
+            LayoutPosition::Relative | LayoutPosition::Absolute => child_bounds,
+        };
+
+        let grandchildren = child.children(arena).collect::<Vec<NodeId>>();
+        layout_children(arena, child_layout, &grandchildren, content_box, child_positioned_box, context, solved);
+    }
+}
+
+/// Resolves the `top`, `right`, `bottom` and `left` of a node (in that order), `None` for `auto`.
+/// Percentages are relative to the size of the `container`.
+fn resolve_offsets(layout: &RectLayout, container: LayoutSize, context: &LengthContext)
+-> (Option<f32>, Option<f32>, Option<f32>, Option<f32>)
+{
+    let resolve = |offset: Option<PixelValue>, size: f32| offset.and_then(|o| Some(o.resolve(context, size)));
+    (
+        resolve(layout.top.and_then(|t| t.0), container.height),
+        resolve(layout.right.and_then(|r| r.0), container.width),
+        resolve(layout.bottom.and_then(|b| b.0), container.height),
+        resolve(layout.left.and_then(|l| l.0), container.width),
+    )
+}
+
+/// Moves the `bounds` of a `position: relative` node by its `top` and `left` or,
+/// if these are `auto`, by its `bottom` and `right`. `container` is the size of
+/// the content box of the parent.
+fn get_relative_bounds(layout: &RectLayout, bounds: LayoutRect, container: LayoutSize, context: &LengthContext)
+-> LayoutRect
+{
+    let (top, right, bottom, left) = resolve_offsets(layout, container, context);
+    let dx = left.or(right.and_then(|r| Some(-r))).unwrap_or(0.0);
+    let dy = top.or(bottom.and_then(|b| Some(-b))).unwrap_or(0.0);
+    bounds.translate(&LayoutVector2D::new(dx, dy))
+}
+
+/// Places a `position: absolute` node inside of the `containing_block` (the bounds of its
+/// nearest positioned ancestor). `static_position` is where the node is placed on an axis where
+/// both offsets are `auto`, which is the start of the content box of the parent.
+fn get_absolute_bounds(
+    node_id: NodeId,
+    layout: &RectLayout,
+    static_position: LayoutPoint,
+    containing_block: LayoutRect,
+    context: &LengthContext)
+-> LayoutRect
+{
+    let (top, right, bottom, left) = resolve_offsets(layout, containing_block.size, context);
+    // A horizontal item has the width on the main axis and the height on the cross axis
+    let item = FlexItem::new(node_id, layout, LayoutDirection::Horizontal, containing_block.size, context);
+
+    let (x, width) = resolve_absolute_axis(
+        (left, right),
+        (item.main_size, item.min_main, item.max_main),
+        item.main_margin,
+        (containing_block.origin.x, containing_block.size.width),
+        static_position.x);
+
+    let (y, height) = resolve_absolute_axis(
+        (top, bottom),
+        (item.cross_size, item.min_cross, item.max_cross),
+        item.cross_margin,
+        (containing_block.origin.y, containing_block.size.height),
+        static_position.y);
+
+    LayoutRect::new(LayoutPoint::new(x, y), LayoutSize::new(width, height))
+}
+
+/// Resolves the origin and size of an absolutely positioned node on one axis, from its
+/// start / end `offsets` (i.e. `left` / `right`), its `(size, min, max)`, its `margin`
+/// and the `(origin, size)` of the containing block.
+///
+/// Since there is no content-based sizing yet, a node without a size stretches
+/// between its offsets, `auto` offsets count as `0` for that.
+fn resolve_absolute_axis(
+    offsets: (Option<f32>, Option<f32>),
+    size: (Option<f32>, f32, f32),
+    margin: (f32, f32),
+    containing_block: (f32, f32),
+    static_start: f32)
+-> (f32, f32)
+{
+    let (start, end) = offsets;
+    let (size, min, max) = size;
+    let (block_start, block_size) = containing_block;
+
+    let size = match size {
+        Some(s) => s,
+        None => block_size - start.unwrap_or(0.0) - end.unwrap_or(0.0) - margin.0 - margin.1,
+    };
+    let size = clamp(size.max(0.0), min, max);
+
+    let origin = match (start, end) {
+        (Some(start), _) => block_start + start + margin.0,
+        (None, Some(end)) => block_start + block_size - end - margin.1 - size,
+        (None, None) => static_start + margin.0,
+    };
+
+    (origin, size)
+}
+
+/// A node in the paint order, see `get_paint_order`
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PaintNode {
+    pub(crate) node_id: NodeId,
+    /// The descendants of the node that are painted together with the node (after it), in
+    /// paint order. If the node creates a stacking context, these are all of its descendants,
+    /// sorted by their `z-index`.
+    ///
+    /// The descendants of a node that doesn't create a stacking context are painted as a
+    /// part of the enclosing stacking context instead (which may paint them further up or down).
+    pub(crate) children: Vec<PaintNode>,
+}
+
+/// Returns the order in which the nodes have to be painted, the last node is the top-most one.
+/// Like in CSS, every node with a `z-index` (or for which `is_isolated` returns `true`, i.e.
+/// because of its `opacity`) creates a stacking context. Inside of every stacking context, the
+/// nodes are painted in this order:
+///
+/// - nodes with a negative `z-index`, from the lowest to the highest `z-index`
+/// - nodes that aren't positioned and don't create a stacking context, in document order
+/// - positioned nodes without a `z-index` and nodes with `z-index: 0`, in document order
+/// - nodes with a positive `z-index`, from the lowest to the highest `z-index`
+///
+/// Like for flex items in CSS, the `z-index` also works for nodes that aren't positioned.
+/// Nodes with `display: none` aren't painted at all.
+pub(crate) fn get_paint_order<F>(arena: &Arena<RectLayout>, root: NodeId, is_isolated: &F)
+-> Vec<PaintNode> where F: Fn(NodeId) -> bool
+{
+    let root_nodes = root.following_siblings(arena).collect::<Vec<NodeId>>();
+    get_stacking_context_children(arena, &root_nodes, is_isolated)
+}
+
+/// Returns the `nodes` and their descendants in the paint order of the stacking context they belong to
+fn get_stacking_context_children<F>(arena: &Arena<RectLayout>, nodes: &[NodeId], is_isolated: &F)
+-> Vec<PaintNode> where F: Fn(NodeId) -> bool
+{
+    let mut in_flow = Vec::new();
+    let mut layers = Vec::new();
+    collect_paint_layers(arena, nodes, is_isolated, &mut in_flow, &mut layers);
+
+    // Stable sort, so that nodes with the same z-index stay in document order
+    layers.sort_by_key(|&(z_index, _)| z_index);
+
+    let mut paint_order = Vec::new();
+    let mut in_flow = Some(in_flow);
+    for (z_index, paint_node) in layers {
+        if z_index >= 0 {
+            if let Some(in_flow) = in_flow.take() {
+                paint_order.extend(in_flow);
+            }
+        }
+        paint_order.push(paint_node);
+    }
+    if let Some(in_flow) = in_flow {
+        paint_order.extend(in_flow);
+    }
+    paint_order
+}
+
+/// Sorts the `nodes` and their descendants into the nodes that are painted in document
+/// order (`in_flow`) and the nodes that are painted by their z-index (`layers`)
+fn collect_paint_layers<F>(
+    arena: &Arena<RectLayout>,
+    nodes: &[NodeId],
+    is_isolated: &F,
+    in_flow: &mut Vec<PaintNode>,
+    layers: &mut Vec<(i32, PaintNode)>)
+where F: Fn(NodeId) -> bool
+{
+    for node_id in nodes {
+        let layout = &arena[*node_id].data;
+        if layout.display.unwrap_or_default() == LayoutDisplay::None {
+            continue;
+        }
+
+        let children = node_id.children(arena).collect::<Vec<NodeId>>();
+        let z_index = layout.z_index.and_then(|z| z.0);
+
+        if z_index.is_some() || is_isolated(*node_id) {
+            // The node creates a stacking context: its descendants are sorted separately
+            let children = get_stacking_context_children(arena, &children, is_isolated);
+            layers.push((z_index.unwrap_or(0), PaintNode { node_id: *node_id, children }));
+        } else if layout.position.unwrap_or_default() != LayoutPosition::Static {
+            // Positioned nodes without a z-index are painted above the nodes in the normal flow,
+            // but their positioned descendants still belong to the enclosing stacking context
+            let idx = layers.len();
+            layers.push((0, PaintNode { node_id: *node_id, children: Vec::new() }));
+            let mut descendants = Vec::new();
+            collect_paint_layers(arena, &children, is_isolated, &mut descendants, layers);
+            layers[idx].1.children = descendants;
+        } else {
+            in_flow.push(PaintNode { node_id: *node_id, children: Vec::new() });
+            collect_paint_layers(arena, &children, is_isolated, in_flow, layers);
+        }
+    }
+}
+
+/// Breaks the items into lines (for `flex-wrap: wrap`), so that the hypothetical
+/// main sizes of the items of one line don't exceed the `main_available` space.
+/// Every line has at least one item.
+fn break_into_lines(items: Vec<FlexItem>, main_available: f32)
+-> Vec<Vec<FlexItem>>
+{
+    let mut lines = Vec::<Vec<FlexItem>>::new();
+    let mut current_line = Vec::new();
+    let mut current_line_size = 0.0;
+
+    for item in items {
+        let item_size = item.hypothetical_main_size();
+        if !current_line.is_empty() && current_line_size + item_size > main_available {
+            lines.push(current_line);
+            current_line = Vec::new();
+            current_line_size = 0.0;
+        }
+        current_line_size += item_size;
+        current_line.push(item);
+    }
+
+    lines.push(current_line);
+    lines
+}
+
+/// Resolves the main axis sizes of the items of one line: items with a fixed size
+/// keep their size, the remaining space (minus the margins of all items) gets shared
+/// equally by the flexible items.
+///
+/// If sharing the space would violate the min / max size of a flexible item, the
+/// item gets frozen at its min / max size and the space is re-distributed among
+/// the remaining flexible items.
+fn resolve_main_sizes(line: &[FlexItem], main_available: f32)
+-> Vec<f32>
+{
+    let mut sizes = line.iter().map(|item| {
+        item.main_size.and_then(|s| Some(clamp(s, item.min_main, item.max_main)))
+    }).collect::<Vec<Option<f32>>>();
+
+    loop {
+        let flexible_items = (0..line.len()).filter(|idx| sizes[*idx].is_none()).collect::<Vec<usize>>();
+        if flexible_items.is_empty() {
+            break;
+        }
+
+        let margins = line.iter().map(|item| item.main_margin.0 + item.main_margin.1).sum::<f32>();
+        let used_space = sizes.iter().filter_map(|s| *s).sum::<f32>() + margins;
+        let share = ((main_available - used_space) / flexible_items.len() as f32).max(0.0);
+
+        let mut has_frozen_item = false;
+        for idx in &flexible_items {
+            let item = &line[*idx];
+            let clamped = clamp(share, item.min_main, item.max_main);
+            if clamped != share {
+                sizes[*idx] = Some(clamped);
+                has_frozen_item = true;
+            }
+        }
+
+        if !has_frozen_item {
+            for idx in flexible_items {
+                sizes[idx] = Some(share);
+            }
+            break;
+        }
+    }
+
+    sizes.into_iter().map(|s| s.unwrap_or(0.0)).collect()
+}
+
+/// Returns the offset of the first item and the gap between two items,
+/// for distributing the `free_space` among `item_count` items
+fn distribute_free_space(free_space: f32, item_count: usize, justify_content: LayoutJustifyContent)
+-> (f32, f32)
+{
+    use css_parser::LayoutJustifyContent::*;
+
+    if item_count == 0 {
+        return (0.0, 0.0);
+    }
+
+    let count = item_count as f32;
+
+    match justify_content {
+        Start => (0.0, 0.0),
+        End => (free_space, 0.0),
+        Center => (free_space / 2.0, 0.0),
+        // Like in CSS, overflowing items fall back to start / center
+        SpaceBetween => {
+            if free_space > 0.0 && item_count > 1 {
+                (0.0, free_space / (count - 1.0))
+            } else {
+                (0.0, 0.0)
+            }
+        },
+        SpaceAround => {
+            if free_space > 0.0 {
+                (free_space / count / 2.0, free_space / count)
+            } else {
+                (free_space / 2.0, 0.0)
+            }
+        },
+    }
+}
+
+/// `align-content` positions the lines on the cross axis the same way that
+/// `justify-content` positions the items on the main axis
+fn align_content_to_justify(align_content: LayoutAlignContent)
+-> LayoutJustifyContent
+{
+    match align_content {
+        LayoutAlignContent::Stretch | LayoutAlignContent::Start => LayoutJustifyContent::Start,
+        LayoutAlignContent::Center => LayoutJustifyContent::Center,
+        LayoutAlignContent::End => LayoutJustifyContent::End,
+        LayoutAlignContent::SpaceBetween => LayoutJustifyContent::SpaceBetween,
+        LayoutAlignContent::SpaceAround => LayoutJustifyContent::SpaceAround,
+    }
+}
+
+#[cfg(test)]
+mod layout_tests {
+
+    use super::*;
+    use css_parser::{
+        PixelValue, CssMetric, LayoutWidth, LayoutHeight, LayoutMaxWidth,
+        LayoutPadding, LayoutMargin, LayoutSideOffsets, FontSize, parse_pixel_value,
+        LayoutTop, LayoutRight, LayoutBottom, LayoutLeft, LayoutZIndex,
+    };
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> LayoutRect {
+        LayoutRect::new(LayoutPoint::new(x, y), LayoutSize::new(width, height))
+    }
+
+    fn px(value: f32) -> PixelValue {
+        PixelValue::from_metric(CssMetric::Px, value)
+    }
+
+    fn window() -> LayoutRect {
+        rect(0.0, 0.0, 800.0, 600.0)
+    }
+
+    /// Creates a root node with the `root` layout and one child per item in `children`
+    fn make_tree(root: RectLayout, children: &[RectLayout]) -> (Arena<RectLayout>, NodeId, Vec<NodeId>) {
+        let mut arena = Arena::new();
+        let root_id = arena.new_node(root);
+        let child_ids = children.iter().map(|child| {
+            let child_id = arena.new_node(*child);
+            root_id.append(child_id, &mut arena);
+            child_id
+        }).collect();
+        (arena, root_id, child_ids)
+    }
+
+    fn fixed_size(width: isize, height: isize) -> RectLayout {
+        RectLayout {
+            width: Some(LayoutWidth(px(width as f32))),
+            height: Some(LayoutHeight(px(height as f32))),
+            .. Default::default()
+        }
+    }
+
+    #[test]
+    fn test_layout_root_fills_window() {
+        let (arena, root, _) = make_tree(RectLayout::default(), &[]);
+        let solved = solve_layout(&arena, root, window());
+        assert_eq!(solved.bounds[&root], window());
+    }
+
+    #[test]
+    fn test_layout_flexible_children_share_space() {
+        let (arena, root, children) = make_tree(RectLayout::default(), &[RectLayout::default(), RectLayout::default()]);
+        let solved = solve_layout(&arena, root, window());
+        assert_eq!(solved.bounds[&children[0]], rect(0.0, 0.0, 800.0, 300.0));
+        assert_eq!(solved.bounds[&children[1]], rect(0.0, 300.0, 800.0, 300.0));
+    }
+
+    #[test]
+    fn test_layout_horizontal_fixed_and_flexible() {
+        let row = RectLayout { direction: Some(LayoutDirection::Horizontal), .. Default::default() };
+        let fixed = RectLayout { width: Some(LayoutWidth(px(200.0))), .. Default::default() };
+        let (arena, root, children) = make_tree(row, &[fixed, RectLayout::default()]);
+        let solved = solve_layout(&arena, root, window());
+        assert_eq!(solved.bounds[&children[0]], rect(0.0, 0.0, 200.0, 600.0));
+        assert_eq!(solved.bounds[&children[1]], rect(200.0, 0.0, 600.0, 600.0));
+    }
+
+    #[test]
+    fn test_layout_max_width_redistributes_space() {
+        let row = RectLayout { direction: Some(LayoutDirection::Horizontal), .. Default::default() };
+        let limited = RectLayout { max_width: Some(LayoutMaxWidth(px(100.0))), .. Default::default() };
+        let (arena, root, children) = make_tree(row, &[limited, RectLayout::default()]);
+        let solved = solve_layout(&arena, root, window());
+        assert_eq!(solved.bounds[&children[0]], rect(0.0, 0.0, 100.0, 600.0));
+        assert_eq!(solved.bounds[&children[1]], rect(100.0, 0.0, 700.0, 600.0));
+    }
+
+    #[test]
+    fn test_layout_justify_and_align_center() {
+        let centered = RectLayout {
+            direction: Some(LayoutDirection::Horizontal),
+            justify_content: Some(LayoutJustifyContent::Center),
+            align_items: Some(LayoutAlignItems::Center),
+            .. Default::default()
+        };
+        let (arena, root, children) = make_tree(centered, &[fixed_size(100, 50), fixed_size(100, 50)]);
+        let solved = solve_layout(&arena, root, window());
+        assert_eq!(solved.bounds[&children[0]], rect(300.0, 275.0, 100.0, 50.0));
+        assert_eq!(solved.bounds[&children[1]], rect(400.0, 275.0, 100.0, 50.0));
+    }
+
+    #[test]
+    fn test_layout_justify_space_between() {
+        let row = RectLayout {
+            direction: Some(LayoutDirection::Horizontal),
+            justify_content: Some(LayoutJustifyContent::SpaceBetween),
+            .. Default::default()
+        };
+        let (arena, root, children) = make_tree(row, &[fixed_size(100, 50), fixed_size(100, 50), fixed_size(100, 50)]);
+        let solved = solve_layout(&arena, root, window());
+        assert_eq!(solved.bounds[&children[0]].origin.x, 0.0);
+        assert_eq!(solved.bounds[&children[1]].origin.x, 350.0);
+        assert_eq!(solved.bounds[&children[2]].origin.x, 700.0);
+    }
+
+    #[test]
+    fn test_layout_wrap_breaks_lines() {
+        let wrapping = RectLayout {
+            direction: Some(LayoutDirection::Horizontal),
+            wrap: Some(LayoutWrap::Wrap),
+            align_content: Some(LayoutAlignContent::Start),
+            .. Default::default()
+        };
+        let (arena, root, children) = make_tree(wrapping, &[fixed_size(500, 100), fixed_size(500, 100)]);
+        let solved = solve_layout(&arena, root, window());
+        assert_eq!(solved.bounds[&children[0]], rect(0.0, 0.0, 500.0, 100.0));
+        assert_eq!(solved.bounds[&children[1]], rect(0.0, 100.0, 500.0, 100.0));
+    }
+
+    #[test]
+    fn test_layout_nested_children_are_relative_to_parent() {
+        let row = RectLayout { direction: Some(LayoutDirection::Horizontal), .. Default::default() };
+        let (mut arena, root, children) = make_tree(row, &[fixed_size(200, 600), RectLayout::default()]);
+        let nested = arena.new_node(fixed_size(50, 50));
+        children[1].append(nested, &mut arena);
+        let solved = solve_layout(&arena, root, window());
+        assert_eq!(solved.bounds[&nested], rect(200.0, 0.0, 50.0, 50.0));
+    }
+
+    #[test]
+    fn test_layout_padding_shrinks_content_box() {
+        let padded = RectLayout {
+            padding: Some(LayoutPadding(LayoutSideOffsets {
+                top: Some(px(10.0)), right: Some(px(20.0)), bottom: Some(px(30.0)), left: Some(px(40.0)),
+            })),
+            .. Default::default()
+        };
+        let (arena, root, children) = make_tree(padded, &[RectLayout::default()]);
+        let solved = solve_layout(&arena, root, window());
+        assert_eq!(solved.bounds[&root], window());
+        assert_eq!(solved.bounds[&children[0]], rect(40.0, 10.0, 740.0, 560.0));
+        assert_eq!(solved.content_boxes[&root], rect(40.0, 10.0, 740.0, 560.0));
+    }
+
+    #[test]
+    fn test_layout_margin_is_added_around_items() {
+        let row = RectLayout { direction: Some(LayoutDirection::Horizontal), .. Default::default() };
+        let margin = RectLayout {
+            margin: Some(LayoutMargin(LayoutSideOffsets::uniform(px(10.0)))),
+            .. Default::default()
+        };
+        let fixed_with_margin = RectLayout { margin: margin.margin, .. fixed_size(100, 50) };
+        let (arena, root, children) = make_tree(row, &[fixed_with_margin, margin]);
+        let solved = solve_layout(&arena, root, window());
+        assert_eq!(solved.bounds[&children[0]], rect(10.0, 10.0, 100.0, 50.0));
+        // the flexible item gets the remaining space minus both margins
+        assert_eq!(solved.bounds[&children[1]], rect(130.0, 10.0, 660.0, 580.0));
+    }
+
+    #[test]
+    fn test_layout_resolves_relative_lengths() {
+        let length = |value| parse_pixel_value(value).unwrap();
+        let root = RectLayout {
+            direction: Some(LayoutDirection::Horizontal),
+            font_size: Some(FontSize::px(20.0)),
+            padding: Some(LayoutPadding(LayoutSideOffsets::uniform(length("10%")))),
+            .. Default::default()
+        };
+        let children = [
+            RectLayout { width: Some(LayoutWidth(length("50%"))), height: Some(LayoutHeight(length("50%"))), .. Default::default() },
+            RectLayout { width: Some(LayoutWidth(length("2em"))), font_size: Some(FontSize::em(2.0)), .. Default::default() },
+            RectLayout { width: Some(LayoutWidth(length("2rem"))), font_size: Some(FontSize::em(2.0)), .. Default::default() },
+            RectLayout { width: Some(LayoutWidth(length("10vw"))), height: Some(LayoutHeight(length("calc(100% - 1rem)"))), .. Default::default() },
+        ];
+        let (arena, root, children) = make_tree(root, &children);
+        let solved = solve_layout(&arena, root, window());
+
+        // the padding is relative to the width of the window: 80px on every side
+        assert_eq!(solved.content_boxes[&root], rect(80.0, 80.0, 640.0, 440.0));
+        // percentages are relative to the content box of the parent
+        assert_eq!(solved.bounds[&children[0]].size, LayoutSize::new(320.0, 220.0));
+        // em is relative to the font size of the node (2 * 2 * 20px), rem to the root node
+        assert_eq!(solved.bounds[&children[1]].size.width, 80.0);
+        assert_eq!(solved.bounds[&children[2]].size.width, 40.0);
+        // vw is relative to the window
+        assert_eq!(solved.bounds[&children[3]].size, LayoutSize::new(80.0, 420.0));
+    }
+
+    #[test]
+    fn test_layout_computes_font_sizes() {
+        let font_size = |value| RectLayout { font_size: Some(FontSize(parse_pixel_value(value).unwrap())), .. Default::default() };
+        let mut arena = Arena::new();
+        let root = arena.new_node(font_size("20px"));
+        let em = arena.new_node(font_size("1.5em"));
+        let inherited = arena.new_node(RectLayout::default());
+        let percentage = arena.new_node(font_size("50%"));
+        root.append(em, &mut arena);
+        em.append(inherited, &mut arena);
+        inherited.append(percentage, &mut arena);
+        let solved = solve_layout(&arena, root, window());
+
+        // em and % are relative to the computed font size of the parent,
+        // a node without a font size inherits the computed font size
+        let font_sizes = [root, em, inherited, percentage].iter()
+            .map(|node_id| solved.length_contexts[node_id].font_size)
+            .collect::<Vec<f32>>();
+        assert_eq!(font_sizes, vec![20.0, 30.0, 30.0, 15.0]);
+        assert_eq!(solved.length_contexts[&percentage].root_font_size, 20.0);
+    }
+
+    #[test]
+    fn test_layout_display_none_is_removed() {
+        let hidden = RectLayout { display: Some(LayoutDisplay::None), .. Default::default() };
+        let (mut arena, root, children) = make_tree(RectLayout::default(), &[hidden, RectLayout::default()]);
+        let nested = arena.new_node(RectLayout::default());
+        children[0].append(nested, &mut arena);
+        let solved = solve_layout(&arena, root, window());
+        assert_eq!(solved.bounds.get(&children[0]), None);
+        assert_eq!(solved.bounds.get(&nested), None);
+        assert_eq!(solved.bounds[&children[1]], rect(0.0, 0.0, 800.0, 600.0));
+    }
+
+    #[test]
+    fn test_layout_position_relative() {
+        let relative = RectLayout {
+            position: Some(LayoutPosition::Relative),
+            top: Some(LayoutTop(Some(px(10.0)))),
+            bottom: Some(LayoutBottom(Some(px(100.0)))),
+            right: Some(LayoutRight(Some(parse_pixel_value("10%").unwrap()))),
+            .. fixed_size(100, 50)
+        };
+        let (mut arena, root, children) = make_tree(RectLayout::default(), &[relative, fixed_size(100, 50)]);
+        let nested = arena.new_node(fixed_size(10, 10));
+        children[0].append(nested, &mut arena);
+        let solved = solve_layout(&arena, root, window());
+        // top wins over bottom, right is used since left is auto
+        assert_eq!(solved.bounds[&children[0]], rect(-80.0, 10.0, 100.0, 50.0));
+        assert_eq!(solved.bounds[&nested], rect(-80.0, 10.0, 10.0, 10.0));
+        // the sibling isn't affected by the offset
+        assert_eq!(solved.bounds[&children[1]], rect(0.0, 50.0, 100.0, 50.0));
+    }
+
+    #[test]
+    fn test_layout_position_absolute() {
+        let positioned = RectLayout {
+            position: Some(LayoutPosition::Relative),
+            padding: Some(LayoutPadding(LayoutSideOffsets::uniform(px(10.0)))),
+            .. fixed_size(400, 300)
+        };
+        let (mut arena, root, children) = make_tree(RectLayout::default(), &[fixed_size(100, 100), positioned]);
+
+        let wrapper = arena.new_node(fixed_size(100, 100));
+        children[1].append(wrapper, &mut arena);
+        let badge = arena.new_node(RectLayout {
+            position: Some(LayoutPosition::Absolute),
+            top: Some(LayoutTop(Some(px(-5.0)))),
+            right: Some(LayoutRight(Some(px(-5.0)))),
+            .. fixed_size(20, 20)
+        });
+        wrapper.append(badge, &mut arena);
+        let overlay = arena.new_node(RectLayout {
+            position: Some(LayoutPosition::Absolute),
+            left: Some(LayoutLeft(Some(px(0.0)))),
+            right: Some(LayoutRight(Some(px(0.0)))),
+            bottom: Some(LayoutBottom(Some(px(0.0)))),
+            height: Some(LayoutHeight(parse_pixel_value("50%").unwrap())),
+            .. Default::default()
+        });
+        wrapper.append(overlay, &mut arena);
+        let in_flow = arena.new_node(fixed_size(30, 30));
+        wrapper.append(in_flow, &mut arena);
+
+        let solved = solve_layout(&arena, root, window());
+        // the wrapper isn't positioned, so the absolute nodes are placed in its parent
+        assert_eq!(solved.bounds[&wrapper], rect(10.0, 110.0, 100.0, 100.0));
+        assert_eq!(solved.bounds[&badge], rect(385.0, 95.0, 20.0, 20.0));
+        assert_eq!(solved.bounds[&overlay], rect(0.0, 250.0, 400.0, 150.0));
+        // absolute nodes don't take up space in the layout of their parent
+        assert_eq!(solved.bounds[&in_flow], rect(10.0, 110.0, 30.0, 30.0));
+    }
+
+    fn paint_order(paint_nodes: &[PaintNode]) -> Vec<NodeId> {
+        let mut order = Vec::new();
+        for paint_node in paint_nodes {
+            order.push(paint_node.node_id);
+            order.extend(paint_order(&paint_node.children));
+        }
+        order
+    }
+
+    #[test]
+    fn test_paint_order_z_index() {
+        let z_index = |z| RectLayout { z_index: Some(LayoutZIndex(Some(z))), .. Default::default() };
+        let positioned = RectLayout { position: Some(LayoutPosition::Relative), .. Default::default() };
+        let (mut arena, root, children) = make_tree(RectLayout::default(), &[
+            z_index(2), positioned, z_index(-1), RectLayout::default(), z_index(1),
+        ]);
+        // the z-index of the dropdown is relative to the whole window, since its
+        // parent doesn't create a stacking context
+        let dropdown = arena.new_node(z_index(5));
+        children[3].append(dropdown, &mut arena);
+        let hidden = arena.new_node(RectLayout { display: Some(LayoutDisplay::None), .. Default::default() });
+        children[3].append(hidden, &mut arena);
+        // but the z-index of this node only sorts it inside of its parent
+        let nested = arena.new_node(z_index(10));
+        children[4].append(nested, &mut arena);
+
+        // the root doesn't create a stacking context either, so the negative z-index is painted below it
+        let order = paint_order(&get_paint_order(&arena, root, &|_| false));
+        assert_eq!(order, vec![
+            children[2], root, children[3], children[1], children[4], nested, children[0], dropdown,
+        ]);
+
+        // isolated nodes (i.e. with an opacity) create a stacking context without a z-index
+        let order = paint_order(&get_paint_order(&arena, root, &|node_id| node_id == children[3]));
+        assert_eq!(order, vec![
+            children[2], root, children[1], children[3], dropdown, children[4], nested, children[0],
+        ]);
+    }
+}
@@ -1,9 +1,17 @@
 use {
-    traits::Layout,
+    traits::{Layout, DefaultStyle},
     dom::{Dom, NodeType},
     images::ImageId,
+    css::Css,
 };
 
+#[cfg(target_os="windows")]
+const BUTTON_CSS: &str = include_str!("../styles/button_windows.css");
+#[cfg(target_os="linux")]
+const BUTTON_CSS: &str = include_str!("../styles/button_linux.css");
+#[cfg(target_os="macos")]
+const BUTTON_CSS: &str = include_str!("../styles/button_macos.css");
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct Button {
     pub content: ButtonContent,
@@ -37,7 +45,7 @@ impl Button {
     -> Dom<T> where T: Layout
     {
         use self::ButtonContent::*;
-        let mut button_root = Dom::new(NodeType::Div).with_class("__azul-native-button");
+        let mut button_root = Dom::new(NodeType::Div).with_class(<Self as DefaultStyle>::STYLE_CLASS);
         button_root.add_child(match self.content {
             Text(s) => Dom::new(NodeType::Label(s)),
             Image(i) => Dom::new(NodeType::Image(i)),
@@ -46,6 +54,14 @@ impl Button {
     }
 }
 
+impl DefaultStyle for Button {
+    const STYLE_CLASS: &'static str = "__azul-native-button";
+
+    fn default_style() -> Css {
+        Css::new_from_string(BUTTON_CSS).unwrap()
+    }
+}
+
 #[test]
 fn __codecov_test_widget_button_file() {
 
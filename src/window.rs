@@ -14,23 +14,18 @@ use glium::{
     IncompatibleOpenGl, Display,
     debug::DebugCallbackBehavior,
     glutin::{self, EventsLoop, AvailableMonitorsIter, GlProfile, GlContext, GlWindow, CreationError,
-             MonitorId, EventsLoopProxy, ContextError, ContextBuilder, WindowBuilder, dpi::LogicalSize},
+             MonitorId, EventsLoopProxy, ContextError, ContextBuilder, WindowBuilder, MouseCursor, dpi::LogicalSize},
     backend::{Context, Facade, glutin::DisplayCreationError},
 };
 use gleam::gl::{self, Gl};
-use euclid::TypedScale;
-use cassowary::{
-    Variable, Solver,
-    strength::*,
-};
 
 use {
     dom::Texture,
     css::{Css, FakeCss},
-    window_state::{WindowState, MouseState, KeyboardState},
+    window_state::{self, WindowState, MouseState, KeyboardState, style_cursor_to_mouse_cursor},
     display_list::SolvedLayout,
     traits::Layout,
-    cache::{EditVariableCache, DomTreeCache},
+    cache::DomTreeCache,
     id_tree::NodeId,
     compositor::Compositor,
     text_cache::TextCache,
@@ -54,6 +49,8 @@ pub struct FakeWindow {
     pub css: FakeCss,
     /// The window state for the next frame
     pub state: WindowState,
+    /// The solved layout of the last frame (read-only), see `get_bounds`
+    pub(crate) layout: SolvedLayout,
     /// An Rc to the original WindowContext - this is only so that
     /// the user can create textures and other OpenGL content in the window
     /// but not change any window properties from underneath - this would
@@ -90,6 +87,22 @@ impl FakeWindow {
         self.state.mouse_state
     }
 
+    /// Returns the bounds of the node with the given `NodeId`, as solved in the last frame.
+    /// Returns `None` if the node didn't exist in the last frame.
+    pub fn get_bounds(&self, node_id: NodeId) -> Option<LayoutRect> {
+        self.layout.get_bounds(node_id)
+    }
+
+    /// Returns the bounds of the node with the given CSS id (`Dom::with_id`), as solved in the last frame
+    pub fn get_bounds_by_id(&self, css_id: &str) -> Option<LayoutRect> {
+        self.layout.get_bounds_by_id(css_id)
+    }
+
+    /// Returns the bounds of the node with the given hit-testing tag, as solved in the last frame
+    pub fn get_bounds_by_tag(&self, tag: u64) -> Option<LayoutRect> {
+        self.layout.get_bounds_by_tag(tag)
+    }
+
 }
 
 /// Read-only window which can be used to create / draw
@@ -172,8 +185,9 @@ impl fmt::Debug for FakeWindow {
             "FakeWindow {{\
                 css: {:?}, \
                 state: {:?}, \
+                layout: {:?}, \
                 read_only_window: Rc<Display>, \
-            }}", self.css, self.state)
+            }}", self.css, self.state, self.layout)
     }
 }
 
@@ -193,6 +207,8 @@ pub struct WindowEvent {
     pub cursor_relative_to_item: (f32, f32),
     /// The (x, y) position of the mouse cursor, **relative to top left of the window**.
     pub cursor_in_viewport: (f32, f32),
+    /// The bounds of the element that was hit, as solved in the last frame
+    pub hit_node_bounds: Option<LayoutRect>,
 }
 
 impl WindowEvent {
@@ -203,6 +219,7 @@ impl WindowEvent {
             number_of_previous_siblings: None,
             cursor_relative_to_item: (0.0, 0.0),
             cursor_in_viewport: (0.0, 0.0),
+            hit_node_bounds: None,
         }
     }
 }
@@ -481,55 +498,25 @@ pub struct Window<T: Layout> {
     /// The `WindowInternal` allows us to solve some borrowing issues
     pub(crate) internal: WindowInternal,
     /// The solver for the UI, for caching the results of the computations
-    pub(crate) solver: UiSolver<T>,
+    pub(crate) solver: UiSolver,
     // The background thread that is running for this window.
     // pub(crate) background_thread: Option<JoinHandle<()>>,
     /// The css (how the current window is styled)
     pub css: Css,
 }
 
-/// Used in the solver, for the root constraint
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub(crate) struct WindowDimensions {
-    pub(crate) layout_size: LayoutSize,
-    pub(crate) width_var: Variable,
-    pub(crate) height_var: Variable,
-}
-
-impl WindowDimensions {
-    pub fn new_from_layout_size(layout_size: LayoutSize) -> Self {
-        Self {
-            layout_size: layout_size,
-            width_var: Variable::new(),
-            height_var: Variable::new(),
-        }
-    }
-
-    pub fn width(&self) -> f32 {
-        self.layout_size.width_typed().get()
-    }
-    pub fn height(&self) -> f32 {
-        self.layout_size.height_typed().get()
-    }
-}
-
 /// Solver for solving the UI of the current window
-pub(crate) struct UiSolver<T: Layout> {
-    /// The actual solver
-    pub(crate) solver: Solver,
+pub(crate) struct UiSolver {
     /// Solved layout from the previous frame (empty by default)
-    /// This is necessary for caching the constraints of the given layout
-    pub(crate) solved_layout: SolvedLayout<T>,
-    /// The list of variables that has been added to the solver
-    pub(crate) edit_variable_cache: EditVariableCache,
+    pub(crate) solved_layout: SolvedLayout,
     /// The cache of the previous frames DOM tree
     pub(crate) dom_tree_cache: DomTreeCache,
 }
 
-impl<T: Layout> UiSolver<T> {
-    pub(crate) fn query_bounds_of_rect(&self, rect_id: NodeId) {
-        // TODO: After solving the UI, use this function to get the actual coordinates of an item in the UI.
-        // This function should cache values accordingly
+impl UiSolver {
+    /// Returns the bounds of the rectangle with the given `NodeId`, as solved in the last frame
+    pub(crate) fn query_bounds_of_rect(&self, rect_id: NodeId) -> Option<LayoutRect> {
+        self.solved_layout.get_bounds(rect_id)
     }
 }
 
@@ -687,20 +674,11 @@ impl<T: Layout> Window<T> {
         let document_id = api.add_document(framebuffer_size, 0);
         let epoch = Epoch(0);
         let pipeline_id = PipelineId(0, 0);
-        let layout_size = framebuffer_size.to_f32() / TypedScale::new(device_pixel_ratio as f32);
 
 /*
         let (sender, receiver) = channel();
         let thread = Builder::new().name(options.title.clone()).spawn(move || Self::handle_event(receiver))?;
 */
-        let mut solver = Solver::new();
-
-        let window_dim = WindowDimensions::new_from_layout_size(layout_size);
-
-        solver.add_edit_variable(window_dim.width_var, STRONG).unwrap();
-        solver.add_edit_variable(window_dim.height_var, STRONG).unwrap();
-        solver.suggest_value(window_dim.width_var, window_dim.width() as f64).unwrap();
-        solver.suggest_value(window_dim.height_var, window_dim.height() as f64).unwrap();
 
         renderer.set_external_image_handler(Box::new(Compositor::default()));
         
@@ -718,9 +696,7 @@ impl<T: Layout> Window<T> {
                 last_display_list_builder: BuiltDisplayList::default(),
             },
             solver: UiSolver {
-                solver: solver,
                 solved_layout: SolvedLayout::empty(),
-                edit_variable_cache: EditVariableCache::empty(),
                 dom_tree_cache: DomTreeCache::empty(),
             }
         };
@@ -754,10 +730,11 @@ impl<T: Layout> Window<T> {
             old_state.title = new_state.title;
         }
 
-        if old_state.mouse_state.mouse_cursor_type != new_state.mouse_state.mouse_cursor_type {
-            window.set_cursor(new_state.mouse_state.mouse_cursor_type);
-            old_state.mouse_state.mouse_cursor_type = new_state.mouse_state.mouse_cursor_type;
-        }
+        // The mouse cursor is set in `update_mouse_cursor`, since the
+        // `cursor` of the hovered node overrides `mouse_cursor_type`
+
+        // The @media queries are re-evaluated by the app
+        old_state.color_scheme = new_state.color_scheme;
 
         if old_state.is_maximized != new_state.is_maximized {
             window.set_maximized(new_state.is_maximized);
@@ -798,6 +775,21 @@ impl<T: Layout> Window<T> {
         }
     }
 
+    /// Shows the `cursor` of the top-most node under the mouse. If the node has no `cursor`
+    /// (or `cursor: auto`) or the mouse has left the window, the `user_cursor`
+    /// (the `mouse_cursor_type` of the users `WindowState`) is shown instead
+    pub(crate) fn update_mouse_cursor(&mut self, user_cursor: MouseCursor) {
+        let solved_layout = &self.solver.solved_layout;
+        let new_cursor = self.state.mouse_state.cursor_pos
+            .and_then(|pos| solved_layout.get_cursor(LayoutPoint::new(pos.x as f32, pos.y as f32)))
+            .and_then(style_cursor_to_mouse_cursor)
+            .unwrap_or(user_cursor);
+
+        let old_cursor = &mut self.state.mouse_state.mouse_cursor_type;
+        window_state::update_mouse_cursor(self.display.gl_window().window(), old_cursor, &new_cursor);
+        *old_cursor = new_cursor;
+    }
+
     pub(crate) fn update_from_external_window_state(&mut self, frame_event_info: &mut FrameEventInfo) {
         use webrender::api::{DeviceUintSize, WorldPoint, LayoutSize};
 
@@ -10,6 +10,7 @@ use std::collections::HashSet;
 use {
     dom::On,
     menu::{ApplicationMenu, ContextMenu},
+    css_parser::StyleCursor,
 };
 
 const DEFAULT_TITLE: &str = "Azul App";
@@ -55,7 +56,8 @@ impl KeyboardState {
 #[derive(Debug, Copy, Clone)]
 pub struct MouseState
 {
-    /// Current mouse cursor type
+    /// Current mouse cursor type. The `cursor` CSS property of the node under
+    /// the mouse takes precedence over this cursor
     pub mouse_cursor_type: MouseCursor,
     //// Where is the mouse cursor currently? Set to `None` if the window is not focused
     pub cursor_pos: Option<LogicalPosition>,
@@ -118,6 +120,22 @@ pub struct WindowState
     pub is_transparent: bool,
     /// Is the window always on top?
     pub is_always_on_top: bool,
+    /// The color scheme that the user prefers, used to evaluate
+    /// `@media (prefers-color-scheme: dark)` queries. Light by default.
+    pub color_scheme: ColorScheme,
+}
+
+/// Light or dark theme, see `WindowState::color_scheme`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ColorScheme {
+    Light,
+    Dark,
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        ColorScheme::Light
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -161,6 +179,7 @@ impl Default for WindowState {
             is_visible: true,
             is_transparent: false,
             is_always_on_top: false,
+            color_scheme: ColorScheme::default(),
         }
     }
 }
@@ -329,12 +348,56 @@ impl WindowState
     }
 }
 
-fn update_mouse_cursor(window: &Window, old: &MouseCursor, new: &MouseCursor) {
+pub(crate) fn update_mouse_cursor(window: &Window, old: &MouseCursor, new: &MouseCursor) {
     if *old != *new {
         window.set_cursor(*new);
     }
 }
 
+/// Returns the `MouseCursor` for the value of the `cursor` CSS property,
+/// `None` for `cursor: auto` (in which case the cursor of the `MouseState` is used)
+pub(crate) fn style_cursor_to_mouse_cursor(cursor: StyleCursor) -> Option<MouseCursor> {
+    use css_parser::StyleCursor::*;
+    let mouse_cursor = match cursor {
+        Auto => return None,
+        Default => MouseCursor::Default,
+        Pointer => MouseCursor::Hand,
+        Text => MouseCursor::Text,
+        VerticalText => MouseCursor::VerticalText,
+        Move => MouseCursor::Move,
+        Crosshair => MouseCursor::Crosshair,
+        Wait => MouseCursor::Wait,
+        Progress => MouseCursor::Progress,
+        Help => MouseCursor::Help,
+        ContextMenu => MouseCursor::ContextMenu,
+        Cell => MouseCursor::Cell,
+        Alias => MouseCursor::Alias,
+        Copy => MouseCursor::Copy,
+        NoDrop => MouseCursor::NoDrop,
+        NotAllowed => MouseCursor::NotAllowed,
+        Grab => MouseCursor::Grab,
+        Grabbing => MouseCursor::Grabbing,
+        AllScroll => MouseCursor::AllScroll,
+        ZoomIn => MouseCursor::ZoomIn,
+        ZoomOut => MouseCursor::ZoomOut,
+        NResize => MouseCursor::NResize,
+        EResize => MouseCursor::EResize,
+        SResize => MouseCursor::SResize,
+        WResize => MouseCursor::WResize,
+        NeResize => MouseCursor::NeResize,
+        NwResize => MouseCursor::NwResize,
+        SeResize => MouseCursor::SeResize,
+        SwResize => MouseCursor::SwResize,
+        EwResize => MouseCursor::EwResize,
+        NsResize => MouseCursor::NsResize,
+        NeswResize => MouseCursor::NeswResize,
+        NwseResize => MouseCursor::NwseResize,
+        ColResize => MouseCursor::ColResize,
+        RowResize => MouseCursor::RowResize,
+    };
+    Some(mouse_cursor)
+}
+
 fn virtual_key_code_to_char(code: VirtualKeyCode) -> Option<char> {
     use glium::glutin::VirtualKeyCode::*;
     match code {